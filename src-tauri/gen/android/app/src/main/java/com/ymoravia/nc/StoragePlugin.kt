@@ -0,0 +1,348 @@
+package com.ymoravia.nc
+
+import android.app.Activity
+import android.content.Intent
+import android.database.Cursor
+import android.net.Uri
+import android.provider.DocumentsContract
+import android.provider.DocumentsContract.Document
+import android.webkit.MimeTypeMap
+import androidx.activity.result.ActivityResult
+import app.tauri.annotation.ActivityCallback
+import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.JSArray
+import app.tauri.plugin.JSObject
+import app.tauri.plugin.Plugin
+import java.io.File
+import java.io.FileNotFoundException
+import java.io.InputStream
+import java.io.OutputStream
+
+// Storage Access Framework side of src/api/storage/android.rs. A Doc is a
+// document below a granted tree (path = "/"-joined names under its top
+// document) or, without a tree, a plain file path.
+
+@InvokeArg
+class Doc {
+    var treeUri: String? = null
+    lateinit var path: String
+}
+
+@InvokeArg
+class OnDocArgs {
+    lateinit var doc: Doc
+}
+
+@InvokeArg
+class TransferArgs {
+    lateinit var src: Doc
+    lateinit var destDir: Doc
+}
+
+@InvokeArg
+class RenameArgs {
+    lateinit var from: Doc
+    lateinit var to: Doc
+}
+
+@InvokeArg
+class RequestAccessArgs {
+    var initialPath: String? = null
+}
+
+private const val EXTERNAL_STORAGE = "com.android.externalstorage.documents"
+private const val PRIMARY = "/storage/emulated/0"
+
+@TauriPlugin
+class StoragePlugin(private val activity: Activity) : Plugin(activity) {
+    private val resolver get() = activity.contentResolver
+
+    /// A file or folder on either side, with just what copy/move/delete need
+    private sealed class Node {
+        abstract val name: String
+        abstract val isDir: Boolean
+    }
+
+    private class TreeNode(
+        val tree: Uri,
+        val docId: String,
+        override val name: String,
+        val mime: String,
+        val size: Long,
+        val modified: Long?,
+    ) : Node() {
+        override val isDir get() = mime == Document.MIME_TYPE_DIR
+        val uri: Uri get() = DocumentsContract.buildDocumentUriUsingTree(tree, docId)
+    }
+
+    private class FileNode(val file: File) : Node() {
+        override val name: String get() = file.name
+        override val isDir get() = file.isDirectory
+    }
+
+    private fun Cursor.treeNode(tree: Uri): TreeNode = TreeNode(
+        tree,
+        docId = getString(0),
+        name = getString(1),
+        mime = getString(2) ?: "application/octet-stream",
+        size = if (isNull(3)) 0 else getLong(3),
+        modified = if (isNull(4)) null else getLong(4),
+    )
+
+    private val columns = arrayOf(
+        Document.COLUMN_DOCUMENT_ID,
+        Document.COLUMN_DISPLAY_NAME,
+        Document.COLUMN_MIME_TYPE,
+        Document.COLUMN_SIZE,
+        Document.COLUMN_LAST_MODIFIED,
+    )
+
+    private fun children(node: Node): List<Node> = when (node) {
+        is FileNode -> node.file.listFiles()?.map { FileNode(it) } ?: emptyList()
+        is TreeNode -> {
+            val uri = DocumentsContract.buildChildDocumentsUriUsingTree(node.tree, node.docId)
+            resolver.query(uri, columns, null, null, null)?.use { c ->
+                generateSequence { if (c.moveToNext()) c.treeNode(node.tree) else null }.toList()
+            } ?: emptyList()
+        }
+    }
+
+    private fun treeTop(tree: Uri): TreeNode {
+        val docId = DocumentsContract.getTreeDocumentId(tree)
+        val uri = DocumentsContract.buildDocumentUriUsingTree(tree, docId)
+        return resolver.query(uri, columns, null, null, null)?.use { c ->
+            if (c.moveToFirst()) c.treeNode(tree) else null
+        } ?: throw FileNotFoundException(tree.toString())
+    }
+
+    private fun names(path: String) = path.split('/').filter { it.isNotEmpty() }
+
+    /// The node `doc` names, or null when it doesn't exist
+    private fun find(doc: Doc): Node? {
+        val tree = doc.treeUri ?: return File(doc.path).takeIf { it.exists() }?.let { FileNode(it) }
+        var node: Node = treeTop(Uri.parse(tree))
+        for (name in names(doc.path)) {
+            node = children(node).firstOrNull { it.name == name } ?: return null
+        }
+        return node
+    }
+
+    private fun need(doc: Doc): Node = find(doc) ?: throw FileNotFoundException(doc.path)
+
+    private fun createChild(parent: Node, name: String, isDir: Boolean, mime: String?): Node = when (parent) {
+        is FileNode -> File(parent.file, name).also { if (isDir) it.mkdirs() else it.createNewFile() }.let { FileNode(it) }
+        is TreeNode -> {
+            val type = if (isDir) Document.MIME_TYPE_DIR else mime ?: mimeOf(name)
+            val uri = DocumentsContract.createDocument(resolver, parent.uri, type, name)
+                ?: throw FileNotFoundException("can't create $name")
+            TreeNode(parent.tree, DocumentsContract.getDocumentId(uri), name, type, 0, null)
+        }
+    }
+
+    private fun mimeOf(name: String): String =
+        MimeTypeMap.getSingleton().getMimeTypeFromExtension(name.substringAfterLast('.', "").lowercase())
+            ?: "application/octet-stream"
+
+    private fun input(node: Node): InputStream = when (node) {
+        is FileNode -> node.file.inputStream()
+        is TreeNode -> resolver.openInputStream(node.uri) ?: throw FileNotFoundException(node.name)
+    }
+
+    private fun output(node: Node): OutputStream = when (node) {
+        is FileNode -> node.file.outputStream()
+        is TreeNode -> resolver.openOutputStream(node.uri, "wt") ?: throw FileNotFoundException(node.name)
+    }
+
+    private fun delete(node: Node) {
+        when (node) {
+            is FileNode -> if (!node.file.deleteRecursively()) throw SecurityException("can't delete ${node.name}")
+            is TreeNode -> if (!DocumentsContract.deleteDocument(resolver, node.uri)) throw SecurityException("can't delete ${node.name}")
+        }
+    }
+
+    /// Copy `src` into `destDir`, replacing an entry of the same name
+    private fun copy(src: Node, destDir: Node) {
+        children(destDir).firstOrNull { it.name == src.name }?.let { delete(it) }
+        val target = createChild(destDir, src.name, src.isDir, (src as? TreeNode)?.mime)
+        if (src.isDir) {
+            children(src).forEach { copy(it, target) }
+        } else {
+            input(src).use { i -> output(target).use { o -> i.copyTo(o) } }
+        }
+    }
+
+    private fun parentDoc(doc: Doc): Doc = Doc().also {
+        it.treeUri = doc.treeUri
+        it.path = names(doc.path).dropLast(1).joinToString("/").let { p ->
+            if (doc.treeUri == null) File(doc.path).parent ?: "/" else p
+        }
+    }
+
+    /// Run off the main thread; failures reject with the code the Rust side maps
+    private fun background(invoke: Invoke, block: () -> JSObject) {
+        Thread {
+            try {
+                invoke.resolve(block())
+            } catch (e: FileNotFoundException) {
+                invoke.reject(e.message, "not_found")
+            } catch (e: SecurityException) {
+                invoke.reject(e.message, "permission_denied")
+            } catch (e: UnsupportedOperationException) {
+                invoke.reject(e.message, "unsupported")
+            } catch (e: Exception) {
+                invoke.reject(e.message ?: e.toString(), "io")
+            }
+        }.start()
+    }
+
+    @Command
+    fun persistedTrees(invoke: Invoke) {
+        val trees = JSArray()
+        resolver.persistedUriPermissions.filter { it.isWritePermission }.forEach {
+            trees.put(JSObject().apply {
+                put("treeUri", it.uri.toString())
+                put("treeDocId", DocumentsContract.getTreeDocumentId(it.uri))
+            })
+        }
+        invoke.resolve(JSObject().apply { put("trees", trees) })
+    }
+
+    @Command
+    fun list(invoke: Invoke) {
+        val args = invoke.parseArgs(OnDocArgs::class.java)
+        background(invoke) {
+            val entries = JSArray()
+            for (child in children(need(args.doc))) {
+                entries.put(JSObject().apply {
+                    put("name", child.name)
+                    put("isDir", child.isDir)
+                    when (child) {
+                        is TreeNode -> {
+                            put("size", child.size)
+                            put("modified", child.modified)
+                        }
+                        is FileNode -> {
+                            put("size", child.file.length())
+                            put("modified", child.file.lastModified())
+                        }
+                    }
+                })
+            }
+            JSObject().apply { put("entries", entries) }
+        }
+    }
+
+    @Command
+    fun mkdirs(invoke: Invoke) {
+        val args = invoke.parseArgs(OnDocArgs::class.java)
+        background(invoke) {
+            val tree = args.doc.treeUri
+            if (tree == null) {
+                File(args.doc.path).mkdirs()
+            } else {
+                var node: Node = treeTop(Uri.parse(tree))
+                for (name in names(args.doc.path)) {
+                    node = children(node).firstOrNull { it.name == name } ?: createChild(node, name, true, null)
+                }
+            }
+            JSObject()
+        }
+    }
+
+    @Command
+    fun copy(invoke: Invoke) {
+        val args = invoke.parseArgs(TransferArgs::class.java)
+        background(invoke) {
+            copy(need(args.src), need(args.destDir))
+            JSObject()
+        }
+    }
+
+    @Command
+    fun move(invoke: Invoke) {
+        val args = invoke.parseArgs(TransferArgs::class.java)
+        background(invoke) {
+            val src = need(args.src)
+            val dest = need(args.destDir)
+            val sameTree = src is TreeNode && dest is TreeNode && args.src.treeUri == args.destDir.treeUri
+            val parent = if (sameTree) need(parentDoc(args.src)) as TreeNode else null
+            val moved = parent != null && try {
+                DocumentsContract.moveDocument(resolver, (src as TreeNode).uri, parent.uri, (dest as TreeNode).uri) != null
+            } catch (e: UnsupportedOperationException) {
+                false
+            }
+            if (!moved) {
+                copy(src, dest)
+                delete(src)
+            }
+            JSObject()
+        }
+    }
+
+    @Command
+    fun delete(invoke: Invoke) {
+        val args = invoke.parseArgs(OnDocArgs::class.java)
+        background(invoke) {
+            delete(need(args.doc))
+            JSObject()
+        }
+    }
+
+    @Command
+    fun rename(invoke: Invoke) {
+        val args = invoke.parseArgs(RenameArgs::class.java)
+        background(invoke) {
+            val from = need(args.from)
+            val newName = names(args.to.path).lastOrNull() ?: File(args.to.path).name
+            val sameParent = args.from.treeUri == args.to.treeUri &&
+                parentDoc(args.from).path == parentDoc(args.to).path
+            when {
+                !sameParent -> throw UnsupportedOperationException("rename can't move to another folder")
+                from is FileNode -> if (!from.file.renameTo(File(args.to.path))) throw SecurityException("can't rename ${from.name}")
+                from is TreeNode -> DocumentsContract.renameDocument(resolver, from.uri, newName)
+                    ?: throw SecurityException("can't rename ${from.name}")
+            }
+            JSObject()
+        }
+    }
+
+    @Command
+    fun requestAccess(invoke: Invoke) {
+        val args = invoke.parseArgs(RequestAccessArgs::class.java)
+        val intent = Intent(Intent.ACTION_OPEN_DOCUMENT_TREE).apply {
+            addFlags(
+                Intent.FLAG_GRANT_READ_URI_PERMISSION or
+                    Intent.FLAG_GRANT_WRITE_URI_PERMISSION or
+                    Intent.FLAG_GRANT_PERSISTABLE_URI_PERMISSION
+            )
+            initialUri(args.initialPath)?.let { putExtra(DocumentsContract.EXTRA_INITIAL_URI, it) }
+        }
+        startActivityForResult(invoke, intent, "accessResult")
+    }
+
+    /// The picker can only open at a document of the external storage provider
+    private fun initialUri(path: String?): Uri? {
+        val rel = path?.removePrefix(PRIMARY)?.takeIf { it != path } ?: return null
+        return DocumentsContract.buildDocumentUri(EXTERNAL_STORAGE, "primary:" + rel.trim('/'))
+    }
+
+    @ActivityCallback
+    fun accessResult(invoke: Invoke, result: ActivityResult) {
+        val uri = result.data?.data
+        if (result.resultCode != Activity.RESULT_OK || uri == null) {
+            invoke.reject("no folder was picked", "cancelled")
+            return
+        }
+        resolver.takePersistableUriPermission(
+            uri,
+            Intent.FLAG_GRANT_READ_URI_PERMISSION or Intent.FLAG_GRANT_WRITE_URI_PERMISSION
+        )
+        invoke.resolve(JSObject().apply {
+            put("treeUri", uri.toString())
+            put("treeDocId", DocumentsContract.getTreeDocumentId(uri))
+        })
+    }
+}
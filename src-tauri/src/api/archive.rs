@@ -0,0 +1,391 @@
+// src/api/archive.rs
+use crate::api::{
+    sandbox,
+    types::{ArchiveEntry, ArchiveProgressEvent},
+};
+use once_cell::sync::Lazy;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{self, BufReader, Read},
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+use tauri::{AppHandle, Emitter, Window};
+
+/// How many archive indexes are kept around
+const INDEX_CACHE_CAP: usize = 8;
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+struct CachedIndex {
+    mtime: SystemTime,
+    last_used: Instant,
+    entries: Arc<Vec<ArchiveEntry>>,
+}
+
+static INDEX_CACHE: Lazy<Mutex<HashMap<PathBuf, CachedIndex>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn kind_of(path: &Path) -> Result<Kind, String> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if name.ends_with(".zip") {
+        Ok(Kind::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Ok(Kind::TarGz)
+    } else if name.ends_with(".tar") {
+        Ok(Kind::Tar)
+    } else {
+        Err("unsupported archive type".into())
+    }
+}
+
+/// "./a\\b/" -> "a/b"
+fn normalize_inner(name: &str) -> String {
+    let s = name.replace('\\', "/");
+    let s = s.trim_start_matches("./").trim_matches('/');
+    s.to_string()
+}
+
+fn parent_of(inner: &str) -> &str {
+    inner.rsplit_once('/').map(|(p, _)| p).unwrap_or("")
+}
+
+fn base_name(inner: &str) -> &str {
+    inner.rsplit_once('/').map(|(_, n)| n).unwrap_or(inner)
+}
+
+fn fmt_unix_time(secs: u64) -> Option<String> {
+    let dt = chrono::DateTime::from_timestamp(secs as i64, 0)?;
+    Some(
+        dt.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+    )
+}
+
+fn open_tar(path: &Path, kind: Kind) -> Result<tar::Archive<Box<dyn Read>>, String> {
+    let file = BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+    let reader: Box<dyn Read> = match kind {
+        Kind::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
+        _ => Box::new(file),
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+fn read_index(path: &Path, kind: Kind) -> Result<Vec<ArchiveEntry>, String> {
+    let mut raw: Vec<ArchiveEntry> = Vec::new();
+    let mk = |inner: String, is_dir, size, modified, encrypted| ArchiveEntry {
+        name: base_name(&inner).to_string(),
+        inner_path: inner,
+        is_dir,
+        size,
+        modified,
+        encrypted,
+    };
+
+    match kind {
+        Kind::Zip => {
+            let file = File::open(path).map_err(|e| e.to_string())?;
+            let mut zip = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+            for i in 0..zip.len() {
+                // raw access works for encrypted entries too
+                let f = zip.by_index_raw(i).map_err(|e| e.to_string())?;
+                let inner = normalize_inner(f.name());
+                if inner.is_empty() {
+                    continue;
+                }
+                let modified = f.last_modified().map(|d| {
+                    format!(
+                        "{:04}-{:02}-{:02} {:02}:{:02}",
+                        d.year(),
+                        d.month(),
+                        d.day(),
+                        d.hour(),
+                        d.minute()
+                    )
+                });
+                raw.push(mk(inner, f.is_dir(), f.size(), modified, f.encrypted()));
+            }
+        }
+        Kind::Tar | Kind::TarGz => {
+            let mut ar = open_tar(path, kind)?;
+            for ent in ar.entries().map_err(|e| e.to_string())? {
+                let ent = ent.map_err(|e| e.to_string())?;
+                let hdr = ent.header();
+                let inner =
+                    normalize_inner(&ent.path().map_err(|e| e.to_string())?.to_string_lossy());
+                if inner.is_empty() {
+                    continue;
+                }
+                let is_dir = hdr.entry_type().is_dir();
+                let size = if is_dir { 0 } else { hdr.size().unwrap_or(0) };
+                let modified = hdr.mtime().ok().and_then(fmt_unix_time);
+                raw.push(mk(inner, is_dir, size, modified, false));
+            }
+        }
+    }
+
+    // Many archives omit explicit directory entries; synthesize the parents
+    let mut dirs: HashSet<String> = raw
+        .iter()
+        .filter(|e| e.is_dir)
+        .map(|e| e.inner_path.clone())
+        .collect();
+    let mut implied = Vec::new();
+    for e in &raw {
+        let mut p = parent_of(&e.inner_path);
+        while !p.is_empty() && dirs.insert(p.to_string()) {
+            implied.push(mk(p.to_string(), true, 0, None, false));
+            p = parent_of(p);
+        }
+    }
+    raw.extend(implied);
+    Ok(raw)
+}
+
+/// Index for `path`, rebuilt only when the archive's mtime changed.
+fn index_for(path: &Path) -> Result<Arc<Vec<ArchiveEntry>>, String> {
+    let kind = kind_of(path)?;
+    let mtime = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|e| e.to_string())?;
+
+    if let Ok(mut cache) = INDEX_CACHE.lock() {
+        if let Some(c) = cache.get_mut(path) {
+            if c.mtime == mtime {
+                c.last_used = Instant::now();
+                return Ok(c.entries.clone());
+            }
+        }
+    }
+
+    let entries = Arc::new(read_index(path, kind)?);
+
+    if let Ok(mut cache) = INDEX_CACHE.lock() {
+        if cache.len() >= INDEX_CACHE_CAP && !cache.contains_key(path) {
+            if let Some(oldest) = cache
+                .iter()
+                .min_by_key(|(_, c)| c.last_used)
+                .map(|(k, _)| k.clone())
+            {
+                cache.remove(&oldest);
+            }
+        }
+        cache.insert(
+            path.to_path_buf(),
+            CachedIndex {
+                mtime,
+                last_used: Instant::now(),
+                entries: entries.clone(),
+            },
+        );
+    }
+    Ok(entries)
+}
+
+#[tauri::command]
+pub async fn list_archive(
+    app: AppHandle,
+    path: String,
+    inner_path: Option<String>,
+) -> Result<Vec<ArchiveEntry>, String> {
+    sandbox::check(&app, &path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let index = index_for(Path::new(&path))?;
+        let level = normalize_inner(inner_path.as_deref().unwrap_or(""));
+
+        let mut out: Vec<ArchiveEntry> = index
+            .iter()
+            .filter(|e| parent_of(&e.inner_path) == level)
+            .cloned()
+            .collect();
+        out.sort_by(|a, b| {
+            b.is_dir
+                .cmp(&a.is_dir)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        Ok(out)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Where `inner` lands under `dest`, or None when it isn't selected.
+/// Selected items are extracted relative to their own parent, and any path
+/// that would escape `dest` (zip-slip) is rejected.
+fn target_for(inner: &str, selected: &[String], dest: &Path) -> Option<Result<PathBuf, String>> {
+    let sel = selected
+        .iter()
+        .find(|s| inner == s.as_str() || inner.starts_with(&format!("{}/", s)))?;
+    let parent = parent_of(sel);
+    let rel = if parent.is_empty() {
+        inner
+    } else {
+        inner.strip_prefix(parent)?.trim_start_matches('/')
+    };
+
+    let rel_path = Path::new(rel);
+    if !rel_path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Some(Err(format!("refusing unsafe entry path: {}", inner)));
+    }
+    Some(Ok(dest.join(rel_path)))
+}
+
+fn write_entry(reader: &mut dyn Read, target: &Path) -> Result<(), String> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut out = File::create(target).map_err(|e| e.to_string())?;
+    io::copy(reader, &mut out).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Reports to the `label` window only
+struct Progress<'a> {
+    app: &'a AppHandle,
+    label: &'a str,
+    op_id: &'a str,
+    done: u64,
+    total: u64,
+    last_emit: Instant,
+}
+
+impl Progress<'_> {
+    fn step(&mut self, current: &str) {
+        self.done += 1;
+        if self.last_emit.elapsed() >= Duration::from_millis(100) || self.done == self.total {
+            let _ = self.app.emit_to(
+                self.label,
+                "archive:progress",
+                ArchiveProgressEvent {
+                    op_id: self.op_id.to_string(),
+                    done: self.done,
+                    total: self.total,
+                    current: current.to_string(),
+                },
+            );
+            self.last_emit = Instant::now();
+        }
+    }
+}
+
+fn extract(
+    app: &AppHandle,
+    label: &str,
+    archive: &Path,
+    selected: &[String],
+    dest: &Path,
+    op_id: &str,
+) -> Result<u64, String> {
+    let kind = kind_of(archive)?;
+    let index = index_for(archive)?;
+    let total = index
+        .iter()
+        .filter(|e| !e.is_dir && target_for(&e.inner_path, selected, dest).is_some())
+        .count() as u64;
+    let mut progress = Progress {
+        app,
+        label,
+        op_id,
+        done: 0,
+        total,
+        last_emit: Instant::now(),
+    };
+
+    fs::create_dir_all(dest).map_err(|e| e.to_string())?;
+
+    match kind {
+        Kind::Zip => {
+            let file = File::open(archive).map_err(|e| e.to_string())?;
+            let mut zip = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+            for i in 0..zip.len() {
+                let (inner, is_dir, encrypted) = {
+                    let f = zip.by_index_raw(i).map_err(|e| e.to_string())?;
+                    (normalize_inner(f.name()), f.is_dir(), f.encrypted())
+                };
+                let Some(target) = target_for(&inner, selected, dest) else {
+                    continue;
+                };
+                let target = target?;
+                if is_dir {
+                    fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+                    continue;
+                }
+                if encrypted {
+                    return Err(format!("entry is encrypted: {}", inner));
+                }
+                let mut f = zip.by_index(i).map_err(|e| e.to_string())?;
+                write_entry(&mut f, &target)?;
+                progress.step(&inner);
+            }
+        }
+        Kind::Tar | Kind::TarGz => {
+            let mut ar = open_tar(archive, kind)?;
+            for ent in ar.entries().map_err(|e| e.to_string())? {
+                let mut ent = ent.map_err(|e| e.to_string())?;
+                let inner =
+                    normalize_inner(&ent.path().map_err(|e| e.to_string())?.to_string_lossy());
+                let Some(target) = target_for(&inner, selected, dest) else {
+                    continue;
+                };
+                let target = target?;
+                let ty = ent.header().entry_type();
+                if ty.is_dir() {
+                    fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+                } else if ty.is_file() {
+                    write_entry(&mut ent, &target)?;
+                    progress.step(&inner);
+                }
+                // links and special files are skipped on purpose
+            }
+        }
+    }
+    Ok(progress.done)
+}
+
+/// Extract only `entries` (inner paths, files or folders) into `dest_dir`.
+/// Emits `archive:progress` keyed by op_id to the calling window; resolves
+/// with the number of files written.
+#[tauri::command]
+pub async fn extract_archive_entries(
+    app: AppHandle,
+    window: Window,
+    path: String,
+    entries: Vec<String>,
+    dest_dir: String,
+    op_id: String,
+) -> Result<u64, String> {
+    sandbox::check(&app, &path)?;
+    sandbox::check(&app, &dest_dir)?;
+    let label = window.label().to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let selected: Vec<String> = entries
+            .iter()
+            .map(|e| normalize_inner(e))
+            .filter(|e| !e.is_empty())
+            .collect();
+        extract(
+            &app,
+            &label,
+            Path::new(&path),
+            &selected,
+            Path::new(&dest_dir),
+            &op_id,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
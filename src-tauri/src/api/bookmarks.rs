@@ -0,0 +1,212 @@
+// src/api/bookmarks.rs
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::Local;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::api::{
+    sandbox,
+    settings::{load_json, save_json},
+    types::{ApiError, Bookmark},
+};
+
+pub struct BookmarksState {
+    file: Option<PathBuf>,
+    list: Mutex<Vec<Bookmark>>,
+}
+
+/// Load `<app data>/bookmarks.json` into managed state (empty when missing)
+pub fn init(app: &AppHandle) {
+    let file = app
+        .path()
+        .app_data_dir()
+        .ok()
+        .map(|d| d.join("bookmarks.json"));
+    let list = file.as_deref().and_then(load_json).unwrap_or_default();
+    app.manage(BookmarksState {
+        file,
+        list: Mutex::new(list),
+    });
+}
+
+fn icon_for(app: &AppHandle, canonical: &Path) -> &'static str {
+    let home = app.path().home_dir().and_then(|h| Ok(fs::canonicalize(h)?));
+    if home.is_ok_and(|h| h == canonical) {
+        "home"
+    } else if canonical.parent().is_none() {
+        "drive"
+    } else if canonical.is_dir() {
+        "folder"
+    } else {
+        "file"
+    }
+}
+
+/// Add `entry`, or relabel the bookmark already there for its canonical path
+fn upsert(list: &mut Vec<Bookmark>, entry: Bookmark) {
+    match list.iter_mut().find(|b| b.canonical == entry.canonical) {
+        Some(b) => b.label = entry.label,
+        None => list.push(entry),
+    }
+}
+
+/// `ids` first, in that order; the rest after them as they were
+fn reorder(list: &mut Vec<Bookmark>, ids: &[String]) -> Result<(), ApiError> {
+    if let Some(unknown) = ids.iter().find(|id| !list.iter().any(|b| &b.id == *id)) {
+        return Err(ApiError::invalid_input(format!(
+            "unknown bookmark: {}",
+            unknown
+        )));
+    }
+    let mut rest = std::mem::take(list);
+    for id in ids {
+        if let Some(i) = rest.iter().position(|b| &b.id == id) {
+            list.push(rest.remove(i));
+        }
+    }
+    list.extend(rest);
+    Ok(())
+}
+
+/// Flag the bookmarks whose paths are gone; they stay listed so the user can
+/// fix or remove them
+fn with_status(list: &[Bookmark]) -> Vec<Bookmark> {
+    list.iter()
+        .cloned()
+        .map(|mut b| {
+            b.missing = !Path::new(&b.canonical).exists();
+            b
+        })
+        .collect()
+}
+
+/// Apply `edit`, save, and tell every window (`bookmarks:changed`)
+fn change(
+    app: &AppHandle,
+    state: &BookmarksState,
+    edit: impl FnOnce(&mut Vec<Bookmark>) -> Result<(), ApiError>,
+) -> Result<Vec<Bookmark>, ApiError> {
+    let list = {
+        let mut current = state.list.lock().map_err(|e| e.to_string())?;
+        let mut next = current.clone();
+        edit(&mut next)?;
+        if let Some(file) = &state.file {
+            save_json(file, &next)?;
+        }
+        *current = next.clone();
+        next
+    };
+    let shown = with_status(&list);
+    let _ = app.emit("bookmarks:changed", shown.clone());
+    Ok(shown)
+}
+
+#[tauri::command]
+pub fn list_bookmarks(state: State<'_, BookmarksState>) -> Result<Vec<Bookmark>, ApiError> {
+    Ok(with_status(&state.list.lock().map_err(|e| e.to_string())?))
+}
+
+/// Bookmark `path` (it must exist); an empty `label` takes the folder name.
+/// Bookmarking a path again, however spelled, only changes the label.
+#[tauri::command]
+pub fn add_bookmark(
+    app: AppHandle,
+    state: State<'_, BookmarksState>,
+    path: String,
+    label: String,
+) -> Result<Vec<Bookmark>, ApiError> {
+    sandbox::check(&app, &path)?;
+    let canonical = fs::canonicalize(&path).map_err(|e| ApiError::from(e).with_path(&path))?;
+    let label = match label.trim() {
+        "" => canonical
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| canonical.to_string_lossy().to_string()),
+        l => l.to_string(),
+    };
+    let entry = Bookmark {
+        id: blake3::hash(canonical.to_string_lossy().as_bytes()).to_hex()[..16].to_string(),
+        icon: icon_for(&app, &canonical).to_string(),
+        canonical: canonical.to_string_lossy().to_string(),
+        path,
+        label,
+        created_at: Local::now().to_rfc3339(),
+        missing: false,
+    };
+    change(&app, &state, |list| {
+        upsert(list, entry);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn remove_bookmark(
+    app: AppHandle,
+    state: State<'_, BookmarksState>,
+    id: String,
+) -> Result<Vec<Bookmark>, ApiError> {
+    change(&app, &state, |list| {
+        let before = list.len();
+        list.retain(|b| b.id != id);
+        if list.len() == before {
+            return Err(ApiError::invalid_input(format!("unknown bookmark: {}", id)));
+        }
+        Ok(())
+    })
+}
+
+/// Put the bookmarks in the order of `ids`; any left out keep their relative
+/// order after those
+#[tauri::command]
+pub fn reorder_bookmarks(
+    app: AppHandle,
+    state: State<'_, BookmarksState>,
+    ids: Vec<String>,
+) -> Result<Vec<Bookmark>, ApiError> {
+    change(&app, &state, |list| reorder(list, &ids))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(id: &str, label: &str) -> Bookmark {
+        Bookmark {
+            id: id.into(),
+            path: format!("/b/{}", id),
+            canonical: format!("/b/{}", id),
+            label: label.into(),
+            icon: "folder".into(),
+            created_at: String::new(),
+            missing: false,
+        }
+    }
+
+    fn ids(list: &[Bookmark]) -> Vec<&str> {
+        list.iter().map(|b| b.id.as_str()).collect()
+    }
+
+    #[test]
+    fn same_path_again_only_relabels() {
+        let mut list = vec![bookmark("a", "old"), bookmark("b", "b")];
+        let mut again = bookmark("a", "new");
+        again.path = "/b/../b/a".into();
+        upsert(&mut list, again);
+        assert_eq!(ids(&list), ["a", "b"]);
+        assert_eq!(list[0].label, "new");
+        assert_eq!(list[0].path, "/b/a");
+    }
+
+    #[test]
+    fn reorder_keeps_unlisted_after() {
+        let mut list = vec![bookmark("a", ""), bookmark("b", ""), bookmark("c", "")];
+        reorder(&mut list, &["c".into(), "a".into()]).unwrap();
+        assert_eq!(ids(&list), ["c", "a", "b"]);
+        assert!(reorder(&mut list, &["x".into()]).is_err());
+        assert_eq!(ids(&list), ["c", "a", "b"]);
+    }
+}
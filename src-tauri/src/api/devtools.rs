@@ -1,16 +1,54 @@
 // src/api/devtools.rs
-use tauri::Manager;
+use tauri::{AppHandle, Manager, WebviewWindow};
 
+use crate::api::{settings, types::ApiError};
+
+/// Set to 1 to allow devtools in a release build (with the `devtools` feature)
+const ENV_SWITCH: &str = "COPYCUT_DEVTOOLS";
+
+/// Debug builds always; release builds when the user flipped the switch
+#[cfg_attr(not(any(debug_assertions, feature = "devtools")), allow(dead_code))]
+fn allowed(app: &AppHandle) -> bool {
+    cfg!(debug_assertions)
+        || std::env::var(ENV_SWITCH).is_ok_and(|v| v == "1")
+        || settings::current(app).enable_devtools
+}
+
+/// Open or close the devtools of the calling window, or of the window
+/// `label`. Returns whether they're open now. A release build needs the
+/// `devtools` feature and `enable_devtools` (or COPYCUT_DEVTOOLS=1).
 #[tauri::command]
-pub fn toggle_devtools(app: tauri::AppHandle) {
-    if let Some(win) = app.get_webview_window("main") {
-        #[cfg(debug_assertions)]
-        {
-            if win.is_devtools_open() {
-                win.close_devtools();
-            } else {
-                win.open_devtools();
-            }
+pub fn toggle_devtools(
+    app: AppHandle,
+    window: WebviewWindow,
+    label: Option<String>,
+) -> Result<bool, ApiError> {
+    let win = match label {
+        Some(l) => app
+            .get_webview_window(&l)
+            .ok_or_else(|| ApiError::invalid_input(format!("no window {}", l)))?,
+        None => window,
+    };
+    #[cfg(any(debug_assertions, feature = "devtools"))]
+    {
+        if !allowed(&app) {
+            return Err(ApiError::unsupported(format!(
+                "devtools are off; turn on enable_devtools or set {}=1",
+                ENV_SWITCH
+            )));
+        }
+        if win.is_devtools_open() {
+            win.close_devtools();
+        } else {
+            win.open_devtools();
         }
+        Ok(win.is_devtools_open())
+    }
+    #[cfg(not(any(debug_assertions, feature = "devtools")))]
+    {
+        let _ = win;
+        Err(ApiError::unsupported(
+            "this build has no devtools (build with the devtools feature)",
+        ))
     }
 }
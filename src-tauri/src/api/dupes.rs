@@ -0,0 +1,260 @@
+// src/api/dupes.rs
+use crate::api::{
+    fs_meta::file_id,
+    path_sizer::keys::IgnoreSet,
+    sandbox,
+    types::{DupeGroupEvent, DupesDoneEvent, DupesOptions},
+};
+use once_cell::sync::Lazy;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File, Metadata},
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+use tauri::{AppHandle, Emitter, Window};
+
+const PARTIAL_BYTES: u64 = 64 * 1024;
+/// Most paths one collecting walk holds on to; further sizes wait for another
+const MAX_PASS_PATHS: u64 = 200_000;
+
+static DUPE_JOBS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn walk_files<'a>(
+    roots: &'a [String],
+    skip: &'a IgnoreSet,
+    cancel: &'a AtomicBool,
+) -> impl Iterator<Item = (PathBuf, Metadata)> + 'a {
+    roots.iter().flat_map(move |root| {
+        walkdir::WalkDir::new(root)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(move |e| {
+                e.depth() == 0
+                    || !skip.is_skipped(
+                        e.path().strip_prefix(root).unwrap_or(e.path()),
+                        e.file_type().is_dir(),
+                    )
+            })
+            .take_while(move |_| !cancel.load(Ordering::SeqCst))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| {
+                let md = e.metadata().ok()?;
+                Some((e.into_path(), md))
+            })
+    })
+}
+
+/// What makes two paths the same file: its (device, inode) where the platform
+/// has one, so hard links count once, else the canonical path, so a file under
+/// two overlapping roots still counts once
+#[derive(Hash, Eq, PartialEq)]
+enum Identity {
+    File(u64, u64),
+    Path(PathBuf),
+}
+
+fn identity(path: &Path, md: &Metadata) -> Identity {
+    match file_id(path, md) {
+        Some((dev, ino)) => Identity::File(dev, ino),
+        None => Identity::Path(fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())),
+    }
+}
+
+fn hash_file(path: &Path, limit: Option<u64>) -> Option<[u8; 32]> {
+    let file = File::open(path).ok()?;
+    let mut hasher = blake3::Hasher::new();
+    let mut reader: Box<dyn Read> = match limit {
+        Some(n) => Box::new(file.take(n)),
+        None => Box::new(file),
+    };
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf).ok()?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Some(*hasher.finalize().as_bytes())
+}
+
+/// Split `paths` into groups with equal hashes, dropping singletons.
+fn group_by_hash(
+    paths: Vec<PathBuf>,
+    limit: Option<u64>,
+    cancel: &AtomicBool,
+) -> Vec<Vec<PathBuf>> {
+    let mut by_hash: HashMap<[u8; 32], Vec<PathBuf>> = HashMap::new();
+    for p in paths {
+        if cancel.load(Ordering::SeqCst) {
+            return Vec::new();
+        }
+        if let Some(h) = hash_file(&p, limit) {
+            by_hash.entry(h).or_default().push(p);
+        }
+    }
+    by_hash.into_values().filter(|g| g.len() > 1).collect()
+}
+
+/// Files of one size that may have copies, split by the hash of their first
+/// PARTIAL_BYTES
+#[derive(Default)]
+struct Candidates {
+    seen: HashSet<Identity>,
+    by_partial: HashMap<[u8; 32], Vec<PathBuf>>,
+}
+
+impl Candidates {
+    fn push(&mut self, path: PathBuf, md: &Metadata) {
+        if !self.seen.insert(identity(&path, md)) {
+            return; // another link to (or path of) a file we already have
+        }
+        if let Some(h) = hash_file(&path, Some(PARTIAL_BYTES)) {
+            self.by_partial.entry(h).or_default().push(path);
+        }
+    }
+}
+
+/// Events go to the `label` window only
+fn run(
+    app: &AppHandle,
+    label: &str,
+    request_id: &str,
+    roots: &[String],
+    opts: &DupesOptions,
+    skip: &IgnoreSet,
+    cancel: &AtomicBool,
+) {
+    let min_size = opts.min_size.unwrap_or(1);
+
+    // 1) count files per size; a size met once can't have copies, and none of
+    // its paths are kept
+    let mut counts: HashMap<u64, u64> = HashMap::new();
+    for (_, md) in walk_files(roots, skip, cancel) {
+        if md.len() >= min_size {
+            *counts.entry(md.len()).or_default() += 1;
+        }
+    }
+    let mut sizes: Vec<(u64, u64)> = counts.into_iter().filter(|(_, n)| *n > 1).collect();
+    sizes.sort_unstable_by_key(|s| std::cmp::Reverse(s.0));
+
+    // 2) walk again for as many of the remaining sizes (largest first) as
+    // MAX_PASS_PATHS allows, then confirm them bucket by bucket, freeing each
+    let mut groups = 0u64;
+    let mut reclaimable = 0u64;
+    let mut rest = &sizes[..];
+    while !rest.is_empty() && !cancel.load(Ordering::SeqCst) {
+        let mut held = 0u64;
+        let taken = rest
+            .iter()
+            .take_while(|(_, n)| {
+                let fits = held == 0 || held + n <= MAX_PASS_PATHS;
+                held += n;
+                fits
+            })
+            .count();
+        let (pass, next) = rest.split_at(taken);
+        rest = next;
+
+        let mut buckets: HashMap<u64, Candidates> = pass
+            .iter()
+            .map(|(size, _)| (*size, Candidates::default()))
+            .collect();
+        for (path, md) in walk_files(roots, skip, cancel) {
+            if let Some(bucket) = buckets.get_mut(&md.len()) {
+                bucket.push(path, &md);
+            }
+        }
+
+        for (size, _) in pass {
+            if cancel.load(Ordering::SeqCst) {
+                break;
+            }
+            let Some(bucket) = buckets.remove(size) else {
+                continue;
+            };
+            let size = *size;
+            for partial_group in bucket.by_partial.into_values().filter(|g| g.len() > 1) {
+                let full_groups = if size <= PARTIAL_BYTES {
+                    vec![partial_group]
+                } else {
+                    group_by_hash(partial_group, None, cancel)
+                };
+                for g in full_groups {
+                    groups += 1;
+                    reclaimable = reclaimable.saturating_add(size * (g.len() as u64 - 1));
+                    let _ = app.emit_to(
+                        label,
+                        "dupes:group",
+                        DupeGroupEvent {
+                            request_id: request_id.to_string(),
+                            size,
+                            paths: g.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    let _ = app.emit_to(
+        label,
+        "dupes:done",
+        DupesDoneEvent {
+            request_id: request_id.to_string(),
+            groups,
+            reclaimable_bytes: reclaimable,
+            cancelled: cancel.load(Ordering::SeqCst),
+        },
+    );
+}
+
+#[tauri::command]
+pub fn find_duplicates(
+    app: AppHandle,
+    window: Window,
+    roots: Vec<String>,
+    request_id: String,
+    options: Option<DupesOptions>,
+) -> Result<(), String> {
+    sandbox::check_all(&app, &roots)?;
+    let opts = options.unwrap_or_default();
+    let skip = IgnoreSet::new(opts.show_hidden, &opts.ignores)?;
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut jobs = DUPE_JOBS.lock().map_err(|e| e.to_string())?;
+        if jobs.contains_key(&request_id) {
+            return Err(format!("request {} already running", request_id));
+        }
+        jobs.insert(request_id.clone(), cancel.clone());
+    }
+
+    let label = window.label().to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        run(&app, &label, &request_id, &roots, &opts, &skip, &cancel);
+        let _ = DUPE_JOBS.lock().map(|mut j| j.remove(&request_id));
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub fn cancel_find_duplicates(request_id: String) -> bool {
+    match DUPE_JOBS
+        .lock()
+        .ok()
+        .and_then(|mut j| j.remove(&request_id))
+    {
+        Some(cancel) => {
+            cancel.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
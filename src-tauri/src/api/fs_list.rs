@@ -1,38 +1,230 @@
 // src/api/fs_list.rs
-use crate::api::types::FileEntry;
-use chrono::{DateTime, Local};
-use std::{cmp::Ordering, fs, path::PathBuf, time::SystemTime};
+use crate::api::{
+    path_sizer::keys::IgnoreSet,
+    sandbox, settings,
+    storage::{self, Storage},
+    types::{ApiError, DirListing, FileEntry, ListSort},
+};
+use once_cell::sync::Lazy;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering as AtomicOrdering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+use tauri::AppHandle;
+use tokio::sync::Semaphore;
 
-#[tauri::command]
-pub fn list_dir(path: &str) -> Result<Vec<FileEntry>, String> {
-    let mut out = Vec::new();
-    let dir = fs::read_dir(PathBuf::from(path)).map_err(|e| e.to_string())?;
-
-    for entry in dir {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let md = entry.metadata().map_err(|e| e.to_string())?;
-
-        let is_dir = md.is_dir();
-        let size = if is_dir { 0 } else { md.len() };
-        let modified = md.modified().ok().and_then(|t: SystemTime| {
-            let dt: DateTime<Local> = t.into();
-            Some(dt.format("%Y-%m-%d %H:%M").to_string())
-        });
+const DEFAULT_CHILD_COUNT_CAP: u64 = 10_000;
+const SNAPSHOT_TTL: Duration = Duration::from_secs(60);
 
-        let name = entry.file_name().to_string_lossy().to_string();
-        out.push(FileEntry {
-            name,
-            is_dir,
-            size,
-            modified,
-        });
+/// Bounds how many directories are being counted at once across all listings.
+static COUNT_SEM: Lazy<Arc<Semaphore>> = Lazy::new(|| Arc::new(Semaphore::new(8)));
+
+/// A sorted listing kept for a short while so paging sees a stable order.
+struct Snapshot {
+    root: PathBuf,
+    /// the listing's filter as given, to reject paging it with another one
+    show_hidden: bool,
+    ignores: Vec<String>,
+    skip: Arc<IgnoreSet>,
+    entries: Vec<FileEntry>,
+    created_at: Instant,
+}
+
+static SNAPSHOTS: Lazy<Mutex<HashMap<String, Snapshot>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static SNAPSHOT_SEQ: AtomicU64 = AtomicU64::new(0);
+
+fn applied_sort() -> ListSort {
+    ListSort {
+        key: "name".into(),
+        dir: "asc".into(),
+        dirs_first: true,
     }
+}
 
-    out.sort_by(|a, b| match (a.is_dir, b.is_dir) {
+fn sort_entries(entries: &mut [FileEntry]) {
+    entries.sort_by(|a, b| match (a.is_dir, b.is_dir) {
         (true, false) => Ordering::Less,
         (false, true) => Ordering::Greater,
         _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
     });
+}
+
+fn slice_page(entries: &[FileEntry], offset: usize, limit: Option<usize>) -> Vec<FileEntry> {
+    let start = offset.min(entries.len());
+    let end = match limit {
+        Some(l) => start.saturating_add(l).min(entries.len()),
+        None => entries.len(),
+    };
+    entries[start..end].to_vec()
+}
+
+async fn fill_child_counts(
+    storage: &Arc<dyn Storage>,
+    root: &Path,
+    entries: &mut [FileEntry],
+    skip: &Arc<IgnoreSet>,
+    cap: u64,
+) -> Result<(), String> {
+    let mut tasks = Vec::new();
+
+    for (idx, e) in entries.iter().enumerate() {
+        if !e.is_dir {
+            continue;
+        }
+        let permit = COUNT_SEM
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| e.to_string())?;
+        let dir = root.join(&e.name);
+        let skip = skip.clone();
+        let storage = storage.clone();
+        tasks.push((
+            idx,
+            tauri::async_runtime::spawn_blocking(move || {
+                let _p = permit;
+                storage.count_children(&dir, &skip, cap)
+            }),
+        ));
+    }
+
+    for (idx, t) in tasks {
+        if let Ok(Some((count, approx))) = t.await {
+            entries[idx].child_count = Some(count);
+            entries[idx].child_count_approx = approx;
+        }
+    }
+    Ok(())
+}
+
+/// `show_hidden` / `ignores` default to the settings. With `snapshot_id` they
+/// may be left out; given, they must match the snapshot (`invalid_input`
+/// otherwise), as must `path`. An expired id fails with `snapshot_expired`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn list_dir(
+    app: AppHandle,
+    path: String,
+    show_hidden: Option<bool>,
+    ignores: Option<Vec<String>>,
+    include_child_counts: Option<bool>,
+    child_count_cap: Option<u64>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    snapshot: Option<bool>,
+    snapshot_id: Option<String>,
+) -> Result<DirListing, ApiError> {
+    sandbox::check(&app, &path)?;
+    let storage = storage::backend(&app);
+    let offset = offset.unwrap_or(0);
+    let count_cap = child_count_cap.unwrap_or(DEFAULT_CHILD_COUNT_CAP).max(1);
+
+    // Paging an existing snapshot: no directory IO except child counts
+    if let Some(id) = snapshot_id {
+        let (root, skip, total, mut page) = {
+            let mut snaps = SNAPSHOTS.lock().map_err(|e| e.to_string())?;
+            snaps.retain(|_, s| s.created_at.elapsed() < SNAPSHOT_TTL);
+            let snap = snaps
+                .get(&id)
+                .ok_or_else(|| ApiError::snapshot_expired(&id))?;
+            let same_filter = show_hidden.is_none_or(|h| h == snap.show_hidden)
+                && ignores.as_ref().is_none_or(|i| *i == snap.ignores);
+            if Path::new(&path) != snap.root || !same_filter {
+                return Err(ApiError::invalid_input(format!(
+                    "snapshot {} is of {}, with its own filters",
+                    id,
+                    snap.root.display()
+                ))
+                .with_path(&path));
+            }
+            (
+                snap.root.clone(),
+                snap.skip.clone(),
+                snap.entries.len(),
+                slice_page(&snap.entries, offset, limit),
+            )
+        };
+        if include_child_counts.unwrap_or(false) {
+            fill_child_counts(&storage, &root, &mut page, &skip, count_cap).await?;
+        }
+        return Ok(DirListing {
+            entries: page,
+            total,
+            offset,
+            sort: applied_sort(),
+            snapshot_id: Some(id),
+        });
+    }
+
+    let defaults = settings::current(&app);
+    let show_hidden = show_hidden.unwrap_or(defaults.show_hidden);
+    let ignores = ignores.unwrap_or(defaults.ignores);
+    let skip = Arc::new(IgnoreSet::new(show_hidden, &ignores).map_err(ApiError::invalid_input)?);
+    let root = PathBuf::from(&path);
+
+    let mut all = tauri::async_runtime::spawn_blocking({
+        let root = root.clone();
+        let skip = skip.clone();
+        let storage = storage.clone();
+        move || storage.list(&root, &skip)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .inspect_err(|e| tracing::warn!(path = path.as_str(), error = %e, "listing failed"))?;
+    sort_entries(&mut all);
+
+    let total = all.len();
+    let mut page = if offset == 0 && limit.is_none() {
+        std::mem::take(&mut all)
+    } else {
+        slice_page(&all, offset, limit)
+    };
+
+    let mut new_snapshot_id = None;
+    if snapshot.unwrap_or(false) {
+        // time component keeps ids from a previous run from matching new snapshots
+        let stamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let id = format!(
+            "snap-{:x}-{}",
+            stamp,
+            SNAPSHOT_SEQ.fetch_add(1, AtomicOrdering::Relaxed)
+        );
+        let entries = if all.is_empty() { page.clone() } else { all };
+        if let Ok(mut snaps) = SNAPSHOTS.lock() {
+            snaps.retain(|_, s| s.created_at.elapsed() < SNAPSHOT_TTL);
+            snaps.insert(
+                id.clone(),
+                Snapshot {
+                    root: root.clone(),
+                    show_hidden,
+                    ignores,
+                    skip: skip.clone(),
+                    entries,
+                    created_at: Instant::now(),
+                },
+            );
+        }
+        new_snapshot_id = Some(id);
+    }
+
+    if include_child_counts.unwrap_or(false) {
+        fill_child_counts(&storage, &root, &mut page, &skip, count_cap).await?;
+    }
 
-    Ok(out)
+    Ok(DirListing {
+        entries: page,
+        total,
+        offset,
+        sort: applied_sort(),
+        snapshot_id: new_snapshot_id,
+    })
 }
@@ -0,0 +1,383 @@
+// src/api/fs_meta.rs
+use std::{collections::HashMap, fs::Metadata, path::Path};
+
+/// Ownership/permission columns of a FileEntry
+#[derive(Default)]
+pub struct Perms {
+    pub mode_octal: Option<u32>,
+    pub mode_string: Option<String>,
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    pub executable: bool,
+}
+
+/// Per-listing cache so a big directory doesn't resolve the same uid over and over.
+#[derive(Default)]
+pub struct OwnerNames {
+    #[cfg_attr(not(unix), allow(dead_code))]
+    users: HashMap<u32, String>,
+    #[cfg_attr(not(unix), allow(dead_code))]
+    groups: HashMap<u32, String>,
+}
+
+#[cfg(unix)]
+fn lookup_name(id: u32, is_group: bool) -> Option<String> {
+    use std::ffi::CStr;
+
+    let mut buf: Vec<libc::c_char> = vec![0; 1024];
+    loop {
+        let rc;
+        let name_ptr;
+        // SAFETY: buffers outlive the calls, result pointers are checked before use
+        unsafe {
+            if is_group {
+                let mut grp: libc::group = std::mem::zeroed();
+                let mut res: *mut libc::group = std::ptr::null_mut();
+                rc = libc::getgrgid_r(id, &mut grp, buf.as_mut_ptr(), buf.len(), &mut res);
+                name_ptr = if res.is_null() {
+                    std::ptr::null()
+                } else {
+                    grp.gr_name
+                };
+            } else {
+                let mut pwd: libc::passwd = std::mem::zeroed();
+                let mut res: *mut libc::passwd = std::ptr::null_mut();
+                rc = libc::getpwuid_r(id, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut res);
+                name_ptr = if res.is_null() {
+                    std::ptr::null()
+                } else {
+                    pwd.pw_name
+                };
+            }
+        }
+        if rc == libc::ERANGE && buf.len() < 1 << 20 {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if rc != 0 || name_ptr.is_null() {
+            return None;
+        }
+        // SAFETY: non-null and NUL-terminated, pointing into `buf`
+        return Some(
+            unsafe { CStr::from_ptr(name_ptr) }
+                .to_string_lossy()
+                .into_owned(),
+        );
+    }
+}
+
+#[cfg(unix)]
+pub fn mode_string(mode: u32) -> String {
+    // S_IFMT and friends; same values on every unix we ship for
+    let kind = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        0o010000 => 'p',
+        0o140000 => 's',
+        _ => '-',
+    };
+    let bit = |mask: u32, c: char| if mode & mask != 0 { c } else { '-' };
+    // exec slot that may carry setuid/setgid/sticky
+    let special = |exec: u32, flag: u32, set: char| match (mode & exec != 0, mode & flag != 0) {
+        (true, true) => set,
+        (false, true) => set.to_ascii_uppercase(),
+        (true, false) => 'x',
+        (false, false) => '-',
+    };
+
+    [
+        kind,
+        bit(0o400, 'r'),
+        bit(0o200, 'w'),
+        special(0o100, 0o4000, 's'),
+        bit(0o040, 'r'),
+        bit(0o020, 'w'),
+        special(0o010, 0o2000, 's'),
+        bit(0o004, 'r'),
+        bit(0o002, 'w'),
+        special(0o001, 0o1000, 't'),
+    ]
+    .iter()
+    .collect()
+}
+
+impl OwnerNames {
+    #[cfg(unix)]
+    pub fn describe(&mut self, md: &Metadata, _name: &str) -> Perms {
+        use std::os::unix::fs::MetadataExt;
+
+        let mode = md.mode();
+        let (uid, gid) = (md.uid(), md.gid());
+        let owner = self
+            .users
+            .entry(uid)
+            .or_insert_with(|| lookup_name(uid, false).unwrap_or_else(|| uid.to_string()))
+            .clone();
+        let group = self
+            .groups
+            .entry(gid)
+            .or_insert_with(|| lookup_name(gid, true).unwrap_or_else(|| gid.to_string()))
+            .clone();
+
+        Perms {
+            mode_octal: Some(mode & 0o7777),
+            mode_string: Some(mode_string(mode)),
+            owner: Some(owner),
+            group: Some(group),
+            executable: md.is_file() && mode & 0o111 != 0,
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn describe(&mut self, md: &Metadata, name: &str) -> Perms {
+        const EXEC_EXTS: [&str; 6] = ["exe", "bat", "cmd", "com", "ps1", "msi"];
+        let ext = std::path::Path::new(name)
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        Perms {
+            executable: md.is_file() && EXEC_EXTS.contains(&ext.as_str()),
+            ..Default::default()
+        }
+    }
+}
+
+/// (device, inode) of a file with more than one hard link; None for the
+/// common single-link case so callers only track the ones that matter.
+#[cfg(unix)]
+pub fn hardlink_id(_path: &Path, md: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (md.nlink() > 1).then(|| (md.dev(), md.ino()))
+}
+
+/// Windows only exposes link count, volume and file index through an open handle;
+/// directories need backup semantics to be opened at all. Opened for attributes
+/// only, like `standard_info`, so no placeholder is recalled.
+#[cfg(windows)]
+fn handle_info(
+    path: &Path,
+) -> Option<windows_sys::Win32::Storage::FileSystem::BY_HANDLE_FILE_INFORMATION> {
+    use std::os::windows::{fs::OpenOptionsExt, io::AsRawHandle};
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_FLAG_BACKUP_SEMANTICS,
+        FILE_READ_ATTRIBUTES,
+    };
+
+    let file = std::fs::OpenOptions::new()
+        .access_mode(FILE_READ_ATTRIBUTES)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)
+        .ok()?;
+    // SAFETY: the handle stays open for the call and `info` is a plain out struct
+    unsafe {
+        let mut info: BY_HANDLE_FILE_INFORMATION = std::mem::zeroed();
+        if GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) == 0 {
+            return None;
+        }
+        Some(info)
+    }
+}
+
+/// Opens for attributes only, which never hydrates a cloud placeholder
+#[cfg(windows)]
+fn standard_info(
+    path: &Path,
+) -> Option<windows_sys::Win32::Storage::FileSystem::FILE_STANDARD_INFO> {
+    use std::os::windows::{fs::OpenOptionsExt, io::AsRawHandle};
+    use windows_sys::Win32::Storage::FileSystem::{
+        FileStandardInfo, GetFileInformationByHandleEx, FILE_READ_ATTRIBUTES, FILE_STANDARD_INFO,
+    };
+
+    let file = std::fs::OpenOptions::new()
+        .access_mode(FILE_READ_ATTRIBUTES)
+        .open(path)
+        .ok()?;
+    // SAFETY: the handle stays open for the call and `info` is a plain out struct
+    // of the size passed
+    unsafe {
+        let mut info: FILE_STANDARD_INFO = std::mem::zeroed();
+        let ok = GetFileInformationByHandleEx(
+            file.as_raw_handle() as _,
+            FileStandardInfo,
+            &mut info as *mut _ as *mut _,
+            std::mem::size_of::<FILE_STANDARD_INFO>() as u32,
+        );
+        (ok != 0).then_some(info)
+    }
+}
+
+#[cfg(windows)]
+fn info_id(
+    info: &windows_sys::Win32::Storage::FileSystem::BY_HANDLE_FILE_INFORMATION,
+) -> (u64, u64) {
+    let index = ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64;
+    (info.dwVolumeSerialNumber as u64, index)
+}
+
+#[cfg(windows)]
+pub fn hardlink_id(path: &Path, _md: &Metadata) -> Option<(u64, u64)> {
+    let info = handle_info(path)?;
+    (info.nNumberOfLinks > 1).then(|| info_id(&info))
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn hardlink_id(_path: &Path, _md: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Bytes allocated on disk: 512-byte blocks on unix, the compressed/sparse size
+/// on Windows, the logical length elsewhere.
+#[cfg(unix)]
+pub fn allocated_size(_path: &Path, md: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    md.blocks().saturating_mul(512)
+}
+
+/// Cloud placeholders (OneDrive online-only files) and offline files are
+/// measured by what's really allocated for them, often nothing.
+#[cfg(windows)]
+pub fn allocated_size(path: &Path, md: &Metadata) -> u64 {
+    use std::os::windows::{ffi::OsStrExt, fs::MetadataExt};
+    use windows_sys::Win32::{
+        Foundation::{GetLastError, NO_ERROR},
+        Storage::FileSystem::{
+            GetCompressedFileSizeW, FILE_ATTRIBUTE_OFFLINE, FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS,
+            FILE_ATTRIBUTE_RECALL_ON_OPEN, INVALID_FILE_SIZE,
+        },
+    };
+
+    let placeholder = FILE_ATTRIBUTE_OFFLINE
+        | FILE_ATTRIBUTE_RECALL_ON_OPEN
+        | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS;
+    if md.file_attributes() & placeholder != 0 {
+        return standard_info(path).map_or(0, |info| info.AllocationSize.max(0) as u64);
+    }
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut high: u32 = 0;
+    // SAFETY: `wide` is NUL-terminated and outlives the call
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+    // INVALID_FILE_SIZE is also a valid low word, only an error with a last-error set
+    if low == INVALID_FILE_SIZE && unsafe { GetLastError() } != NO_ERROR {
+        return md.len();
+    }
+    ((high as u64) << 32) | low as u64
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn allocated_size(_path: &Path, md: &Metadata) -> u64 {
+    md.len()
+}
+
+/// Device / volume of `path`, to tell when a walk crosses into another filesystem.
+#[cfg(unix)]
+pub fn device_id(_path: &Path, md: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(md.dev())
+}
+
+/// Volume serial number
+#[cfg(windows)]
+pub fn device_id(path: &Path, _md: &Metadata) -> Option<u64> {
+    handle_info(path).map(|info| info.dwVolumeSerialNumber as u64)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn device_id(_path: &Path, _md: &Metadata) -> Option<u64> {
+    None
+}
+
+/// (device, inode) of any file or directory, whatever its link count; identifies
+/// the real target when symlinks are followed.
+#[cfg(unix)]
+pub fn file_id(_path: &Path, md: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((md.dev(), md.ino()))
+}
+
+#[cfg(windows)]
+pub fn file_id(path: &Path, _md: &Metadata) -> Option<(u64, u64)> {
+    handle_info(path).map(|info| info_id(&info))
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn file_id(_path: &Path, _md: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// A directory junction or folder mount point (reparse tag MOUNT_POINT), as
+/// opposed to a symlink; std reports both as `is_symlink()`
+#[cfg(windows)]
+pub fn is_junction(path: &Path) -> bool {
+    use std::os::windows::{fs::OpenOptionsExt, io::AsRawHandle};
+    use windows_sys::Win32::Storage::FileSystem::{
+        FileAttributeTagInfo, GetFileInformationByHandleEx, FILE_ATTRIBUTE_TAG_INFO,
+        FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT, FILE_READ_ATTRIBUTES,
+    };
+    const IO_REPARSE_TAG_MOUNT_POINT: u32 = 0xA000_0003;
+
+    let Ok(file) = std::fs::OpenOptions::new()
+        .access_mode(FILE_READ_ATTRIBUTES)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT)
+        .open(path)
+    else {
+        return false;
+    };
+    // SAFETY: as in `standard_info`
+    unsafe {
+        let mut info: FILE_ATTRIBUTE_TAG_INFO = std::mem::zeroed();
+        GetFileInformationByHandleEx(
+            file.as_raw_handle() as _,
+            FileAttributeTagInfo,
+            &mut info as *mut _ as *mut _,
+            std::mem::size_of::<FILE_ATTRIBUTE_TAG_INFO>() as u32,
+        ) != 0
+            && info.ReparseTag == IO_REPARSE_TAG_MOUNT_POINT
+    }
+}
+
+#[cfg(not(windows))]
+pub fn is_junction(_path: &Path) -> bool {
+    false
+}
+
+/// (total, used) bytes of the filesystem holding `path`
+#[cfg(unix)]
+pub fn volume_usage(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: `c` is NUL-terminated and `st` is a plain out struct
+    let st = unsafe {
+        let mut st: libc::statvfs = std::mem::zeroed();
+        if libc::statvfs(c.as_ptr(), &mut st) != 0 {
+            return None;
+        }
+        st
+    };
+    let frsize = st.f_frsize as u64;
+    let total = (st.f_blocks as u64).saturating_mul(frsize);
+    let free = (st.f_bfree as u64).saturating_mul(frsize);
+    Some((total, total.saturating_sub(free)))
+}
+
+#[cfg(windows)]
+pub fn volume_usage(path: &Path) -> Option<(u64, u64)> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let (mut avail, mut total, mut free) = (0u64, 0u64, 0u64);
+    // SAFETY: `wide` is NUL-terminated and outlives the call
+    if unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut avail, &mut total, &mut free) } == 0 {
+        return None;
+    }
+    Some((total, total.saturating_sub(free)))
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn volume_usage(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
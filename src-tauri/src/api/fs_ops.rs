@@ -1,79 +1,186 @@
 // src/api/fs_ops.rs
-use std::{
-    fs,
-    path::{Path, PathBuf},
+use crate::api::{
+    path_sizer::{
+        cache::{invalidate_related, Invalidation},
+        keys::normalize_maybe_missing,
+    },
+    sandbox,
+    storage::{self, bad_name},
+    types::ApiError,
 };
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
 
-fn copy_one(src: &Path, dest_dir: &Path) -> Result<(), String> {
-    let file_name = src.file_name().ok_or("bad source name")?;
-    let target = dest_dir.join(file_name);
+// Normalized paths for size cache invalidation (taken before the op runs,
+// while sources still resolve)
+fn normalized(paths: &[String]) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .map(|p| normalize_maybe_missing(Path::new(p)))
+        .collect()
+}
 
-    if src.is_dir() {
-        let mut opts = fs_extra::dir::CopyOptions::new();
-        opts.overwrite = true;
-        opts.copy_inside = true;
-        fs_extra::dir::copy(src, &target, &opts).map_err(|e| e.to_string())?;
-    } else {
-        if let Some(parent) = target.parent() {
-            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-        }
-        fs::copy(src, &target).map_err(|e| e.to_string())?;
+fn landing_paths(paths: &[String], dest: &Path) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .filter_map(|p| Path::new(p).file_name())
+        .map(|name| normalize_maybe_missing(&dest.join(name)))
+        .collect()
+}
+
+fn log_start(op: &str, paths: &[String], dest: Option<&Path>) {
+    tracing::info!(
+        op,
+        count = paths.len(),
+        path = paths.first().map(String::as_str),
+        dest = dest.map(|d| d.display().to_string()),
+        "file operation started"
+    );
+}
+
+/// The error carries the item that failed, so it's logged here once
+fn log_end(op: &str, res: &Result<(), ApiError>) {
+    match res {
+        Ok(()) => tracing::info!(op, "file operation finished"),
+        Err(e) => tracing::warn!(
+            op,
+            paths = ?e.detail().paths,
+            os_code = e.detail().os_code,
+            error = %e,
+            "file operation failed"
+        ),
     }
-    Ok(())
 }
 
+/// `preserve_metadata`: give the copies the times, permissions and xattrs of
+/// the originals (default off)
 #[tauri::command]
-pub fn copy_paths(paths: Vec<String>, dest_dir: String) -> Result<(), String> {
+pub fn copy_paths(
+    app: AppHandle,
+    paths: Vec<String>,
+    dest_dir: String,
+    preserve_metadata: Option<bool>,
+) -> Result<(), ApiError> {
+    sandbox::check_all(&app, &paths)?;
+    sandbox::check(&app, &dest_dir)?;
+    let preserve_metadata = preserve_metadata.unwrap_or(false);
     let dest = PathBuf::from(dest_dir);
-    if !dest.exists() {
-        fs::create_dir_all(&dest).map_err(|e| e.to_string())?;
-    }
+    // only the destination side changes, sources keep their size
+    let affected = landing_paths(&paths, &dest);
+    log_start("copy", &paths, Some(&dest));
+    let res = copy_all(&app, &paths, &dest, preserve_metadata);
+    log_end("copy", &res);
+    invalidate_related(&app, &affected, Invalidation::Drop);
+    res
+}
+
+fn copy_all(
+    app: &AppHandle,
+    paths: &[String],
+    dest: &Path,
+    preserve_metadata: bool,
+) -> Result<(), ApiError> {
+    let storage = storage::backend(app);
+    storage.create_dir_all(dest)?;
     for p in paths {
-        copy_one(Path::new(&p), &dest)?;
+        let src = Path::new(p);
+        // the landing spot itself may be a link leading out of the allowed roots
+        let file_name = src.file_name().ok_or_else(|| bad_name(src))?;
+        sandbox::check(app, dest.join(file_name))?;
+        check_below(app, src, &dest.join(file_name))?;
+        storage.copy_into(src, dest, preserve_metadata)?;
     }
     Ok(())
 }
 
+/// Check every spot under `target` a copy of the folder `src` writes to: a
+/// folder already at `target` is merged into, and one of its subfolders may be
+/// a link leading out of the allowed roots
+fn check_below(app: &AppHandle, src: &Path, target: &Path) -> Result<(), ApiError> {
+    if !sandbox::restricted(app) || !target.is_dir() {
+        return Ok(());
+    }
+    for entry in walkdir::WalkDir::new(src)
+        .min_depth(1)
+        .follow_links(false)
+        .into_iter()
+        .flatten()
+    {
+        if let Ok(rel) = entry.path().strip_prefix(src) {
+            sandbox::check(app, target.join(rel))?;
+        }
+    }
+    Ok(())
+}
+
+/// `preserve_metadata` (default on) applies when a move has to copy, across
+/// volumes; a plain rename keeps everything anyway
 #[tauri::command]
-pub fn move_paths(paths: Vec<String>, dest_dir: String) -> Result<(), String> {
+pub fn move_paths(
+    app: AppHandle,
+    paths: Vec<String>,
+    dest_dir: String,
+    preserve_metadata: Option<bool>,
+) -> Result<(), ApiError> {
+    sandbox::check_all(&app, &paths)?;
+    sandbox::check(&app, &dest_dir)?;
+    let preserve_metadata = preserve_metadata.unwrap_or(true);
     let dest = PathBuf::from(dest_dir);
-    if !dest.exists() {
-        fs::create_dir_all(&dest).map_err(|e| e.to_string())?;
-    }
+    let mut affected = normalized(&paths);
+    affected.extend(landing_paths(&paths, &dest));
+    log_start("move", &paths, Some(&dest));
+    let res = move_all(&app, &paths, &dest, preserve_metadata);
+    log_end("move", &res);
+    invalidate_related(&app, &affected, Invalidation::Drop);
+    res
+}
+
+fn move_all(
+    app: &AppHandle,
+    paths: &[String],
+    dest: &Path,
+    preserve_metadata: bool,
+) -> Result<(), ApiError> {
+    let storage = storage::backend(app);
+    storage.create_dir_all(dest)?;
     for p in paths {
-        let src = PathBuf::from(&p);
-        let file_name = src.file_name().ok_or("bad source name")?;
-        let target = dest.join(file_name);
-        match fs::rename(&src, &target) {
-            Ok(_) => {}
-            Err(_) => {
-                // fallback: copy then delete
-                copy_one(&src, &dest)?;
-                if src.is_dir() {
-                    fs::remove_dir_all(&src).map_err(|e| e.to_string())?;
-                } else {
-                    fs::remove_file(&src).map_err(|e| e.to_string())?;
-                }
-            }
-        }
+        let src = Path::new(p);
+        let file_name = src.file_name().ok_or_else(|| bad_name(src))?;
+        sandbox::check(app, dest.join(file_name))?;
+        // a move across volumes copies
+        check_below(app, src, &dest.join(file_name))?;
+        storage.move_into(src, dest, preserve_metadata)?;
     }
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_paths(paths: Vec<String>) -> Result<(), String> {
+pub fn delete_paths(app: AppHandle, paths: Vec<String>) -> Result<(), ApiError> {
+    sandbox::check_all(&app, &paths)?;
+    let affected = normalized(&paths);
+    log_start("delete", &paths, None);
+    let res = delete_all(&app, &paths);
+    log_end("delete", &res);
+    invalidate_related(&app, &affected, Invalidation::Drop);
+    res
+}
+
+fn delete_all(app: &AppHandle, paths: &[String]) -> Result<(), ApiError> {
+    let storage = storage::backend(app);
     for p in paths {
-        let pb = PathBuf::from(&p);
-        if pb.is_dir() {
-            fs::remove_dir_all(pb).map_err(|e| e.to_string())?;
-        } else {
-            fs::remove_file(pb).map_err(|e| e.to_string())?;
-        }
+        storage.remove(Path::new(p))?;
     }
     Ok(())
 }
 
 #[tauri::command]
-pub fn rename_path(from: String, to: String) -> Result<(), String> {
-    std::fs::rename(&from, &to).map_err(|e| e.to_string())
+pub fn rename_path(app: AppHandle, from: String, to: String) -> Result<(), ApiError> {
+    sandbox::check(&app, &from)?;
+    sandbox::check(&app, &to)?;
+    let affected = normalized(&[from.clone(), to.clone()]);
+    log_start("rename", std::slice::from_ref(&from), Some(Path::new(&to)));
+    let res = storage::backend(&app).rename(Path::new(&from), Path::new(&to));
+    log_end("rename", &res);
+    invalidate_related(&app, &affected, Invalidation::Drop);
+    res
 }
@@ -0,0 +1,214 @@
+// src/api/fs_path.rs
+use crate::api::{
+    sandbox,
+    types::{ApiError, ResolvedPath},
+};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+use tauri::AppHandle;
+
+fn home() -> Option<String> {
+    std::env::var("HOME")
+        .ok()
+        .or_else(|| std::env::var("USERPROFILE").ok())
+        .filter(|h| !h.is_empty())
+}
+
+fn is_var_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Expands `$VAR`, `${VAR}` and `%VAR%`. Unknown variables are kept verbatim
+/// so the user sees what didn't resolve.
+fn expand_vars(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '$' && i + 1 < chars.len() {
+            let (name, end) = if chars[i + 1] == '{' {
+                match chars[i + 2..].iter().position(|&c| c == '}') {
+                    Some(p) => (chars[i + 2..i + 2 + p].iter().collect(), i + 3 + p),
+                    None => (String::new(), i),
+                }
+            } else {
+                let len = chars[i + 1..]
+                    .iter()
+                    .take_while(|&&c| is_var_char(c))
+                    .count();
+                (
+                    chars[i + 1..i + 1 + len].iter().collect::<String>(),
+                    i + 1 + len,
+                )
+            };
+            if !name.is_empty() {
+                if let Ok(val) = std::env::var(&name) {
+                    out.push_str(&val);
+                    i = end;
+                    continue;
+                }
+            }
+        } else if c == '%' {
+            if let Some(p) = chars[i + 1..].iter().position(|&c| c == '%') {
+                let name: String = chars[i + 1..i + 1 + p].iter().collect();
+                if !name.is_empty() && name.chars().all(is_var_char) {
+                    if let Ok(val) = std::env::var(&name) {
+                        out.push_str(&val);
+                        i += p + 2;
+                        continue;
+                    }
+                }
+            }
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+fn expand_tilde(input: &str) -> String {
+    let rest = match input.strip_prefix('~') {
+        Some(r) if r.is_empty() || r.starts_with('/') || r.starts_with('\\') => r,
+        _ => return input.to_string(),
+    };
+    match home() {
+        Some(h) => format!("{}{}", h, rest),
+        None => input.to_string(),
+    }
+}
+
+/// `C:folder` (drive-relative) -> relative to `base` when it is on the same
+/// drive, otherwise relative to that drive's root.
+#[cfg(windows)]
+fn fix_drive_relative(input: String, base: Option<&Path>) -> String {
+    let b = input.as_bytes();
+    let drive_relative = b.len() >= 2
+        && b[0].is_ascii_alphabetic()
+        && b[1] == b':'
+        && !matches!(b.get(2), Some(b'\\') | Some(b'/'));
+    if !drive_relative {
+        return input;
+    }
+    let drive = input[..2].to_ascii_uppercase();
+    let rest = &input[2..];
+    if let Some(base) = base {
+        let bs = base.to_string_lossy();
+        if bs.get(..2).is_some_and(|d| d.eq_ignore_ascii_case(&drive)) {
+            return base.join(rest).to_string_lossy().to_string();
+        }
+    }
+    format!("{}\\{}", drive, rest)
+}
+
+#[cfg(not(windows))]
+fn fix_drive_relative(input: String, _base: Option<&Path>) -> String {
+    input
+}
+
+/// Lexically resolve `.` and `..` without touching the filesystem
+fn normalize_lexically(p: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for comp in p.components() {
+        match comp {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                // never pop past the root/prefix
+                if !matches!(
+                    out.components().next_back(),
+                    None | Some(Component::RootDir) | Some(Component::Prefix(_))
+                ) {
+                    out.pop();
+                }
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// canonicalize() on Windows returns verbatim `\\?\C:\...` paths; the UI wants `C:\...`
+fn display_path(p: &Path) -> String {
+    let s = p.to_string_lossy().to_string();
+    #[cfg(windows)]
+    if let Some(rest) = s.strip_prefix(r"\\?\") {
+        if rest.as_bytes().get(1) == Some(&b':') {
+            return rest.to_string();
+        }
+    }
+    s
+}
+
+fn nearest_existing(p: &Path) -> Option<String> {
+    p.ancestors()
+        .skip(1)
+        .find(|a| !a.as_os_str().is_empty() && a.exists())
+        .map(display_path)
+}
+
+/// A path outside the allowed roots resolves as if it didn't exist, so the
+/// address bar can't be used to probe what's there
+#[tauri::command]
+pub fn resolve_path(
+    app: AppHandle,
+    input: String,
+    base: Option<String>,
+) -> Result<ResolvedPath, ApiError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(ApiError::invalid_input("empty path"));
+    }
+
+    let base = match base {
+        Some(b) => PathBuf::from(b),
+        None => std::env::current_dir().map_err(ApiError::from)?,
+    };
+
+    let expanded = expand_vars(&expand_tilde(trimmed));
+    #[cfg(windows)]
+    let expanded = expanded.replace('/', "\\");
+    let expanded = fix_drive_relative(expanded, Some(&base));
+
+    let joined = {
+        let p = Path::new(&expanded);
+        if p.is_absolute() {
+            p.to_path_buf()
+        } else {
+            base.join(p)
+        }
+    };
+    let lexical = normalize_lexically(&joined);
+    if sandbox::check(&app, &lexical).is_err() {
+        return Ok(ResolvedPath {
+            path: display_path(&lexical),
+            exists: false,
+            is_dir: false,
+            is_symlink: false,
+            nearest_existing: None,
+        });
+    }
+
+    let is_symlink = fs::symlink_metadata(&lexical)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+
+    match fs::canonicalize(&lexical) {
+        Ok(canon) => Ok(ResolvedPath {
+            path: display_path(&canon),
+            exists: true,
+            is_dir: canon.is_dir(),
+            is_symlink,
+            nearest_existing: None,
+        }),
+        Err(_) => Ok(ResolvedPath {
+            path: display_path(&lexical),
+            exists: false,
+            is_dir: false,
+            is_symlink,
+            nearest_existing: nearest_existing(&lexical),
+        }),
+    }
+}
@@ -0,0 +1,156 @@
+// src/api/fs_tree.rs
+use crate::api::{path_sizer::keys::IgnoreSet, sandbox, types::DirNode};
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+use tauri::AppHandle;
+
+/// Max subdirectories listed per directory before the node is marked truncated.
+const LEVEL_CAP: usize = 2_000;
+/// Recursion guard for pathological depth requests.
+const MAX_DEPTH: u32 = 64;
+
+static TREE_REQUESTS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct Cancelled;
+
+struct TreeOpts<'a> {
+    root: &'a Path,
+    skip: &'a IgnoreSet,
+    cancel: &'a AtomicBool,
+}
+
+fn build_node(
+    path: &Path,
+    name: String,
+    depth_left: u32,
+    opts: &TreeOpts,
+) -> Result<DirNode, Cancelled> {
+    if opts.cancel.load(Ordering::SeqCst) {
+        return Err(Cancelled);
+    }
+
+    let mut node = DirNode {
+        name,
+        path: path.to_string_lossy().to_string(),
+        has_children: false,
+        truncated: false,
+        denied: false,
+        children: Vec::new(),
+    };
+
+    let rd = match fs::read_dir(path) {
+        Ok(rd) => rd,
+        Err(_) => {
+            node.denied = true;
+            return Ok(node);
+        }
+    };
+
+    let mut subdirs: Vec<String> = Vec::new();
+    for ent in rd.flatten() {
+        let child_name = ent.file_name().to_string_lossy().to_string();
+        // symlinks are not followed, same as the sizer
+        if !ent.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        let child_path = path.join(&child_name);
+        let rel = child_path.strip_prefix(opts.root).unwrap_or(&child_path);
+        if opts.skip.is_skipped(rel, true) {
+            continue;
+        }
+        if depth_left == 0 {
+            // only need to know that one exists
+            node.has_children = true;
+            return Ok(node);
+        }
+        if subdirs.len() >= LEVEL_CAP {
+            node.truncated = true;
+            break;
+        }
+        subdirs.push(child_name);
+    }
+
+    node.has_children = !subdirs.is_empty();
+    subdirs.sort_by_key(|a| a.to_lowercase());
+    for child_name in subdirs {
+        let child_path = path.join(&child_name);
+        node.children
+            .push(build_node(&child_path, child_name, depth_left - 1, opts)?);
+    }
+    Ok(node)
+}
+
+#[tauri::command]
+pub async fn list_dir_tree(
+    app: AppHandle,
+    root: String,
+    depth: u32,
+    show_hidden: bool,
+    ignores: Vec<String>,
+    request_id: Option<String>,
+) -> Result<DirNode, String> {
+    sandbox::check(&app, &root)?;
+    let skip = IgnoreSet::new(show_hidden, &ignores)?;
+    let cancel = Arc::new(AtomicBool::new(false));
+    if let Some(id) = &request_id {
+        if let Ok(mut reqs) = TREE_REQUESTS.lock() {
+            if let Some(prev) = reqs.insert(id.clone(), cancel.clone()) {
+                prev.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+
+    let res = tauri::async_runtime::spawn_blocking({
+        let cancel = cancel.clone();
+        move || {
+            let root_path = Path::new(&root);
+            let name = root_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| root.clone());
+            let opts = TreeOpts {
+                root: root_path,
+                skip: &skip,
+                cancel: &cancel,
+            };
+            build_node(root_path, name, depth.min(MAX_DEPTH), &opts)
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if let Some(id) = &request_id {
+        if let Ok(mut reqs) = TREE_REQUESTS.lock() {
+            // only drop our own entry; a newer request may have replaced it
+            if reqs.get(id).is_some_and(|c| Arc::ptr_eq(c, &cancel)) {
+                reqs.remove(id);
+            }
+        }
+    }
+
+    res.map_err(|_| "cancelled".to_string())
+}
+
+#[tauri::command]
+pub fn cancel_dir_tree(request_id: String) -> bool {
+    match TREE_REQUESTS
+        .lock()
+        .ok()
+        .and_then(|mut r| r.remove(&request_id))
+    {
+        Some(cancel) => {
+            cancel.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,185 @@
+// src/api/history.rs
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::Utc;
+use tauri::{AppHandle, Manager, State};
+
+use crate::api::{
+    path_sizer::keys::normalize_maybe_missing,
+    settings::{self, load_json, save_json},
+    types::{ApiError, LocationVisit},
+};
+
+/// Locations kept; past this the lowest scores go
+const MAX_ENTRIES: usize = 500;
+/// A visit counts half as much after this long
+const HALF_LIFE_MS: f64 = 14.0 * 24.0 * 3600.0 * 1000.0;
+/// A missing location is dropped once unvisited this long (until then it may
+/// be an unplugged drive)
+const PURGE_AFTER_MS: i64 = 30 * 24 * 3600 * 1000;
+
+pub struct HistoryState {
+    file: Option<PathBuf>,
+    visits: Mutex<Vec<LocationVisit>>,
+}
+
+/// Load `<app data>/history.json` into managed state (empty when missing)
+pub fn init(app: &AppHandle) {
+    let file = app
+        .path()
+        .app_data_dir()
+        .ok()
+        .map(|d| d.join("history.json"));
+    let visits = file.as_deref().and_then(load_json).unwrap_or_default();
+    app.manage(HistoryState {
+        file,
+        visits: Mutex::new(visits),
+    });
+}
+
+fn frecency(v: &LocationVisit, now: i64) -> f64 {
+    let age = (now - v.last_visit).max(0) as f64;
+    v.visits as f64 * 0.5f64.powf(age / HALF_LIFE_MS)
+}
+
+/// Count a visit to `path`, then prune to MAX_ENTRIES by score
+fn record(visits: &mut Vec<LocationVisit>, path: String, now: i64) {
+    match visits.iter_mut().find(|v| v.path == path) {
+        Some(v) => {
+            v.visits += 1;
+            v.last_visit = now;
+        }
+        None => visits.push(LocationVisit {
+            path,
+            visits: 1,
+            last_visit: now,
+            score: 0.0,
+        }),
+    }
+    if visits.len() > MAX_ENTRIES {
+        visits.sort_by(|a, b| frecency(b, now).total_cmp(&frecency(a, now)));
+        visits.truncate(MAX_ENTRIES);
+    }
+}
+
+fn save(state: &HistoryState, visits: &[LocationVisit]) -> Result<(), ApiError> {
+    match &state.file {
+        Some(file) => save_json(file, &visits),
+        None => Ok(()),
+    }
+}
+
+/// The existing locations ordered by `key` (highest first), at most `limit`.
+/// Missing ones are left out, and forgotten once long unvisited.
+fn ranked(
+    state: &HistoryState,
+    limit: usize,
+    key: impl Fn(&LocationVisit) -> f64,
+) -> Result<Vec<LocationVisit>, ApiError> {
+    let now = Utc::now().timestamp_millis();
+    let mut visits = state.visits.lock().map_err(|e| e.to_string())?;
+    let mut found = Vec::new();
+    let mut purged = false;
+    visits.retain(|v| {
+        if Path::new(&v.path).exists() {
+            found.push(LocationVisit {
+                score: frecency(v, now),
+                ..v.clone()
+            });
+            return true;
+        }
+        let keep = now - v.last_visit < PURGE_AFTER_MS;
+        purged |= !keep;
+        keep
+    });
+    if purged {
+        save(state, &visits)?;
+    }
+    found.sort_by(|a, b| key(b).total_cmp(&key(a)));
+    found.truncate(limit);
+    Ok(found)
+}
+
+/// Note a navigation to `path`; does nothing while `track_history` is off.
+/// Returns whether it was recorded.
+#[tauri::command]
+pub fn record_visit(
+    app: AppHandle,
+    state: State<'_, HistoryState>,
+    path: String,
+) -> Result<bool, ApiError> {
+    if !settings::current(&app).track_history {
+        return Ok(false);
+    }
+    let path = normalize_maybe_missing(Path::new(&path))
+        .to_string_lossy()
+        .to_string();
+    let mut visits = state.visits.lock().map_err(|e| e.to_string())?;
+    record(&mut visits, path, Utc::now().timestamp_millis());
+    save(&state, &visits)?;
+    Ok(true)
+}
+
+/// Most recently visited first
+#[tauri::command]
+pub fn get_recent_locations(
+    state: State<'_, HistoryState>,
+    limit: usize,
+) -> Result<Vec<LocationVisit>, ApiError> {
+    ranked(&state, limit, |v| v.last_visit as f64)
+}
+
+/// Highest frecency first
+#[tauri::command]
+pub fn get_frequent_locations(
+    state: State<'_, HistoryState>,
+    limit: usize,
+) -> Result<Vec<LocationVisit>, ApiError> {
+    ranked(&state, limit, |v| v.score)
+}
+
+#[tauri::command]
+pub fn clear_history(state: State<'_, HistoryState>) -> Result<(), ApiError> {
+    let mut visits = state.visits.lock().map_err(|e| e.to_string())?;
+    visits.clear();
+    save(&state, &visits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 3600 * 1000;
+
+    #[test]
+    fn old_visits_weigh_less() {
+        let now = 100 * DAY;
+        let visit = |visits, days_ago| LocationVisit {
+            path: String::new(),
+            visits,
+            last_visit: now - days_ago * DAY,
+            score: 0.0,
+        };
+        assert_eq!(frecency(&visit(4, 0), now), 4.0);
+        assert_eq!(frecency(&visit(4, 14), now), 2.0);
+        // daily last week beats very often two months ago
+        assert!(frecency(&visit(7, 1), now) > frecency(&visit(40, 60), now));
+    }
+
+    #[test]
+    fn prunes_the_lowest_scores() {
+        let mut visits = Vec::new();
+        for i in 0..MAX_ENTRIES {
+            record(&mut visits, format!("/p/{}", i), i as i64 * DAY);
+        }
+        record(&mut visits, "/p/0".into(), MAX_ENTRIES as i64 * DAY);
+        record(&mut visits, "/p/new".into(), MAX_ENTRIES as i64 * DAY);
+        assert_eq!(visits.len(), MAX_ENTRIES);
+        // /p/1 was the stalest; /p/0 survived through its second visit
+        assert!(!visits.iter().any(|v| v.path == "/p/1"));
+        assert!(visits.iter().any(|v| v.path == "/p/0" && v.visits == 2));
+    }
+}
@@ -0,0 +1,250 @@
+// src/api/logs.rs
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Mutex,
+    },
+};
+
+use chrono::Local;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level, Metadata, Subscriber,
+};
+
+use crate::api::types::{ApiError, LogEntry};
+
+/// Log file size that triggers a rotation
+const MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+/// copycut.log plus copycut.log.1 .. .N
+const KEEP_ROTATED: usize = 3;
+/// Entries kept in memory for `get_recent_logs`
+const RECENT_MAX: usize = 2000;
+
+/// Most verbose level logged (1 = error .. 5 = trace); info by default, so
+/// per-entry traces only appear when asked for
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(3);
+static RECENT: Lazy<Mutex<VecDeque<LogEntry>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+static FILE: Lazy<Mutex<Option<LogFile>>> = Lazy::new(|| Mutex::new(None));
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    len: u64,
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> Option<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .ok()?;
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Some(Self { path, file, len })
+    }
+
+    fn write_line(&mut self, line: &[u8]) {
+        if self.len + line.len() as u64 > MAX_FILE_BYTES {
+            self.rotate();
+        }
+        if self.file.write_all(line).is_ok() {
+            self.len += line.len() as u64;
+        }
+    }
+
+    /// copycut.log -> .1 -> .2 ...; the oldest falls off
+    fn rotate(&mut self) {
+        let numbered = |n: usize| PathBuf::from(format!("{}.{}", self.path.display(), n));
+        let _ = fs::remove_file(numbered(KEEP_ROTATED));
+        for n in (1..KEEP_ROTATED).rev() {
+            let _ = fs::rename(numbered(n), numbered(n + 1));
+        }
+        let _ = fs::rename(&self.path, numbered(1));
+        if let Some(fresh) = Self::open(self.path.clone()) {
+            *self = fresh;
+        }
+    }
+}
+
+fn level_rank(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 1,
+        Level::WARN => 2,
+        Level::INFO => 3,
+        Level::DEBUG => 4,
+        Level::TRACE => 5,
+    }
+}
+
+fn parse_level(s: &str) -> Option<u8> {
+    match s.to_ascii_lowercase().as_str() {
+        "error" => Some(1),
+        "warn" | "warning" => Some(2),
+        "info" => Some(3),
+        "debug" => Some(4),
+        "trace" => Some(5),
+        _ => None,
+    }
+}
+
+/// Takes this crate's `tracing` events only; other crates stay quiet
+struct JsonLines;
+
+#[derive(Default)]
+struct Fields {
+    message: String,
+    path: Option<String>,
+    op_id: Option<String>,
+    rest: BTreeMap<String, Value>,
+}
+
+impl Fields {
+    fn put(&mut self, field: &Field, value: Value) {
+        let text = || match &value {
+            Value::String(s) => s.clone(),
+            v => v.to_string(),
+        };
+        match field.name() {
+            "message" => self.message = text(),
+            "path" => self.path = Some(text()),
+            "op_id" => self.op_id = Some(text()),
+            name => {
+                self.rest.insert(name.to_string(), value);
+            }
+        }
+    }
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.put(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.put(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.put(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.put(field, Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.put(field, Value::from(format!("{:?}", value)));
+    }
+}
+
+impl Subscriber for JsonLines {
+    fn register_callsite(
+        &self,
+        metadata: &'static Metadata<'static>,
+    ) -> tracing::subscriber::Interest {
+        // asked every time, so `set_log_level` takes effect at once
+        if metadata.target().starts_with(env!("CARGO_CRATE_NAME")) {
+            tracing::subscriber::Interest::sometimes()
+        } else {
+            tracing::subscriber::Interest::never()
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        level_rank(metadata.level()) <= MAX_LEVEL.load(Ordering::Relaxed)
+    }
+
+    // events only; spans aren't recorded
+    fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let meta = event.metadata();
+        let entry = LogEntry {
+            ts: Local::now().to_rfc3339(),
+            level: meta.level().as_str().to_ascii_lowercase(),
+            target: meta.target().to_string(),
+            message: fields.message,
+            path: fields.path,
+            op_id: fields.op_id,
+            fields: fields.rest,
+        };
+
+        if let Ok(mut line) = serde_json::to_vec(&entry) {
+            line.push(b'\n');
+            if let Ok(mut file) = FILE.lock() {
+                if let Some(f) = file.as_mut() {
+                    f.write_line(&line);
+                }
+            }
+        }
+        if let Ok(mut recent) = RECENT.lock() {
+            if recent.len() >= RECENT_MAX {
+                recent.pop_front();
+            }
+            recent.push_back(entry);
+        }
+    }
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+/// Log to `<app log dir>/copycut.log` (JSON lines, rotated) and memory
+pub fn init(app: &AppHandle) {
+    if let Ok(dir) = app.path().app_log_dir() {
+        if fs::create_dir_all(&dir).is_ok() {
+            if let Ok(mut f) = FILE.lock() {
+                *f = LogFile::open(dir.join("copycut.log"));
+            }
+        }
+    }
+    let _ = tracing::subscriber::set_global_default(JsonLines);
+}
+
+/// The last `lines` entries of this session, oldest first. `level` keeps only
+/// entries at least that severe ("warn": warnings and errors).
+#[tauri::command]
+pub fn get_recent_logs(lines: usize, level: Option<String>) -> Result<Vec<LogEntry>, ApiError> {
+    let min = match level.as_deref() {
+        Some(l) => parse_level(l)
+            .ok_or_else(|| ApiError::invalid_input(format!("unknown level: {}", l)))?,
+        None => 5,
+    };
+    let recent = RECENT.lock().map_err(|e| e.to_string())?;
+    let mut out: Vec<LogEntry> = recent
+        .iter()
+        .rev()
+        .filter(|e| parse_level(&e.level).is_some_and(|r| r <= min))
+        .take(lines)
+        .cloned()
+        .collect();
+    out.reverse();
+    Ok(out)
+}
+
+/// "error" | "warn" | "info" | "debug" | "trace"
+#[tauri::command]
+pub fn set_log_level(level: String) -> Result<(), ApiError> {
+    let rank = parse_level(&level)
+        .ok_or_else(|| ApiError::invalid_input(format!("unknown level: {}", level)))?;
+    MAX_LEVEL.store(rank, Ordering::Relaxed);
+    tracing::info!(level = level.as_str(), "log level changed");
+    Ok(())
+}
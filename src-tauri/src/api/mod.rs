@@ -1,6 +1,23 @@
 pub mod app;
+pub mod archive;
+pub mod bookmarks;
 pub mod devtools;
+pub mod dupes;
 pub mod fs_list;
+pub mod fs_meta;
 pub mod fs_ops;
+pub mod fs_path;
+pub mod fs_tree;
+pub mod history;
+pub mod logs;
 pub mod path_sizer;
+pub mod preview;
+pub mod sandbox;
+pub mod settings;
+pub mod storage;
+pub mod thumbnails;
+pub mod transfer;
+pub mod trash;
 pub mod types;
+pub mod window_ctl;
+pub mod xattrs;
@@ -1,28 +1,495 @@
-use crate::api::types::{CacheEntry, CacheKey};
+use super::{jobs, keys::normalize_maybe_missing, persist};
+use crate::api::types::{
+    CacheEntry, CacheKey, CacheStats, CachedSize, CachedSizeReply, CompositionTally,
+    InvalidatedEvent, SizerOptions, StaleEvent, TopEntry,
+};
 use once_cell::sync::Lazy;
-use std::{collections::HashMap, sync::Mutex};
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Emitter};
 
-pub static SIZE_CACHE: Lazy<Mutex<HashMap<CacheKey, CacheEntry>>> =
+pub const DEFAULT_MAX_ENTRIES: usize = 100_000;
+const TOP_CACHE_MAX: usize = 512;
+
+/// Size entries with least-recently-used eviction past `max_entries`.
+/// Partial (`completed: false`) entries are evicted before completed ones.
+pub struct SizeCache {
+    map: HashMap<CacheKey, (CacheEntry, u64)>,
+    tick: u64,
+    max_entries: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+    /// set on the first change since the last save (see `persist`)
+    dirty_since: Option<Instant>,
+    last_write: Instant,
+}
+
+impl SizeCache {
+    fn new(max_entries: usize) -> Self {
+        Self {
+            map: HashMap::new(),
+            tick: 0,
+            max_entries,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+            dirty_since: None,
+            last_write: Instant::now(),
+        }
+    }
+
+    /// The saved entries as they were written (see `persist::verify_loaded`),
+    /// trimmed to `max_entries` like any other insert
+    fn loaded(max_entries: usize) -> Self {
+        let mut cache = Self::new(max_entries);
+        for (key, entry) in persist::load() {
+            let tick = cache.next_tick();
+            cache.map.insert(key, (entry, tick));
+        }
+        if cache.map.len() > cache.max_entries {
+            cache.evict();
+        }
+        cache
+    }
+
+    fn mark_dirty(&mut self) {
+        self.last_write = Instant::now();
+        self.dirty_since.get_or_insert(self.last_write);
+    }
+
+    /// Quiet for `debounce`, or dirty for longer than `max_delay`
+    pub fn save_due(&self, debounce: Duration, max_delay: Duration) -> bool {
+        self.dirty_since.is_some_and(|since| {
+            self.last_write.elapsed() >= debounce || since.elapsed() >= max_delay
+        })
+    }
+
+    /// Entries oldest-used first (so a reload keeps the LRU order), clears the dirty mark
+    pub fn take_for_save(&mut self) -> Option<Vec<(CacheKey, CacheEntry)>> {
+        self.dirty_since.take()?;
+        let mut all: Vec<_> = self.map.iter().collect();
+        all.sort_by_key(|(_, (_, t))| *t);
+        Some(
+            all.into_iter()
+                .map(|(k, (e, _))| (k.clone(), e.clone()))
+                .collect(),
+        )
+    }
+
+    pub fn clear_dirty(&mut self) {
+        self.dirty_since = None;
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// Read and mark as recently used
+    pub fn get(&mut self, key: &CacheKey) -> Option<&CacheEntry> {
+        let tick = self.next_tick();
+        match self.map.get_mut(key) {
+            Some(slot) => {
+                self.hits += 1;
+                slot.1 = tick;
+                Some(&slot.0)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Read without touching recency or the hit counters
+    pub fn peek(&self, key: &CacheKey) -> Option<&CacheEntry> {
+        self.map.get(key).map(|(e, _)| e)
+    }
+
+    /// An estimate never replaces a finished size
+    pub fn insert(&mut self, key: CacheKey, entry: CacheEntry) {
+        if entry.estimated && self.map.get(&key).is_some_and(|(e, _)| e.completed) {
+            return;
+        }
+        let tick = self.next_tick();
+        self.map.insert(key, (entry, tick));
+        self.mark_dirty();
+        if self.map.len() > self.max_entries {
+            self.evict();
+        }
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(&CacheKey, &CacheEntry) -> bool) {
+        let before = self.map.len();
+        self.map.retain(|k, (e, _)| f(k, e));
+        if self.map.len() != before {
+            self.mark_dirty();
+        }
+    }
+
+    /// Turn matching completed entries back into hints (`completed: false`);
+    /// returns their paths
+    pub fn mark_stale(&mut self, f: impl Fn(&Path, &CacheEntry) -> bool) -> BTreeSet<PathBuf> {
+        let mut stale = BTreeSet::new();
+        for (k, (e, _)) in self.map.iter_mut() {
+            if e.completed && f(&k.path, e) {
+                e.completed = false;
+                e.complete_with_errors = false;
+                stale.insert(k.path.clone());
+            }
+        }
+        if !stale.is_empty() {
+            self.mark_dirty();
+        }
+        stale
+    }
+
+    /// Iterate without touching recency
+    pub fn iter(&self) -> impl Iterator<Item = (&CacheKey, &CacheEntry)> {
+        self.map.iter().map(|(k, (e, _))| (k, e))
+    }
+
+    pub fn set_max_entries(&mut self, max_entries: usize) {
+        self.max_entries = max_entries.max(1);
+        if self.map.len() > self.max_entries {
+            self.evict();
+        }
+    }
+
+    /// Drop down to the cap plus some slack so we don't sort on every insert
+    fn evict(&mut self) {
+        let slack = (self.max_entries / 64).max(1);
+        let excess = self.map.len().saturating_sub(self.max_entries) + slack;
+        let mut order: Vec<(bool, u64, CacheKey)> = self
+            .map
+            .iter()
+            .map(|(k, (e, t))| (e.completed, *t, k.clone()))
+            .collect();
+        let excess = excess.min(order.len());
+        if excess < order.len() {
+            order.select_nth_unstable_by_key(excess, |o| (o.0, o.1));
+        }
+        for (_, _, k) in order.into_iter().take(excess) {
+            self.map.remove(&k);
+        }
+        self.evictions += excess as u64;
+        tracing::info!(
+            evicted = excess,
+            max_entries = self.max_entries,
+            "size cache evicted entries"
+        );
+        self.mark_dirty();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let per_entry =
+            (std::mem::size_of::<CacheKey>() + std::mem::size_of::<(CacheEntry, u64)>()) as u64;
+        let approx_bytes = self
+            .map
+            .keys()
+            .map(|k| per_entry + k.path.as_os_str().len() as u64 + k.ignores_sig.len() as u64)
+            .sum();
+        CacheStats {
+            entries: self.map.len(),
+            partial_entries: self.map.values().filter(|(e, _)| !e.completed).count(),
+            max_entries: self.max_entries,
+            approx_bytes,
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+        }
+    }
+}
+
+pub static SIZE_CACHE: Lazy<Mutex<SizeCache>> =
+    Lazy::new(|| Mutex::new(SizeCache::loaded(DEFAULT_MAX_ENTRIES)));
+
+/// Completed extension breakdowns, keyed exactly like SIZE_CACHE; bounded
+/// like TOP_CACHE
+static COMPOSITION_CACHE: Lazy<Mutex<HashMap<CacheKey, (CompositionTally, Instant)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A report and when it was stored
+type TopReport = (Vec<TopEntry>, Instant);
+
+/// Largest files/folders from finished scans (roots and their children), keyed
+/// like SIZE_CACHE. Small and in-memory only; the oldest report goes when full.
+static TOP_CACHE: Lazy<Mutex<HashMap<CacheKey, TopReport>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
-/// Command: prefill multiple paths
+/// What a finished root scan left out, so a cached answer can report it too
+#[derive(Clone, Default)]
+pub struct LeftOut {
+    pub skipped_mounts: Vec<String>,
+    pub unreadable: u64,
+    pub junctions: u64,
+}
+
+/// `LeftOut` of finished root scans, keyed like SIZE_CACHE; in-memory only and
+/// bounded like TOP_CACHE
+static LEFT_OUT: Lazy<Mutex<HashMap<CacheKey, (LeftOut, Instant)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Insert into one of the small in-memory caches, making room by dropping the
+/// oldest entry
+fn insert_bounded<V>(map: &Mutex<HashMap<CacheKey, (V, Instant)>>, key: CacheKey, value: V) {
+    let Ok(mut map) = map.lock() else {
+        return;
+    };
+    if map.len() >= TOP_CACHE_MAX && !map.contains_key(&key) {
+        let oldest = map
+            .iter()
+            .min_by_key(|(_, (_, at))| *at)
+            .map(|(k, _)| k.clone());
+        if let Some(k) = oldest {
+            map.remove(&k);
+        }
+    }
+    map.insert(key, (value, Instant::now()));
+}
+
+pub fn store_top(key: CacheKey, entries: Vec<TopEntry>) {
+    insert_bounded(&TOP_CACHE, key, entries);
+}
+
+pub fn top_for(key: &CacheKey) -> Option<Vec<TopEntry>> {
+    TOP_CACHE.lock().ok()?.get(key).map(|(e, _)| e.clone())
+}
+
+pub fn store_composition(key: CacheKey, tally: CompositionTally) {
+    insert_bounded(&COMPOSITION_CACHE, key, tally);
+}
+
+pub fn composition_for(key: &CacheKey) -> Option<CompositionTally> {
+    COMPOSITION_CACHE
+        .lock()
+        .ok()?
+        .get(key)
+        .map(|(t, _)| t.clone())
+}
+
+pub fn store_left_out(key: CacheKey, left_out: LeftOut) {
+    insert_bounded(&LEFT_OUT, key, left_out);
+}
+
+pub fn left_out_for(key: &CacheKey) -> Option<LeftOut> {
+    LEFT_OUT.lock().ok()?.get(key).map(|(l, _)| l.clone())
+}
+
+fn to_cached_size(entry: &CacheEntry, approximate: bool) -> CachedSize {
+    CachedSize {
+        bytes: entry.bytes,
+        items: entry.items,
+        completed: entry.completed,
+        age_ms: entry
+            .updated_at
+            .elapsed()
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+        approximate,
+        estimated: entry.estimated,
+        errors: entry.errors,
+        complete_with_errors: entry.complete_with_errors,
+    }
+}
+
+/// Best entry for the same path under other options: completed first, then newest
+fn approximate_for<'a>(cache: &'a SizeCache, path: &Path) -> Option<&'a CacheEntry> {
+    cache
+        .iter()
+        .filter(|(k, _)| k.path == path)
+        .map(|(_, e)| e)
+        .max_by_key(|e| (e.completed, e.updated_at))
+}
+
+/// Command: prefill multiple paths.
+/// `version` 1 keeps the old `(bytes, items, completed)` tuples.
 pub fn get_cached_sizes(
     paths: Vec<String>,
     show_hidden: bool,
     ignores: Vec<String>,
-) -> Result<Vec<Option<(u64, u64, bool)>>, String> {
-    use super::keys::make_cache_key;
+    opts: &SizerOptions,
+    approximate: bool,
+    version: u32,
+) -> Result<Vec<Option<CachedSizeReply>>, String> {
+    use super::keys::make_child_cache_key;
 
-    let key_for = |p: &str| make_cache_key(p, show_hidden, &ignores);
-    let cache = SIZE_CACHE.lock().map_err(|e| e.to_string())?;
+    // paths are the children of the listed folder, keyed like the sizer's children
+    let key_for = |p: &str| make_child_cache_key(p, show_hidden, &ignores, opts);
+    let mut cache = SIZE_CACHE.lock().map_err(|e| e.to_string())?;
     let mut out = Vec::with_capacity(paths.len());
     for p in paths {
         let k = key_for(&p);
-        if let Some(entry) = cache.get(&k) {
-            out.push(Some((entry.bytes, entry.items, entry.completed)));
-        } else {
-            out.push(None);
-        }
+        let found = match cache.get(&k) {
+            Some(entry) => Some(to_cached_size(entry, false)),
+            None if approximate => {
+                approximate_for(&cache, &k.path).map(|e| to_cached_size(e, true))
+            }
+            None => None,
+        };
+        out.push(found.map(|c| {
+            if version == 1 {
+                CachedSizeReply::Legacy((c.bytes, c.items, c.completed))
+            } else {
+                CachedSizeReply::Full(c)
+            }
+        }));
     }
     Ok(out)
 }
+
+/// Remove matching entries from the size, composition, top and left-out caches; returns how many size entries
+/// went and every affected path.
+fn drop_where(pred: impl Fn(&Path) -> bool) -> (usize, BTreeSet<PathBuf>) {
+    let mut removed = 0;
+    let mut affected = BTreeSet::new();
+
+    if let Ok(mut cache) = SIZE_CACHE.lock() {
+        cache.retain(|k, _| {
+            if pred(&k.path) {
+                affected.insert(k.path.clone());
+                removed += 1;
+                return false;
+            }
+            true
+        });
+    }
+    if let Ok(mut comp) = COMPOSITION_CACHE.lock() {
+        comp.retain(|k, _| {
+            if pred(&k.path) {
+                affected.insert(k.path.clone());
+                return false;
+            }
+            true
+        });
+    }
+    if let Ok(mut tops) = TOP_CACHE.lock() {
+        tops.retain(|k, _| !pred(&k.path));
+    }
+    if let Ok(mut left_out) = LEFT_OUT.lock() {
+        left_out.retain(|k, _| !pred(&k.path));
+    }
+    (removed, affected)
+}
+
+/// Drop every option variant of one path's entries (a root that turned out
+/// missing or unreadable); returns how many size entries went
+pub fn forget_path(path: &Path) -> usize {
+    drop_where(|k| k == path).0
+}
+
+fn emit_invalidated(app: &AppHandle, affected: BTreeSet<PathBuf>) {
+    if affected.is_empty() {
+        return;
+    }
+    let _ = app.emit(
+        "dir_size:invalidated",
+        InvalidatedEvent {
+            paths: affected
+                .into_iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect(),
+        },
+    );
+}
+
+/// Tell the UI these paths' sizes are hints now (`dir_size:stale`)
+pub fn emit_stale(app: &AppHandle, paths: BTreeSet<PathBuf>) {
+    if paths.is_empty() {
+        return;
+    }
+    let _ = app.emit(
+        "dir_size:stale",
+        StaleEvent {
+            paths: paths
+                .into_iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect(),
+        },
+    );
+}
+
+/// What `invalidate_related` does with the entries it hits
+#[derive(Clone, Copy)]
+pub enum Invalidation {
+    /// forget them (`dir_size:invalidated`)
+    Drop,
+    /// keep them as hints, `completed: false` (`dir_size:stale`)
+    Stale,
+}
+
+/// Invalidate every entry (all show_hidden/ignores variants) whose path equals,
+/// contains or lies inside one of `paths`. Ancestors go too since their totals
+/// include the changed subtree. Emits the affected entry paths as `how` says.
+/// `Drop` also cancels related running scans first, so they can't write their
+/// pre-change totals back as finished.
+/// `paths` should already be normalized (see `keys::normalize_maybe_missing`).
+pub fn invalidate_related(app: &AppHandle, paths: &[PathBuf], how: Invalidation) {
+    let related = |k: &Path| paths.iter().any(|p| k.starts_with(p) || p.starts_with(k));
+    match how {
+        Invalidation::Drop => {
+            jobs::JOBS.cancel_where(|key| related(&key.path));
+            emit_invalidated(app, drop_where(related).1)
+        }
+        Invalidation::Stale => {
+            let stale = SIZE_CACHE
+                .lock()
+                .map(|mut c| c.mark_stale(|k, _| related(k)))
+                .unwrap_or_default();
+            // a breakdown has no unfinished form to fall back to
+            if let Ok(mut comp) = COMPOSITION_CACHE.lock() {
+                comp.retain(|k, _| !related(&k.path));
+            }
+            emit_stale(app, stale);
+        }
+    }
+}
+
+/// Command: drop entries for `path` (plus descendants when `recursive`) in all
+/// option variants, or everything without a path. Scans rooted inside the area
+/// are cancelled so they don't write stale partials back. Returns entries removed.
+pub fn invalidate_size_cache(
+    app: &AppHandle,
+    path: Option<String>,
+    recursive: bool,
+) -> Result<usize, String> {
+    let target = path.map(|p| normalize_maybe_missing(Path::new(&p)));
+    let inside = |k: &Path| match &target {
+        Some(t) => k == t || (recursive && k.starts_with(t)),
+        None => true,
+    };
+
+    jobs::JOBS.cancel_where(|key| inside(&key.path));
+    let (removed, affected) = drop_where(inside);
+    emit_invalidated(app, affected);
+    Ok(removed)
+}
+
+/// Command: entry counts, rough memory use and hit/miss counters
+pub fn get_cache_stats() -> Result<CacheStats, String> {
+    Ok(SIZE_CACHE.lock().map_err(|e| e.to_string())?.stats())
+}
+
+pub fn set_max_entries(max_entries: usize) -> Result<(), String> {
+    SIZE_CACHE
+        .lock()
+        .map_err(|e| e.to_string())?
+        .set_max_entries(max_entries);
+    Ok(())
+}
+
+/// Command: the top report of the last finished scan of `path` with these options
+pub fn get_top_entries(
+    path: &str,
+    show_hidden: bool,
+    ignores: &[String],
+    opts: &SizerOptions,
+) -> Result<Option<Vec<TopEntry>>, String> {
+    let key = super::keys::make_cache_key(path, show_hidden, ignores, opts);
+    Ok(top_for(&key))
+}
@@ -0,0 +1,230 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use tauri::{AppHandle, Emitter};
+
+use crate::api::{
+    path_sizer::{
+        cache::{composition_for, store_composition},
+        keys::{make_cache_key, IgnoreSet},
+        worker::root_error,
+    },
+    types::{ApiError, CompositionBucket, CompositionEvent, CompositionTally, SizerOptions},
+};
+
+const DEFAULT_TOP_N: usize = 10;
+const PARTIAL_EVERY: Duration = Duration::from_millis(250);
+
+static RUNNING: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn category_of(ext: &str) -> &'static str {
+    match ext {
+        "jpg" | "jpeg" | "png" | "gif" | "bmp" | "webp" | "svg" | "heic" | "tif" | "tiff"
+        | "raw" | "cr2" | "nef" | "arw" | "dng" | "ico" => "images",
+        "mp4" | "mkv" | "mov" | "avi" | "webm" | "wmv" | "flv" | "m4v" | "mpg" | "mpeg" => "video",
+        "mp3" | "flac" | "wav" | "ogg" | "m4a" | "aac" | "opus" | "wma" => "audio",
+        "rs" | "ts" | "tsx" | "js" | "jsx" | "mjs" | "py" | "go" | "c" | "h" | "cc" | "cpp"
+        | "hpp" | "java" | "kt" | "swift" | "rb" | "php" | "cs" | "html" | "css" | "scss"
+        | "json" | "toml" | "yaml" | "yml" | "sh" | "lua" | "sql" => "code",
+        "zip" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "7z" | "rar" | "zst" | "iso" | "dmg" => {
+            "archives"
+        }
+        "pdf" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "odt" | "ods" | "txt" | "md"
+        | "rtf" | "epub" => "documents",
+        _ => "other",
+    }
+}
+
+fn to_event(
+    tally: &CompositionTally,
+    request_id: &str,
+    path: &str,
+    top_n: usize,
+    done: bool,
+) -> CompositionEvent {
+    let mut exts: Vec<(&String, &(u64, u64))> = tally.by_ext.iter().collect();
+    exts.sort_by_key(|e| Reverse(e.1 .0));
+
+    let mut by_extension: Vec<CompositionBucket> = exts
+        .iter()
+        .take(top_n)
+        .map(|(ext, (bytes, files))| CompositionBucket {
+            key: ext.to_string(),
+            bytes: *bytes,
+            files: *files,
+        })
+        .collect();
+    let (other_bytes, other_files) = exts
+        .iter()
+        .skip(top_n)
+        .fold((0u64, 0u64), |acc, (_, (b, f))| (acc.0 + b, acc.1 + f));
+    if other_files > 0 {
+        by_extension.push(CompositionBucket {
+            key: "other".into(),
+            bytes: other_bytes,
+            files: other_files,
+        });
+    }
+
+    let mut cats: HashMap<&'static str, (u64, u64)> = HashMap::new();
+    for (ext, (bytes, files)) in &tally.by_ext {
+        let c = cats.entry(category_of(ext)).or_default();
+        c.0 += bytes;
+        c.1 += files;
+    }
+    let mut by_category: Vec<CompositionBucket> = cats
+        .into_iter()
+        .map(|(key, (bytes, files))| CompositionBucket {
+            key: key.to_string(),
+            bytes,
+            files,
+        })
+        .collect();
+    by_category.sort_by_key(|b| Reverse(b.bytes));
+
+    CompositionEvent {
+        request_id: request_id.to_string(),
+        path: path.to_string(),
+        total_bytes: tally.total_bytes,
+        total_files: tally.total_files,
+        by_extension,
+        by_category,
+        done,
+    }
+}
+
+/// Walk `root`, emitting `composition:partial` to the `label` window
+/// periodically. Returns None when cancelled.
+fn walk(
+    app: &AppHandle,
+    label: &str,
+    root: &Path,
+    request_id: &str,
+    ignores: &IgnoreSet,
+    top_n: usize,
+    cancel: &AtomicBool,
+) -> Option<CompositionTally> {
+    let mut tally = CompositionTally::default();
+    let mut last_emit = Instant::now();
+    let path_str = root.to_string_lossy().to_string();
+
+    for entry in walkdir::WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !ignores.is_skipped(
+                    e.path().strip_prefix(root).unwrap_or(e.path()),
+                    e.file_type().is_dir(),
+                )
+        })
+        .filter_map(|e| e.ok())
+    {
+        if cancel.load(Ordering::SeqCst) {
+            return None;
+        }
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(md) = entry.metadata() else {
+            continue;
+        };
+        let ext = entry
+            .path()
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let slot = tally.by_ext.entry(ext).or_default();
+        slot.0 = slot.0.saturating_add(md.len());
+        slot.1 += 1;
+        tally.total_bytes = tally.total_bytes.saturating_add(md.len());
+        tally.total_files += 1;
+
+        if last_emit.elapsed() >= PARTIAL_EVERY {
+            let _ = app.emit_to(
+                label,
+                "composition:partial",
+                to_event(&tally, request_id, &path_str, top_n, false),
+            );
+            last_emit = Instant::now();
+        }
+    }
+    Some(tally)
+}
+
+/// Events go to the `label` window only
+pub fn folder_composition_impl(
+    app: AppHandle,
+    label: String,
+    path: String,
+    request_id: String,
+    show_hidden: bool,
+    ignores: Vec<String>,
+    top_n: Option<usize>,
+) -> Result<(), ApiError> {
+    let top_n = top_n.unwrap_or(DEFAULT_TOP_N);
+    let skip = IgnoreSet::new(show_hidden, &ignores).map_err(ApiError::invalid_input)?;
+    let cache_key = make_cache_key(&path, show_hidden, &ignores, &SizerOptions::default());
+
+    // A missing or unreadable root is an error, not an empty breakdown
+    if let Some(e) = root_error(Path::new(&path)) {
+        return Err(ApiError::from(e).with_path(&path));
+    }
+
+    // Cached breakdown: answer right away
+    if let Some(tally) = composition_for(&cache_key) {
+        let _ = app.emit_to(
+            label.as_str(),
+            "composition:done",
+            to_event(&tally, &request_id, &path, top_n, true),
+        );
+        return Ok(());
+    }
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut running = RUNNING.lock().map_err(|e| e.to_string())?;
+        if running.contains_key(&request_id) {
+            return Err(ApiError::invalid_input(format!(
+                "request {} already running",
+                request_id
+            )));
+        }
+        running.insert(request_id.clone(), cancel.clone());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = Path::new(&path);
+        if let Some(tally) = walk(&app, &label, root, &request_id, &skip, top_n, &cancel) {
+            store_composition(cache_key, tally.clone());
+            let _ = app.emit_to(
+                label.as_str(),
+                "composition:done",
+                to_event(&tally, &request_id, &path, top_n, true),
+            );
+        }
+        let _ = RUNNING.lock().map(|mut r| r.remove(&request_id));
+    });
+
+    Ok(())
+}
+
+pub fn cancel(request_id: &str) -> bool {
+    match RUNNING.lock().ok().and_then(|mut r| r.remove(request_id)) {
+        Some(c) => {
+            c.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::sync::Notify;
+
+pub const MIN_CONCURRENCY: usize = 1;
+pub const MAX_CONCURRENCY: usize = 32;
+const FALLBACK: usize = 4;
+
+/// Process-wide default for scans that don't pass `concurrency`; 0 = per volume
+static DEFAULT: AtomicUsize = AtomicUsize::new(0);
+
+/// How often a waiting scan re-reads the limit, so a changed default reaches
+/// scans that are already running
+const RECHECK_EVERY: Duration = Duration::from_millis(250);
+
+pub fn set_default(n: usize) {
+    let n = if n == 0 {
+        0
+    } else {
+        n.clamp(MIN_CONCURRENCY, MAX_CONCURRENCY)
+    };
+    DEFAULT.store(n, Ordering::Relaxed);
+}
+
+/// Child-scan slots for one scan. With an explicit `concurrency` the limit is
+/// fixed; otherwise it follows the global default (or the volume's guess).
+pub struct Limiter {
+    explicit: Option<usize>,
+    volume_default: usize,
+    running: AtomicUsize,
+    freed: Notify,
+}
+
+pub struct Slot {
+    limiter: Arc<Limiter>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.limiter.running.fetch_sub(1, Ordering::SeqCst);
+        self.limiter.freed.notify_one();
+    }
+}
+
+impl Limiter {
+    pub fn new(explicit: Option<usize>, root: &Path) -> Arc<Self> {
+        let explicit = explicit.map(|n| n.clamp(MIN_CONCURRENCY, MAX_CONCURRENCY));
+        Arc::new(Self {
+            explicit,
+            volume_default: if explicit.is_some() {
+                FALLBACK
+            } else {
+                volume_default(root)
+            },
+            running: AtomicUsize::new(0),
+            freed: Notify::new(),
+        })
+    }
+
+    pub fn limit(&self) -> usize {
+        self.explicit
+            .unwrap_or_else(|| match DEFAULT.load(Ordering::Relaxed) {
+                0 => self.volume_default,
+                n => n,
+            })
+    }
+
+    /// Wait for a free slot; held until the returned guard drops
+    pub async fn acquire(self: &Arc<Self>) -> Slot {
+        loop {
+            let running = self.running.load(Ordering::SeqCst);
+            if running < self.limit()
+                && self
+                    .running
+                    .compare_exchange(running, running + 1, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok()
+            {
+                return Slot {
+                    limiter: self.clone(),
+                };
+            }
+            let _ = tokio::time::timeout(RECHECK_EVERY, self.freed.notified()).await;
+        }
+    }
+}
+
+/// Spinning disks seek themselves to death under many walkers; SSDs keep up
+#[cfg(target_os = "linux")]
+fn volume_default(root: &Path) -> usize {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(md) = std::fs::metadata(root) else {
+        return FALLBACK;
+    };
+    let dev = md.dev();
+    let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+    let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+    // major 0: network/virtual filesystems, latency-bound
+    if major == 0 {
+        return 2;
+    }
+    // partitions have no queue/ of their own; it's on the parent disk
+    let sys = Path::new("/sys/dev/block").join(format!("{}:{}", major, minor));
+    let rotational = std::fs::read_to_string(sys.join("queue/rotational"))
+        .or_else(|_| std::fs::read_to_string(sys.join("../queue/rotational")));
+    match rotational.as_deref().map(str::trim) {
+        Ok("1") => 2,
+        Ok("0") => 8,
+        _ => FALLBACK,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn volume_default(_root: &Path) -> usize {
+    FALLBACK
+}
@@ -0,0 +1,335 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::api::types::{
+    ChildEvent, ChildSize, ChildrenEvent, ErrorsEvent, ProgressBatchEvent, ProgressEvent,
+    ProgressSize, ScanErrorEvent, SummaryEvent, TopEvent, VolumeEvent,
+};
+
+const FLUSH_EVERY: Duration = Duration::from_millis(100);
+const FLUSH_AT: usize = 50;
+
+/// What one caller asked its events to look like: the ids they echo, the
+/// format (`batch_events`) and how much of the top report it wants
+#[derive(Clone, Default, PartialEq)]
+pub struct Delivery {
+    pub job_id: String,
+    pub echo_path: String,
+    pub batched: bool,
+    /// 0: no `dir_size:top`
+    pub top_n: usize,
+}
+
+#[derive(Clone, PartialEq)]
+struct Subscriber {
+    label: String,
+    /// every window rather than just `label`
+    broadcast: bool,
+    delivery: Delivery,
+}
+
+/// The windows a scan's events go to, each as its own caller asked for them.
+/// Clones share the subscribers, so a window joining a running job gets the
+/// rest of its events, in its own format.
+#[derive(Clone, Default)]
+pub struct Audience(Arc<Mutex<Vec<Subscriber>>>);
+
+impl Audience {
+    pub fn new(label: &str, broadcast: bool) -> Self {
+        Self(Arc::new(Mutex::new(vec![Subscriber {
+            label: label.to_string(),
+            broadcast,
+            delivery: Delivery::default(),
+        }])))
+    }
+
+    /// Set how this (not yet joined) audience wants its events
+    pub fn set_delivery(&self, delivery: &Delivery) {
+        if let Ok(mut subs) = self.0.lock() {
+            for s in subs.iter_mut() {
+                s.delivery = delivery.clone();
+            }
+        }
+    }
+
+    /// Add `other`'s subscribers to these
+    pub fn join(&self, other: &Audience) {
+        if Arc::ptr_eq(&self.0, &other.0) {
+            return;
+        }
+        let Ok(theirs) = other.0.lock() else {
+            return;
+        };
+        if let Ok(mut mine) = self.0.lock() {
+            for s in theirs.iter() {
+                if !mine.contains(s) {
+                    mine.push(s.clone());
+                }
+            }
+        }
+    }
+
+    pub fn emit<E: Addressed>(&self, app: &AppHandle, event: &str, payload: E) {
+        self.send(app, event, &payload, None);
+    }
+
+    /// Emit to the subscribers that asked for (or against) batched events
+    fn emit_as<E: Addressed>(&self, app: &AppHandle, event: &str, payload: E, batched: bool) {
+        self.send(app, event, &payload, Some(batched));
+    }
+
+    fn send<E: Addressed>(&self, app: &AppHandle, event: &str, payload: &E, batched: Option<bool>) {
+        for (label, payload) in self.recipients(payload, batched) {
+            let _ = match label {
+                Some(label) => app.emit_to(label.as_str(), event, payload),
+                None => app.emit(event, payload),
+            };
+        }
+    }
+
+    /// Who gets `payload`, each with its own copy: a window label, or None for
+    /// every window. `batched` keeps only the subscribers in that format.
+    pub fn recipients<E: Addressed>(
+        &self,
+        payload: &E,
+        batched: Option<bool>,
+    ) -> Vec<(Option<String>, E)> {
+        let Ok(subs) = self.0.lock() else {
+            return Vec::new();
+        };
+        subs.iter()
+            .filter(|s| batched.is_none_or(|b| s.delivery.batched == b))
+            // a broadcast with the same delivery reaches this window already
+            .filter(|s| {
+                s.broadcast || !subs.iter().any(|b| b.broadcast && b.delivery == s.delivery)
+            })
+            .filter_map(|s| {
+                let mut payload = payload.clone();
+                payload
+                    .address(&s.delivery)
+                    .then(|| ((!s.broadcast).then(|| s.label.clone()), payload))
+            })
+            .collect()
+    }
+}
+
+/// A scan event, rewritten for each subscriber it goes to
+pub trait Addressed: Serialize + Clone {
+    /// Fit the payload to `to`; false when `to` doesn't want it at all
+    fn address(&mut self, to: &Delivery) -> bool;
+}
+
+macro_rules! addressed {
+    ($($event:ty),*) => {$(
+        impl Addressed for $event {
+            fn address(&mut self, to: &Delivery) -> bool {
+                self.job_id.clone_from(&to.job_id);
+                self.echo_path.clone_from(&to.echo_path);
+                true
+            }
+        }
+    )*};
+}
+
+addressed!(
+    ChildEvent,
+    ProgressEvent,
+    ChildrenEvent,
+    ProgressBatchEvent,
+    SummaryEvent,
+    ErrorsEvent,
+    ScanErrorEvent,
+    VolumeEvent
+);
+
+impl Addressed for TopEvent {
+    fn address(&mut self, to: &Delivery) -> bool {
+        self.job_id.clone_from(&to.job_id);
+        self.echo_path.clone_from(&to.echo_path);
+        self.entries.truncate(to.top_n);
+        to.top_n > 0
+    }
+}
+
+enum Pending {
+    Child(ChildEvent),
+    Progress(ProgressEvent),
+}
+
+/// Where a scan's child/progress events go: one IPC event each to the
+/// subscribers in the legacy mode the UI listens for, and through an
+/// aggregator task as `dir_size:children` / `dir_size:progress_batch` to
+/// those that asked for batches.
+#[derive(Clone)]
+pub struct ScanEvents {
+    app: AppHandle,
+    audience: Audience,
+    batch: UnboundedSender<Pending>,
+}
+
+impl ScanEvents {
+    /// Returns the sink plus the aggregator to await before the summary goes
+    /// out (it finishes once every sink clone is dropped).
+    pub fn new(
+        app: &AppHandle,
+        audience: &Audience,
+        job_id: &str,
+        scan_key: &str,
+        echo_path: &str,
+    ) -> (Self, tauri::async_runtime::JoinHandle<()>) {
+        let (tx, rx) = unbounded_channel();
+        let flusher = tauri::async_runtime::spawn(aggregate(
+            app.clone(),
+            audience.clone(),
+            job_id.to_string(),
+            scan_key.to_string(),
+            echo_path.to_string(),
+            rx,
+        ));
+        (
+            Self {
+                app: app.clone(),
+                audience: audience.clone(),
+                batch: tx,
+            },
+            flusher,
+        )
+    }
+
+    pub fn child(&self, ev: ChildEvent) {
+        self.audience
+            .emit_as(&self.app, "dir_size:child", ev.clone(), false);
+        let _ = self.batch.send(Pending::Child(ev));
+    }
+
+    pub fn progress(&self, ev: ProgressEvent) {
+        self.audience
+            .emit_as(&self.app, "dir_size:progress", ev.clone(), false);
+        let _ = self.batch.send(Pending::Progress(ev));
+    }
+}
+
+/// Emit already-known children in one go (cache fast path)
+pub fn emit_children(
+    app: &AppHandle,
+    audience: &Audience,
+    job_id: &str,
+    scan_key: &str,
+    echo_path: &str,
+    children: Vec<ChildEvent>,
+) {
+    for ev in &children {
+        audience.emit_as(app, "dir_size:child", ev.clone(), false);
+    }
+    if !children.is_empty() {
+        audience.emit_as(
+            app,
+            "dir_size:children",
+            ChildrenEvent {
+                job_id: job_id.to_string(),
+                scan_key: scan_key.to_string(),
+                echo_path: echo_path.to_string(),
+                children: children.into_iter().map(child_size).collect(),
+            },
+            true,
+        );
+    }
+}
+
+fn child_size(ev: ChildEvent) -> ChildSize {
+    ChildSize {
+        name: ev.name,
+        bytes: ev.bytes,
+        items: ev.items,
+        files: ev.files,
+        dirs: ev.dirs,
+        other_device: ev.other_device,
+        junction: ev.junction,
+        errors: ev.errors,
+    }
+}
+
+/// Collect events and flush every FLUSH_EVERY or at FLUSH_AT entries.
+/// Progress keeps only the latest partial per child, and none for a child
+/// whose final size is in the same batch.
+async fn aggregate(
+    app: AppHandle,
+    audience: Audience,
+    job_id: String,
+    scan_key: String,
+    echo_path: String,
+    mut rx: UnboundedReceiver<Pending>,
+) {
+    let mut children: Vec<ChildSize> = Vec::new();
+    let mut progress: Vec<ProgressSize> = Vec::new();
+    let mut expected: (Option<u64>, Option<u64>) = (None, None);
+    let mut open = true;
+
+    while open {
+        let deadline = tokio::time::Instant::now() + FLUSH_EVERY;
+        while children.len() + progress.len() < FLUSH_AT {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(Pending::Child(ev))) => {
+                    progress.retain(|p| p.name != ev.name);
+                    children.push(child_size(ev));
+                }
+                Ok(Some(Pending::Progress(ev))) => {
+                    expected = (ev.expected_bytes, ev.expected_items);
+                    let p = ProgressSize {
+                        name: ev.name,
+                        bytes: ev.bytes,
+                        items: ev.items,
+                        files: ev.files,
+                        dirs: ev.dirs,
+                        bytes_per_sec: ev.bytes_per_sec,
+                        eta_ms: ev.eta_ms,
+                    };
+                    match progress.iter_mut().find(|q| q.name == p.name) {
+                        Some(q) => *q = p,
+                        None => progress.push(p),
+                    }
+                }
+                Ok(None) => {
+                    open = false;
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+
+        if !children.is_empty() {
+            audience.emit_as(
+                &app,
+                "dir_size:children",
+                ChildrenEvent {
+                    job_id: job_id.clone(),
+                    scan_key: scan_key.clone(),
+                    echo_path: echo_path.clone(),
+                    children: std::mem::take(&mut children),
+                },
+                true,
+            );
+        }
+        if !progress.is_empty() {
+            audience.emit_as(
+                &app,
+                "dir_size:progress_batch",
+                ProgressBatchEvent {
+                    job_id: job_id.clone(),
+                    scan_key: scan_key.clone(),
+                    echo_path: echo_path.clone(),
+                    progress: std::mem::take(&mut progress),
+                    expected_bytes: expected.0,
+                    expected_items: expected.1,
+                },
+                true,
+            );
+        }
+    }
+}
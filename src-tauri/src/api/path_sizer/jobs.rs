@@ -1,21 +1,333 @@
 use once_cell::sync::Lazy;
-use std::{collections::HashMap, sync::Mutex};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
 
-use crate::api::types::Job;
+use tokio::sync::watch;
 
-pub static JOBS: Lazy<Mutex<HashMap<String, Job>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+use crate::api::types::{ActiveScan, ApiError, CacheKey, Job, JobExtras, ScanOutcome};
 
-pub fn insert_if_absent(scan_key: String, job: Job) -> bool {
-    if let Ok(mut j) = JOBS.lock() {
-        if j.contains_key(&scan_key) {
-            return false;
+/// The running scans
+pub static JOBS: Lazy<Registry> = Lazy::new(Registry::default);
+
+/// Running jobs by scan_key
+#[derive(Default)]
+pub struct Registry {
+    jobs: Mutex<HashMap<String, Job>>,
+    /// visible-priority jobs in `jobs` that aren't paused (see `holds_back`);
+    /// kept in step under its lock
+    visible: AtomicUsize,
+}
+
+/// Whether `job` makes background scans wait: visible and not paused
+fn holds_back(job: &Job) -> bool {
+    job.visible.load(Ordering::SeqCst) && !job.paused.load(Ordering::SeqCst)
+}
+
+impl Registry {
+    /// Background scans hold back (start no children, park their walks) while true
+    pub fn visible_active(&self) -> bool {
+        self.visible.load(Ordering::SeqCst) > 0
+    }
+
+    /// Bookkeeping for a job leaving the registry
+    fn forget(&self, job: &Job) {
+        if holds_back(job) {
+            self.visible.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Add `job` unless one runs for `scan_key` already and works out all `job`
+    /// would; that one then sends its events to `job`'s audience too (None).
+    /// A running job doing less is cancelled and replaced by `job`, which takes
+    /// over its audience, waiters and extras. Returns the extras `job` must work
+    /// out when it was added.
+    pub fn insert_if_absent(&self, scan_key: String, mut job: Job) -> Option<JobExtras> {
+        let mut j = self.jobs.lock().ok()?;
+        if let Some(running) = j.get(&scan_key) {
+            if running.extras.covers(&job.extras) {
+                running.audience.join(&job.audience);
+                return None;
+            }
+        }
+        if let Some(outgrown) = j.remove(&scan_key) {
+            self.forget(&outgrown);
+            outgrown.cancel.store(true, Ordering::SeqCst);
+            job.audience.join(&outgrown.audience);
+            job.extras = job.extras.merge(outgrown.extras);
+            if outgrown.visible.load(Ordering::SeqCst) {
+                job.visible.store(true, Ordering::SeqCst);
+            }
+            job.paused
+                .store(outgrown.paused.load(Ordering::SeqCst), Ordering::SeqCst);
+            job.outcome = outgrown.outcome;
+        }
+        if holds_back(&job) {
+            self.visible.fetch_add(1, Ordering::SeqCst);
         }
+        let extras = job.extras;
         j.insert(scan_key, job);
-        return true;
+        Some(extras)
+    }
+
+    /// Make a background job visible; false when there's no such job or it
+    /// already was
+    pub fn promote(&self, scan_key: &str) -> bool {
+        let Ok(j) = self.jobs.lock() else {
+            return false;
+        };
+        match j.get(scan_key) {
+            Some(job) if !job.visible.swap(true, Ordering::SeqCst) => {
+                if holds_back(job) {
+                    self.visible.fetch_add(1, Ordering::SeqCst);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Drop the job for `scan_key`, but only if it is still the one owning `cancel`
+    /// (a cancelled scan must not remove a newer job started for the same key).
+    pub fn remove(&self, scan_key: &str, cancel: &Arc<AtomicBool>) {
+        if let Ok(mut j) = self.jobs.lock() {
+            if j.get(scan_key)
+                .is_some_and(|job| Arc::ptr_eq(&job.cancel, cancel))
+            {
+                if let Some(job) = j.remove(scan_key) {
+                    self.forget(&job);
+                }
+            }
+        }
+    }
+
+    /// Record how the job owning `cancel` ended, for `wait` callers
+    pub fn set_outcome(&self, scan_key: &str, cancel: &Arc<AtomicBool>, outcome: ScanOutcome) {
+        if let Ok(j) = self.jobs.lock() {
+            if let Some(job) = j
+                .get(scan_key)
+                .filter(|job| Arc::ptr_eq(&job.cancel, cancel))
+            {
+                job.outcome.send_replace(Some(outcome));
+            }
+        }
+    }
+
+    /// Wait for the running job of `scan_key` to end. None when there's no such
+    /// job (anymore); a cancelled job ends in an error.
+    pub async fn wait(&self, scan_key: &str) -> Option<ScanOutcome> {
+        let mut rx: watch::Receiver<Option<ScanOutcome>> =
+            self.jobs.lock().ok()?.get(scan_key)?.outcome.subscribe();
+        let ended = rx
+            .wait_for(|o| o.is_some())
+            .await
+            .ok()
+            .and_then(|o| o.clone());
+        Some(ended.unwrap_or_else(|| Err(ApiError::cancelled())))
+    }
+
+    /// Flip the cancel flag of the job for `scan_key` and forget it.
+    pub fn cancel(&self, scan_key: &str) -> bool {
+        let Ok(mut j) = self.jobs.lock() else {
+            return false;
+        };
+        match j.remove(scan_key) {
+            Some(job) => {
+                self.forget(&job);
+                job.cancel.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel (and forget) every job whose key matches; returns how many were cancelled.
+    pub fn cancel_where(&self, pred: impl Fn(&CacheKey) -> bool) -> usize {
+        let Ok(mut j) = self.jobs.lock() else {
+            return 0;
+        };
+        let mut n = 0;
+        j.retain(|_, job| {
+            if pred(&job.key) {
+                self.forget(job);
+                job.cancel.store(true, Ordering::SeqCst);
+                n += 1;
+                return false;
+            }
+            true
+        });
+        n
+    }
+
+    /// Set the pause flag of the job for `scan_key` (or of every job without one).
+    /// Returns how many jobs changed state.
+    pub fn set_paused(&self, scan_key: Option<&str>, paused: bool) -> usize {
+        let Ok(j) = self.jobs.lock() else {
+            return 0;
+        };
+        let mut changed = 0;
+        for (_, job) in j
+            .iter()
+            .filter(|(k, _)| scan_key.is_none_or(|s| s == k.as_str()))
+        {
+            if job.paused.swap(paused, Ordering::SeqCst) == paused {
+                continue;
+            }
+            changed += 1;
+            // a paused visible scan lets background ones go on meanwhile
+            if job.visible.load(Ordering::SeqCst) {
+                if paused {
+                    self.visible.fetch_sub(1, Ordering::SeqCst);
+                } else {
+                    self.visible.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }
+        changed
+    }
+
+    pub fn list(&self) -> Vec<ActiveScan> {
+        let Ok(j) = self.jobs.lock() else {
+            return Vec::new();
+        };
+        let yielding = self.visible_active();
+        j.iter()
+            .map(|(k, job)| {
+                let visible = job.visible.load(Ordering::SeqCst);
+                ActiveScan {
+                    scan_key: k.clone(),
+                    path: job.key.path.to_string_lossy().to_string(),
+                    bytes: job.bytes.load(Ordering::Relaxed),
+                    state: if job.paused.load(Ordering::SeqCst) {
+                        "paused".into()
+                    } else if yielding && !visible {
+                        "yielding".into()
+                    } else {
+                        "running".into()
+                    },
+                    priority: if visible { "visible" } else { "background" }.into(),
+                }
+            })
+            .collect()
     }
-    false
 }
 
-pub fn remove(scan_key: &str) {
-    let _ = JOBS.lock().map(|mut j| j.remove(scan_key));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{
+        path_sizer::{
+            events::{Audience, Delivery},
+            keys::{make_cache_key, make_scan_key},
+        },
+        types::{ChildEvent, SizerOptions},
+    };
+    use std::sync::atomic::AtomicU64;
+
+    /// A job for `path`, asked for by window `label` under `job_id`
+    fn job(path: &str, label: &str, job_id: &str, batched: bool) -> Job {
+        let audience = Audience::new(label, false);
+        audience.set_delivery(&Delivery {
+            job_id: job_id.to_string(),
+            echo_path: path.to_string(),
+            batched,
+            top_n: 0,
+        });
+        Job {
+            key: make_cache_key(path, false, &[], &SizerOptions::default()),
+            cancel: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            bytes: Arc::new(AtomicU64::new(0)),
+            visible: Arc::new(AtomicBool::new(false)),
+            outcome: watch::channel(None).0,
+            audience,
+            extras: JobExtras::default(),
+        }
+    }
+
+    fn child(scan_key: &str) -> ChildEvent {
+        ChildEvent {
+            job_id: String::new(),
+            scan_key: scan_key.to_string(),
+            echo_path: String::new(),
+            name: "sub".into(),
+            bytes: 1,
+            items: 1,
+            files: 1,
+            dirs: 0,
+            other_device: false,
+            junction: false,
+            errors: 0,
+        }
+    }
+
+    #[test]
+    fn differently_spelled_paths_share_one_job() {
+        let jobs = Registry::default();
+        let d = SizerOptions::default();
+        let (first, second) = ("/nowhere/jobs-test/stuff", "/nowhere/jobs-test/./stuff/");
+        let key = make_scan_key(first, false, &[], &d);
+        assert_eq!(make_scan_key(second, false, &[], &d), key);
+
+        assert!(jobs
+            .insert_if_absent(key.clone(), job(first, "main", "a", false))
+            .is_some());
+        assert!(jobs
+            .insert_if_absent(key.clone(), job(second, "side", "b", true))
+            .is_none());
+        assert_eq!(jobs.list().len(), 1);
+
+        // the one job's events reach both windows, each as its caller asked
+        let audience = jobs.jobs.lock().unwrap()[&key].audience.clone();
+        let sent: Vec<(Option<String>, String, String)> = audience
+            .recipients(&child(&key), None)
+            .into_iter()
+            .map(|(label, ev)| (label, ev.job_id, ev.echo_path))
+            .collect();
+        assert_eq!(
+            sent,
+            [
+                (Some("main".into()), "a".into(), first.into()),
+                (Some("side".into()), "b".into(), second.into()),
+            ]
+        );
+        let batched: Vec<Option<String>> = audience
+            .recipients(&child(&key), Some(true))
+            .into_iter()
+            .map(|(label, _)| label)
+            .collect();
+        assert_eq!(batched, [Some("side".to_string())]);
+
+        assert!(jobs.cancel(&key));
+        assert!(!jobs.cancel(&key));
+        assert!(jobs.list().is_empty());
+    }
+
+    #[test]
+    fn paused_visible_scans_let_background_ones_run() {
+        let jobs = Registry::default();
+        let shown = job("/nowhere/jobs-test/shown", "main", "a", false);
+        shown.visible.store(true, Ordering::SeqCst);
+        jobs.insert_if_absent("shown".into(), shown);
+        jobs.insert_if_absent(
+            "bg".into(),
+            job("/nowhere/jobs-test/bg", "main", "b", false),
+        );
+        assert!(jobs.visible_active());
+
+        assert_eq!(jobs.set_paused(Some("shown"), true), 1);
+        assert!(!jobs.visible_active());
+        // pausing everything again changes only the background job
+        assert_eq!(jobs.set_paused(None, true), 1);
+        assert!(!jobs.visible_active());
+
+        assert_eq!(jobs.set_paused(None, false), 2);
+        assert!(jobs.visible_active());
+        assert!(jobs.cancel("shown"));
+        assert!(!jobs.visible_active());
+    }
 }
@@ -1,33 +1,416 @@
-use std::path::PathBuf;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::{
+    collections::HashSet,
+    path::{Component, Path, PathBuf},
+};
 
-use crate::api::types::CacheKey;
+use crate::api::types::{CacheKey, SizeMode, SizerOptions};
 
 pub fn normalize_path(p: &str) -> PathBuf {
     std::fs::canonicalize(p).unwrap_or_else(|_| PathBuf::from(p))
 }
 
+/// Like `normalize_path`, but for paths that may not exist (yet / anymore):
+/// canonicalizes the parent and re-appends the file name.
+pub fn normalize_maybe_missing(p: &Path) -> PathBuf {
+    if let Ok(c) = std::fs::canonicalize(p) {
+        return c;
+    }
+    match (p.parent(), p.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => {
+            normalize_maybe_missing(parent).join(name)
+        }
+        _ => p.to_path_buf(),
+    }
+}
+
 pub fn ignores_sig(ignores: &[String]) -> String {
     let mut ig = ignores.to_vec();
     ig.sort();
     ig.join(",")
 }
 
-pub fn make_cache_key(path: &str, show_hidden: bool, ignores: &[String]) -> CacheKey {
+/// Non-default options only, so default scans keep their old keys
+pub fn options_sig(opts: &SizerOptions) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    if !opts.dedupe_hardlinks {
+        parts.push("links");
+    }
+    if opts.size_mode == SizeMode::DiskUsage {
+        parts.push("disk_usage");
+    }
+    if opts.same_device {
+        parts.push("same_dev");
+    }
+    if opts.follow_symlinks {
+        parts.push("follow");
+    }
+    if opts.follow_junctions {
+        parts.push("junctions");
+    }
+    parts.join(";")
+}
+
+pub fn make_cache_key(
+    path: &str,
+    show_hidden: bool,
+    ignores: &[String],
+    opts: &SizerOptions,
+) -> CacheKey {
     CacheKey {
         path: normalize_path(path),
         show_hidden,
         ignores_sig: ignores_sig(ignores),
+        opts_sig: options_sig(opts),
+    }
+}
+
+/// Key for a directory sized as a child of its parent's scan. Path patterns are
+/// anchored at the scan root, so with any of them the result depends on where
+/// the scan started: the sig then carries the entry's name relative to it.
+pub fn make_child_cache_key(
+    path: &str,
+    show_hidden: bool,
+    ignores: &[String],
+    opts: &SizerOptions,
+) -> CacheKey {
+    let mut key = make_cache_key(path, show_hidden, ignores, opts);
+    if has_path_patterns(ignores) {
+        if let Some(name) = key.path.file_name() {
+            key.ignores_sig = format!("{}@{}", key.ignores_sig, name.to_string_lossy());
+        }
+    }
+    key
+}
+
+/// Spelling of `raw` for scan keys, without touching the disk: no trailing
+/// separators or `.` segments, `..` applied, case-folded on Windows.
+pub fn normalize_scan_path(raw: &str) -> String {
+    let mut out = PathBuf::new();
+    for c in Path::new(raw).components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir => match out.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    out.pop();
+                }
+                // nothing above the root
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => out.push(".."),
+            },
+            c => out.push(c),
+        }
+    }
+    let out = if out.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        out.to_string_lossy().to_string()
+    };
+    if cfg!(windows) {
+        out.to_lowercase()
+    } else {
+        out
     }
 }
 
 /// SCAN KEY used for event filtering and job de-dup.
-pub fn make_scan_key(raw_path: &str, show_hidden: bool, ignores: &[String]) -> String {
-    format!("{}|{}|{}", raw_path, show_hidden, ignores_sig(ignores))
+/// Options only show up when non-default, keeping `path|hidden|ignores` for the UI.
+/// The path is normalized (see `normalize_scan_path`); events echo the raw one.
+pub fn make_scan_key(
+    raw_path: &str,
+    show_hidden: bool,
+    ignores: &[String],
+    opts: &SizerOptions,
+) -> String {
+    let mut key = format!(
+        "{}|{}|{}",
+        normalize_scan_path(raw_path),
+        show_hidden,
+        ignores_sig(ignores)
+    );
+    let o = options_sig(opts);
+    if !o.is_empty() {
+        key.push('|');
+        key.push_str(&o);
+    }
+    // not in the cache key: shallow and full scans share entries, but are
+    // separate jobs
+    if let Some(d) = opts.max_depth {
+        key.push_str(&format!("|depth{}", d));
+    }
+    key
+}
+
+/// Any pattern anchored at the scan root (as opposed to a plain name)
+pub fn has_path_patterns(ignores: &[String]) -> bool {
+    ignores.iter().any(|p| is_path_pattern(p))
+}
+
+fn is_path_pattern(pat: &str) -> bool {
+    pat.trim_end_matches(['/', '\\']).contains(['/', '\\'])
+}
+
+/// `rel` with `/` separators, whatever the platform
+fn to_slash(rel: &Path) -> String {
+    let mut out = String::new();
+    for c in rel.components() {
+        if !out.is_empty() {
+            out.push('/');
+        }
+        out.push_str(&c.as_os_str().to_string_lossy());
+    }
+    out
+}
+
+struct PatternBuilder {
+    exact: HashSet<String>,
+    globs: GlobSetBuilder,
+}
+
+impl PatternBuilder {
+    fn new() -> Self {
+        Self {
+            exact: HashSet::new(),
+            globs: GlobSetBuilder::new(),
+        }
+    }
+
+    fn add(&mut self, pat: &str, raw: &str) -> Result<(), String> {
+        if pat.contains(['*', '?', '[', '{']) {
+            let glob = GlobBuilder::new(pat)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| format!("invalid ignore pattern `{raw}`: {e}"))?;
+            self.globs.add(glob);
+        } else {
+            self.exact.insert(pat.to_string());
+        }
+        Ok(())
+    }
+
+    fn build(self) -> Result<PatternSet, String> {
+        Ok(PatternSet {
+            exact: self.exact,
+            globs: self.globs.build().map_err(|e| e.to_string())?,
+        })
+    }
+}
+
+struct PatternSet {
+    exact: HashSet<String>,
+    globs: GlobSet,
 }
 
-pub fn should_skip(name: &str, show_hidden: bool, ignores: &[String]) -> bool {
-    if !show_hidden && name.starts_with('.') {
-        return true;
+impl PatternSet {
+    fn is_match(&self, s: &str) -> bool {
+        self.exact.contains(s) || self.globs.is_match(s)
+    }
+}
+
+/// Ignore patterns compiled once per scan, plus the dotfile rule.
+/// Patterns with glob metacharacters (`* ? [ {`) are globs, anything else must
+/// match exactly. Patterns without a separator apply to the entry name at any
+/// depth; patterns with one (`target/debug`, `docs/**/generated`) apply to the
+/// path relative to the scan root, with `\` read as `/` and a leading `/`
+/// allowed. A trailing `/` limits a pattern to directories.
+pub struct IgnoreSet {
+    show_hidden: bool,
+    names: PatternSet,
+    dir_names: PatternSet,
+    paths: PatternSet,
+    dir_paths: PatternSet,
+    has_paths: bool,
+}
+
+impl IgnoreSet {
+    pub fn new(show_hidden: bool, ignores: &[String]) -> Result<Self, String> {
+        let mut names = PatternBuilder::new();
+        let mut dir_names = PatternBuilder::new();
+        let mut paths = PatternBuilder::new();
+        let mut dir_paths = PatternBuilder::new();
+        let mut has_paths = false;
+
+        for raw in ignores {
+            let norm = raw.replace('\\', "/");
+            let (pat, dirs_only) = match norm.strip_suffix('/') {
+                Some(p) => (p, true),
+                None => (norm.as_str(), false),
+            };
+            if pat.contains('/') {
+                let pat = pat.trim_start_matches('/');
+                if pat.is_empty() {
+                    continue;
+                }
+                has_paths = true;
+                let target = if dirs_only {
+                    &mut dir_paths
+                } else {
+                    &mut paths
+                };
+                target.add(pat, raw)?;
+            } else if !pat.is_empty() {
+                let target = if dirs_only {
+                    &mut dir_names
+                } else {
+                    &mut names
+                };
+                target.add(pat, raw)?;
+            }
+        }
+
+        Ok(Self {
+            show_hidden,
+            names: names.build()?,
+            dir_names: dir_names.build()?,
+            paths: paths.build()?,
+            dir_paths: dir_paths.build()?,
+            has_paths,
+        })
+    }
+
+    /// `rel` is the entry's path relative to the scan root (just the name for
+    /// direct children).
+    pub fn is_skipped(&self, rel: &Path, is_dir: bool) -> bool {
+        let name = rel
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        if !self.show_hidden && name.starts_with('.') {
+            return true;
+        }
+        if self.names.is_match(&name) || (is_dir && self.dir_names.is_match(&name)) {
+            return true;
+        }
+        if !self.has_paths {
+            return false;
+        }
+        let rel = to_slash(rel);
+        self.paths.is_match(&rel) || (is_dir && self.dir_paths.is_match(&rel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(patterns: &[&str]) -> IgnoreSet {
+        let owned: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        IgnoreSet::new(true, &owned).unwrap()
+    }
+
+    #[test]
+    fn names_match_at_any_depth() {
+        let s = set(&["debug", "*.tmp"]);
+        assert!(s.is_skipped(Path::new("debug"), true));
+        assert!(s.is_skipped(Path::new("src/target/debug"), true));
+        assert!(s.is_skipped(Path::new("a/b/c.tmp"), false));
+        assert!(!s.is_skipped(Path::new("debug_keep"), true));
+    }
+
+    #[test]
+    fn path_patterns_are_anchored_at_the_root() {
+        let s = set(&["target/debug"]);
+        assert!(s.is_skipped(Path::new("target/debug"), true));
+        assert!(!s.is_skipped(Path::new("src/target/debug"), true));
+        assert!(!s.is_skipped(Path::new("debug"), true));
+        assert!(!s.is_skipped(Path::new("target/debug/deps"), true));
+
+        let lead = set(&["/build"]);
+        assert!(lead.is_skipped(Path::new("build"), true));
+        assert!(!lead.is_skipped(Path::new("x/build"), true));
+    }
+
+    #[test]
+    fn nested_globs_cross_directories_only_with_double_star() {
+        let s = set(&["docs/**/generated", "src/*/gen"]);
+        assert!(s.is_skipped(Path::new("docs/generated"), true));
+        assert!(s.is_skipped(Path::new("docs/a/b/generated"), true));
+        assert!(!s.is_skipped(Path::new("other/docs/generated"), true));
+        assert!(s.is_skipped(Path::new("src/a/gen"), true));
+        assert!(!s.is_skipped(Path::new("src/a/b/gen"), true));
+    }
+
+    #[test]
+    fn backslashes_are_separators() {
+        let s = set(&["target\\debug", "out\\"]);
+        assert!(s.is_skipped(Path::new("target/debug"), true));
+        assert!(s.is_skipped(Path::new("out"), true));
+        assert!(!s.is_skipped(Path::new("out"), false));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_relative_paths_are_normalized() {
+        let s = set(&["target/debug"]);
+        assert!(s.is_skipped(Path::new("target\\debug"), true));
+    }
+
+    #[test]
+    fn dirs_only_patterns_skip_dirs_only() {
+        let s = set(&["build/", "gen/*/"]);
+        assert!(s.is_skipped(Path::new("build"), true));
+        assert!(!s.is_skipped(Path::new("build"), false));
+        assert!(s.is_skipped(Path::new("gen/x"), true));
+        assert!(!s.is_skipped(Path::new("gen/x"), false));
+    }
+
+    #[test]
+    fn invalid_pattern_is_an_error() {
+        assert!(IgnoreSet::new(true, &["[oops".to_string()]).is_err());
+    }
+
+    #[test]
+    fn scan_paths_drop_trailing_separators_and_dot_segments() {
+        let want = normalize_scan_path("/home/me/stuff");
+        assert_eq!(normalize_scan_path("/home/me/stuff/"), want);
+        assert_eq!(normalize_scan_path("/home/me/./stuff"), want);
+        assert_eq!(normalize_scan_path("/home/me/other/../stuff"), want);
+        assert_eq!(normalize_scan_path("/home//me/stuff"), want);
+        assert_ne!(normalize_scan_path("/home/me/stuff2"), want);
+    }
+
+    #[test]
+    fn parent_segments_stop_at_the_root() {
+        assert_eq!(normalize_scan_path("/.."), normalize_scan_path("/"));
+        assert_eq!(
+            normalize_scan_path("../a/./b/.."),
+            normalize_scan_path("../a")
+        );
+        assert_eq!(normalize_scan_path("a/.."), ".");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_scan_paths_ignore_case_and_slash_style() {
+        assert_eq!(
+            normalize_scan_path("C:/Users/Me/Stuff/"),
+            normalize_scan_path("c:\\users\\me\\stuff")
+        );
+    }
+
+    #[test]
+    fn equivalent_spellings_share_a_scan_key() {
+        let ig = vec!["node_modules".to_string()];
+        let d = SizerOptions::default();
+        let a = make_scan_key("/home/me/stuff", false, &ig, &d);
+        assert_eq!(a, "/home/me/stuff|false|node_modules");
+        assert_eq!(make_scan_key("/home/me/stuff/", false, &ig, &d), a);
+        assert_eq!(make_scan_key("/home/me/./stuff", false, &ig, &d), a);
+        assert_ne!(make_scan_key("/home/me/stuff", true, &ig, &d), a);
+    }
+
+    #[test]
+    fn child_keys_carry_their_name_only_with_path_patterns() {
+        let names = vec!["node_modules".to_string()];
+        let paths = vec!["target/debug".to_string()];
+        let d = SizerOptions::default();
+        assert_eq!(
+            make_child_cache_key("/nowhere/target", false, &names, &d).ignores_sig,
+            make_cache_key("/nowhere/target", false, &names, &d).ignores_sig
+        );
+        assert_eq!(
+            make_child_cache_key("/nowhere/target", false, &paths, &d).ignores_sig,
+            "target/debug@target"
+        );
     }
-    ignores.iter().any(|ig| name.contains(ig))
 }
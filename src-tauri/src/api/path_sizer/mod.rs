@@ -1,26 +1,485 @@
-use tauri::AppHandle;
+use std::{path::Path, time::Duration};
 
-mod cache;
+use tauri::{AppHandle, Window};
+
+use crate::api::{sandbox, settings, storage};
+use events::Audience;
+
+use crate::api::types::{
+    ActiveScan, ApiError, CacheStats, CachedSizeReply, SizerOptions, SizesReply, TopEntry,
+};
+
+pub(crate) mod cache;
+mod composition;
+mod concurrency;
+pub(crate) mod events;
 mod jobs;
-mod keys;
+pub(crate) mod keys;
+pub(crate) mod persist;
+mod rate;
+mod selection;
+mod volume;
+mod walk;
+pub(crate) mod watch;
 mod worker;
 
+const DEFAULT_ESTIMATE_BUDGET_MS: u64 = 2000;
+
+/// `top_n`: also report the N largest files/folders (`dir_size:top`); 0/None skips it.
+/// `concurrency`: child folders walked at once (1-32); defaults to the global setting.
+/// `force`: ignore completed cache entries and rescan everything (restarting a
+/// running scan of the same key); the results overwrite the cache.
+/// `batch_events`: send `dir_size:children` / `dir_size:progress_batch` every
+/// ~100ms instead of one `dir_size:child` / `dir_size:progress` per folder.
+/// `priority`: "visible" (default) or "background"; background scans hold back
+/// while visible ones run (see `promote_scan`).
+/// `estimate_total`: count the tree first (for at most `estimate_budget_ms`,
+/// default 2000) so progress events carry `expected_bytes` / `expected_items`.
+/// `show_hidden` / `ignores` / `options` default to the settings.
+/// Events go to the calling window (and any other that asks for the same scan
+/// while it runs, each in the format and with the ids it asked for);
+/// `broadcast` sends them to every window instead. Asking a running scan for
+/// a longer top report or an estimate it isn't making restarts it with both.
+/// Returns the scan_key the events will carry (the path in it is normalized, so
+/// other spellings of the same folder share one scan).
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn ensure_path_sizer(
     app: AppHandle,
+    window: Window,
     path: String,
     job_id: String,
+    show_hidden: Option<bool>,
+    ignores: Option<Vec<String>>,
+    options: Option<SizerOptions>,
+    top_n: Option<u32>,
+    concurrency: Option<usize>,
+    force: Option<bool>,
+    batch_events: Option<bool>,
+    estimate_total: Option<bool>,
+    estimate_budget_ms: Option<u64>,
+    priority: Option<String>,
+    broadcast: Option<bool>,
+) -> Result<String, ApiError> {
+    sandbox::check(&app, &path)?;
+    let background = match priority.as_deref() {
+        None | Some("visible") => false,
+        Some("background") => true,
+        Some(other) => {
+            return Err(ApiError::invalid_input(format!(
+                "unknown priority: {}",
+                other
+            )))
+        }
+    };
+    let budget = Duration::from_millis(estimate_budget_ms.unwrap_or(DEFAULT_ESTIMATE_BUDGET_MS));
+    let (show_hidden, ignores, opts) = settings::scan_defaults(&app, show_hidden, ignores, options);
+    worker::ensure_path_sizer_impl(
+        app,
+        path,
+        job_id,
+        show_hidden,
+        ignores,
+        opts,
+        worker::ScanConfig {
+            top_n: top_n.unwrap_or(0) as usize,
+            concurrency,
+            force: force.unwrap_or(false),
+            batch_events: batch_events.unwrap_or(false),
+            estimate_budget: estimate_total.unwrap_or(false).then_some(budget),
+            background,
+            audience: Audience::new(window.label(), broadcast.unwrap_or(false)),
+            ..Default::default()
+        },
+    )
+}
+
+/// "What's eating my disk": size a whole volume, one child per top-level dir,
+/// with the usual child/progress/summary events plus `dir_size:volume` (used
+/// space vs found), sent to the calling window. Returns the scan_key, for
+/// pause/cancel.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn analyze_volume(
+    app: AppHandle,
+    window: Window,
+    mount_point: String,
+    request_id: String,
+    show_hidden: Option<bool>,
+    ignores: Option<Vec<String>>,
+    options: Option<SizerOptions>,
+    top_n: Option<u32>,
+    concurrency: Option<usize>,
+    batch_events: Option<bool>,
+) -> Result<String, ApiError> {
+    sandbox::check(&app, &mount_point)?;
+    let (show_hidden, ignores, opts) = settings::scan_defaults(&app, show_hidden, ignores, options);
+    volume::analyze_volume_impl(
+        app,
+        mount_point,
+        request_id,
+        show_hidden,
+        ignores,
+        opts,
+        top_n.unwrap_or(0) as usize,
+        concurrency,
+        batch_events.unwrap_or(false),
+        Audience::new(window.label(), false),
+    )
+}
+
+/// Total size of `path` as `(bytes, items)`, awaited instead of followed
+/// through events. A completed cache entry answers at once; otherwise this
+/// starts (or joins) the same scan `ensure_path_sizer` would and waits for it.
+#[tauri::command]
+pub async fn dir_size(
+    app: AppHandle,
+    path: String,
+    show_hidden: Option<bool>,
+    ignores: Option<Vec<String>>,
+    options: Option<SizerOptions>,
+) -> Result<(u64, u64), ApiError> {
+    sandbox::check(&app, &path)?;
+    let (show_hidden, ignores, opts) = settings::scan_defaults(&app, show_hidden, ignores, options);
+    measure(app, path, show_hidden, ignores, opts, false).await
+}
+
+/// `dir_size` for the backend; `force` rescans instead of trusting the cache
+pub(crate) async fn measure(
+    app: AppHandle,
+    path: String,
     show_hidden: bool,
     ignores: Vec<String>,
-) -> Result<(), String> {
-    worker::ensure_path_sizer_impl(app, path, job_id, show_hidden, ignores)
+    opts: SizerOptions,
+    force: bool,
+) -> Result<(u64, u64), ApiError> {
+    let storage = storage::backend(&app);
+    if !storage.is_native(Path::new(&path)) {
+        let skip = keys::IgnoreSet::new(show_hidden, &ignores).map_err(ApiError::invalid_input)?;
+        let (bytes, files, dirs) = tauri::async_runtime::spawn_blocking(move || {
+            storage.tree_size(Path::new(&path), &skip)
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        return Ok((bytes, files + dirs));
+    }
+    if let Some(e) = worker::root_error(Path::new(&path)) {
+        return Err(ApiError::from(e).with_path(&path));
+    }
+    let cache_key = keys::make_cache_key(&path, show_hidden, &ignores, &opts);
+    let completed = || {
+        cache::SIZE_CACHE
+            .lock()
+            .ok()?
+            .get(&cache_key)
+            .filter(|e| e.completed)
+            .map(|e| (e.bytes, e.items))
+    };
+    if let Some(done) = completed().filter(|_| !force) {
+        return Ok(done);
+    }
+
+    let scan_key = worker::ensure_path_sizer_impl(
+        app,
+        path.clone(),
+        format!("dir_size-{}", path),
+        show_hidden,
+        ignores,
+        opts,
+        worker::ScanConfig {
+            force,
+            ..Default::default()
+        },
+    )?;
+    match jobs::JOBS.wait(&scan_key).await {
+        Some(outcome) => outcome,
+        // served from cache, or over before we could subscribe
+        None => completed().ok_or_else(|| ApiError::from("scan ended without a size".to_string())),
+    }
 }
 
+/// Default child-scan concurrency for scans without their own (1-32; 0 = pick
+/// per volume). Running scans pick it up for the children they start next.
+#[tauri::command]
+pub fn set_sizer_concurrency(n: usize) {
+    concurrency::set_default(n)
+}
+
+/// Cancel the running scan for `path` + options, or for a raw `scan_key`
+/// (as carried by the events). Returns whether a job was found.
+#[tauri::command]
+pub fn cancel_path_sizer(
+    app: AppHandle,
+    path: Option<String>,
+    show_hidden: Option<bool>,
+    ignores: Option<Vec<String>>,
+    options: Option<SizerOptions>,
+    scan_key: Option<String>,
+) -> Result<bool, ApiError> {
+    let scan_key = match (scan_key, path) {
+        (Some(k), _) => k,
+        (None, Some(p)) => {
+            let (show_hidden, ignores, opts) =
+                settings::scan_defaults(&app, show_hidden, ignores, options);
+            keys::make_scan_key(&p, show_hidden, &ignores, &opts)
+        }
+        (None, None) => return Err(ApiError::invalid_input("path or scan_key required")),
+    };
+    Ok(jobs::JOBS.cancel(&scan_key))
+}
+
+/// Cancel every scan whose root is `path_prefix` or lies below it (e.g. when
+/// navigating away). Partials already cached stay as hints. Returns how many
+/// jobs were signalled.
+#[tauri::command]
+pub fn cancel_scans_under(path_prefix: String) -> usize {
+    let prefix = keys::normalize_maybe_missing(std::path::Path::new(&path_prefix));
+    jobs::JOBS.cancel_where(|key| key.path.starts_with(&prefix))
+}
+
+#[tauri::command]
+pub fn cancel_all_scans() -> usize {
+    jobs::JOBS.cancel_where(|_| true)
+}
+
+/// Park a running scan (its walks stop between entries, no progress is emitted)
+/// until resumed; cancelling still works. Returns whether a running scan was paused.
+#[tauri::command]
+pub fn pause_path_sizer(scan_key: String) -> bool {
+    jobs::JOBS.set_paused(Some(&scan_key), true) > 0
+}
+
+#[tauri::command]
+pub fn resume_path_sizer(scan_key: String) -> bool {
+    jobs::JOBS.set_paused(Some(&scan_key), false) > 0
+}
+
+/// Pause every running scan; returns how many were paused
+#[tauri::command]
+pub fn pause_all_scans() -> usize {
+    jobs::JOBS.set_paused(None, true)
+}
+
+#[tauri::command]
+pub fn resume_all_scans() -> usize {
+    jobs::JOBS.set_paused(None, false)
+}
+
+/// Move a running background scan to visible priority (the user opened that
+/// folder). Returns whether one was promoted.
+#[tauri::command]
+pub fn promote_scan(scan_key: String) -> bool {
+    jobs::JOBS.promote(&scan_key)
+}
+
+#[tauri::command]
+pub fn list_active_scans() -> Vec<ActiveScan> {
+    jobs::JOBS.list()
+}
+
+/// `approximate`: on an exact miss, fall back to the same path under other options.
+/// `version`: 1 returns the legacy `(bytes, items, completed)` tuples (default 2).
 #[tauri::command]
 pub fn get_cached_sizes(
+    app: AppHandle,
     paths: Vec<String>,
-    show_hidden: bool,
-    ignores: Vec<String>,
-) -> Result<Vec<Option<(u64, u64, bool)>>, String> {
-    cache::get_cached_sizes(paths, show_hidden, ignores)
+    show_hidden: Option<bool>,
+    ignores: Option<Vec<String>>,
+    options: Option<SizerOptions>,
+    approximate: Option<bool>,
+    version: Option<u32>,
+) -> Result<Vec<Option<CachedSizeReply>>, ApiError> {
+    sandbox::check_all(&app, &paths)?;
+    let (show_hidden, ignores, opts) = settings::scan_defaults(&app, show_hidden, ignores, options);
+    cache::get_cached_sizes(
+        paths,
+        show_hidden,
+        ignores,
+        &opts,
+        approximate.unwrap_or(false),
+        version.unwrap_or(2),
+    )
+    .map_err(ApiError::from)
+}
+
+/// `get_cached_sizes` for the children of `parent`, and in the same call start
+/// (or join) the scan of `parent` when any of them is missing or unfinished.
+/// Repeated calls share the running job; `force` rescans like a refresh.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn get_or_ensure_sizes(
+    app: AppHandle,
+    window: Window,
+    paths: Vec<String>,
+    parent: String,
+    show_hidden: Option<bool>,
+    ignores: Option<Vec<String>>,
+    job_id: String,
+    options: Option<SizerOptions>,
+    approximate: Option<bool>,
+    version: Option<u32>,
+    force: Option<bool>,
+    batch_events: Option<bool>,
+) -> Result<SizesReply, ApiError> {
+    sandbox::check(&app, &parent)?;
+    sandbox::check_all(&app, &paths)?;
+    let (show_hidden, ignores, opts) = settings::scan_defaults(&app, show_hidden, ignores, options);
+    let force = force.unwrap_or(false);
+    let sizes = cache::get_cached_sizes(
+        paths,
+        show_hidden,
+        ignores.clone(),
+        &opts,
+        approximate.unwrap_or(false),
+        version.unwrap_or(2),
+    )?;
+
+    let settled = sizes
+        .iter()
+        .all(|s| s.as_ref().is_some_and(|s| s.is_settled()));
+    let scan_key = if force || !settled {
+        Some(worker::ensure_path_sizer_impl(
+            app,
+            parent,
+            job_id,
+            show_hidden,
+            ignores,
+            opts,
+            worker::ScanConfig {
+                force,
+                batch_events: batch_events.unwrap_or(false),
+                audience: Audience::new(window.label(), false),
+                ..Default::default()
+            },
+        )?)
+    } else {
+        None
+    };
+    Ok(SizesReply { sizes, scan_key })
+}
+
+/// Mark cached sizes `completed: false` and emit `dir_size:stale` when something
+/// at or below them changes on disk. Only `watch_sizes` roots are watched,
+/// and changes are reported in batches once things are quiet for 2s. Off by
+/// default.
+#[tauri::command]
+pub fn auto_invalidate(enabled: bool) {
+    watch::set_enabled(enabled)
+}
+
+/// Watch the cached sizes at or below `path` (e.g. the folder on screen);
+/// the least recently watched of more than 16 roots is dropped.
+#[tauri::command]
+pub fn watch_sizes(app: AppHandle, path: String) -> Result<(), ApiError> {
+    sandbox::check(&app, &path)?;
+    watch::watch(&path);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unwatch_sizes(path: String) -> bool {
+    watch::unwatch(&path)
+}
+
+/// Forget cached sizes for `path` (and below when `recursive`), or all of them.
+#[tauri::command]
+pub fn invalidate_size_cache(
+    app: AppHandle,
+    path: Option<String>,
+    recursive: bool,
+) -> Result<usize, ApiError> {
+    cache::invalidate_size_cache(&app, path, recursive).map_err(ApiError::from)
+}
+
+/// Largest files/folders from the last finished `top_n` scan of `path`;
+/// omitted filters fall back to the settings, as for the scan itself
+#[tauri::command]
+pub fn get_top_entries(
+    app: AppHandle,
+    path: String,
+    show_hidden: Option<bool>,
+    ignores: Option<Vec<String>>,
+    options: Option<SizerOptions>,
+) -> Result<Option<Vec<TopEntry>>, ApiError> {
+    sandbox::check(&app, &path)?;
+    let (show_hidden, ignores, opts) = settings::scan_defaults(&app, show_hidden, ignores, options);
+    cache::get_top_entries(&path, show_hidden, &ignores, &opts).map_err(ApiError::from)
+}
+
+#[tauri::command]
+pub fn get_cache_stats() -> Result<CacheStats, ApiError> {
+    cache::get_cache_stats().map_err(ApiError::from)
+}
+
+/// Cap the size cache entry count (default 100k); evicts right away when over.
+#[tauri::command]
+pub fn set_size_cache_limit(max_entries: usize) -> Result<(), ApiError> {
+    cache::set_max_entries(max_entries).map_err(ApiError::from)
+}
+
+/// Delete the size cache file in the app data dir. Returns whether one existed.
+#[tauri::command]
+pub fn clear_persistent_size_cache() -> Result<bool, ApiError> {
+    persist::clear().map_err(ApiError::from)
+}
+
+#[tauri::command]
+pub fn folder_composition(
+    app: AppHandle,
+    window: Window,
+    path: String,
+    request_id: String,
+    show_hidden: Option<bool>,
+    ignores: Option<Vec<String>>,
+    top_n: Option<usize>,
+) -> Result<(), ApiError> {
+    sandbox::check(&app, &path)?;
+    let (show_hidden, ignores, _) = settings::scan_defaults(&app, show_hidden, ignores, None);
+    composition::folder_composition_impl(
+        app,
+        window.label().to_string(),
+        path,
+        request_id,
+        show_hidden,
+        ignores,
+        top_n,
+    )
+}
+
+#[tauri::command]
+pub fn cancel_folder_composition(request_id: String) -> bool {
+    composition::cancel(&request_id)
+}
+
+/// `concurrency`: selected folders walked at once (1-32); it and the filters
+/// default to the settings, like `ensure_path_sizer`
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn size_of_selection(
+    app: AppHandle,
+    window: Window,
+    paths: Vec<String>,
+    request_id: String,
+    show_hidden: Option<bool>,
+    ignores: Option<Vec<String>>,
+    options: Option<SizerOptions>,
+    concurrency: Option<usize>,
+) -> Result<(), ApiError> {
+    sandbox::check_all(&app, &paths)?;
+    let (show_hidden, ignores, opts) = settings::scan_defaults(&app, show_hidden, ignores, options);
+    selection::size_of_selection_impl(
+        app,
+        window.label().to_string(),
+        paths,
+        request_id,
+        show_hidden,
+        ignores,
+        opts,
+        concurrency,
+    )
+}
+
+#[tauri::command]
+pub fn cancel_selection_size(request_id: String) -> bool {
+    selection::cancel(&request_id)
 }
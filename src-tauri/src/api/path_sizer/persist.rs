@@ -0,0 +1,235 @@
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Manager};
+
+use crate::api::{
+    path_sizer::cache::{emit_stale, SIZE_CACHE},
+    types::{CacheEntry, CacheKey},
+};
+
+/// 2: ignore patterns are globs / exact names, not substrings
+/// 3: patterns with a `/` match the path relative to the scan root
+/// 4: hard links counted once by default
+const FILE_VERSION: u32 = 4;
+const DEBOUNCE: Duration = Duration::from_secs(3);
+const MAX_DELAY: Duration = Duration::from_secs(30);
+/// Loaded entries checked between cache updates, so the lock is held briefly
+const VERIFY_BATCH: usize = 1024;
+
+static CACHE_FILE: OnceCell<PathBuf> = OnceCell::new();
+
+#[derive(Serialize, Deserialize)]
+struct PersistedEntry {
+    path: String,
+    show_hidden: bool,
+    ignores_sig: String,
+    #[serde(default)]
+    opts_sig: String,
+    bytes: u64,
+    items: u64,
+    files: u64,
+    dirs: u64,
+    completed: bool,
+    #[serde(default)]
+    estimated: bool,
+    #[serde(default)]
+    errors: u64,
+    updated_at_ms: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedCache {
+    version: u32,
+    entries: Vec<PersistedEntry>,
+}
+
+fn to_ms(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Point the cache at `<app data>/size_cache.json` and start the debounced saver.
+/// The file itself is read on first access to SIZE_CACHE, which the checker
+/// started here usually makes before any command.
+pub fn init(app: &AppHandle) {
+    let Ok(dir) = app.path().app_data_dir() else {
+        return;
+    };
+    if CACHE_FILE.set(dir.join("size_cache.json")).is_err() {
+        return;
+    }
+    let checker = app.clone();
+    std::thread::spawn(move || verify_loaded(&checker));
+    std::thread::spawn(|| loop {
+        std::thread::sleep(Duration::from_secs(1));
+        let due = SIZE_CACHE
+            .lock()
+            .map(|c| c.save_due(DEBOUNCE, MAX_DELAY))
+            .unwrap_or(false);
+        if due {
+            flush();
+        }
+    });
+}
+
+/// Entries from disk, as saved: nothing is stat'ed here, since the first
+/// access to SIZE_CACHE may come from a command the UI waits on.
+/// Unreadable, corrupt or other-version files yield nothing.
+pub fn load() -> Vec<(CacheKey, CacheEntry)> {
+    let Some(file) = CACHE_FILE.get() else {
+        return Vec::new();
+    };
+    let Ok(raw) = fs::read(file) else {
+        return Vec::new();
+    };
+    let Ok(parsed) = serde_json::from_slice::<PersistedCache>(&raw) else {
+        return Vec::new();
+    };
+    if parsed.version != FILE_VERSION {
+        return Vec::new();
+    }
+
+    parsed
+        .entries
+        .into_iter()
+        .map(|e| {
+            let updated_at = UNIX_EPOCH + Duration::from_millis(e.updated_at_ms);
+            (
+                CacheKey {
+                    path: PathBuf::from(e.path),
+                    show_hidden: e.show_hidden,
+                    ignores_sig: e.ignores_sig,
+                    opts_sig: e.opts_sig,
+                },
+                CacheEntry {
+                    bytes: e.bytes,
+                    items: e.items,
+                    files: e.files,
+                    dirs: e.dirs,
+                    completed: e.completed,
+                    estimated: e.estimated,
+                    errors: e.errors,
+                    complete_with_errors: e.completed && e.errors > 0,
+                    updated_at,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Check the loaded entries against the disk, off the command threads (a slow
+/// network drive can take minutes). A directory modified after its entry was
+/// written keeps the entry only as a hint (`completed: false`, announced with
+/// `dir_size:stale`); missing directories are dropped. Entries written since
+/// the load are left alone.
+fn verify_loaded(app: &AppHandle) {
+    let loaded_at = SystemTime::now();
+    let paths: BTreeSet<PathBuf> = match SIZE_CACHE.lock() {
+        Ok(cache) => cache.iter().map(|(k, _)| k.path.clone()).collect(),
+        Err(_) => return,
+    };
+
+    let mut missing = HashSet::new();
+    let mut modified = HashMap::new();
+    for (n, path) in paths.into_iter().enumerate() {
+        match fs::metadata(&path) {
+            Ok(md) => {
+                if let Ok(mtime) = md.modified() {
+                    modified.insert(path, mtime);
+                }
+            }
+            Err(_) => {
+                missing.insert(path);
+            }
+        }
+        if (n + 1) % VERIFY_BATCH == 0 {
+            apply_verified(app, loaded_at, &mut missing, &mut modified);
+        }
+    }
+    apply_verified(app, loaded_at, &mut missing, &mut modified);
+}
+
+fn apply_verified(
+    app: &AppHandle,
+    loaded_at: SystemTime,
+    missing: &mut HashSet<PathBuf>,
+    modified: &mut HashMap<PathBuf, SystemTime>,
+) {
+    let stale = match SIZE_CACHE.lock() {
+        Ok(mut cache) => {
+            cache.retain(|k, e| e.updated_at > loaded_at || !missing.contains(&k.path));
+            cache.mark_stale(|path, e| {
+                e.updated_at <= loaded_at && modified.get(path).is_some_and(|m| *m > e.updated_at)
+            })
+        }
+        Err(_) => return,
+    };
+    missing.clear();
+    modified.clear();
+    emit_stale(app, stale);
+}
+
+/// Write the cache out now if it changed since the last save
+pub fn flush() {
+    let Some(file) = CACHE_FILE.get() else {
+        return;
+    };
+    let Some(entries) = SIZE_CACHE.lock().ok().and_then(|mut c| c.take_for_save()) else {
+        return;
+    };
+    let doc = PersistedCache {
+        version: FILE_VERSION,
+        entries: entries
+            .into_iter()
+            .filter_map(|(k, e)| {
+                // non-UTF-8 paths can't round-trip through JSON
+                Some(PersistedEntry {
+                    path: k.path.to_str()?.to_string(),
+                    show_hidden: k.show_hidden,
+                    ignores_sig: k.ignores_sig,
+                    opts_sig: k.opts_sig,
+                    bytes: e.bytes,
+                    items: e.items,
+                    files: e.files,
+                    dirs: e.dirs,
+                    completed: e.completed,
+                    estimated: e.estimated,
+                    errors: e.errors,
+                    updated_at_ms: to_ms(e.updated_at),
+                })
+            })
+            .collect(),
+    };
+    let Ok(data) = serde_json::to_vec(&doc) else {
+        return;
+    };
+    if let Some(dir) = file.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let tmp = file.with_extension("json.part");
+    if let Err(e) = fs::write(&tmp, data).and_then(|_| fs::rename(&tmp, file)) {
+        tracing::warn!(path = %file.display(), error = %e, "size cache not saved");
+    }
+}
+
+/// Command: delete the on-disk cache (the in-memory one is kept)
+pub fn clear() -> Result<bool, String> {
+    let Some(file) = CACHE_FILE.get() else {
+        return Ok(false);
+    };
+    if let Ok(mut c) = SIZE_CACHE.lock() {
+        c.clear_dirty();
+    }
+    match fs::remove_file(file) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.to_string()),
+    }
+}
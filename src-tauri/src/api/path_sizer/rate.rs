@@ -0,0 +1,94 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Rate over roughly the last WINDOW of samples
+const WINDOW: Duration = Duration::from_secs(3);
+/// Below this span a rate is mostly noise from the throttled progress cadence
+const MIN_SPAN: Duration = Duration::from_millis(500);
+
+/// Rolling bytes/s from `(time, running byte count)` samples. Feed it bytes
+/// actually walked: bytes taken from the cache arrive in one jump and would
+/// read as an absurd rate.
+pub struct Throughput {
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl Throughput {
+    /// Starts at 0 bytes now, so the first sample already spans the walk so far
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+
+    fn starting_at(t: Instant) -> Self {
+        Self {
+            samples: VecDeque::from([(t, 0)]),
+        }
+    }
+
+    /// Add a sample; returns bytes/s, 0 until MIN_SPAN has passed
+    pub fn record(&mut self, bytes: u64) -> u64 {
+        self.record_at(Instant::now(), bytes)
+    }
+
+    fn record_at(&mut self, now: Instant, bytes: u64) -> u64 {
+        self.samples.push_back((now, bytes));
+        // keep the newest sample at least WINDOW old as the baseline
+        while self
+            .samples
+            .get(1)
+            .is_some_and(|(t, _)| now.duration_since(*t) >= WINDOW)
+        {
+            self.samples.pop_front();
+        }
+        let (t0, b0) = self.samples[0];
+        let span = now.duration_since(t0);
+        if span < MIN_SPAN {
+            return 0;
+        }
+        (bytes.saturating_sub(b0) as u128 * 1000 / span.as_millis()) as u64
+    }
+}
+
+impl Default for Throughput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Milliseconds left for `remaining` bytes at `bytes_per_sec`; None without a rate
+pub fn eta_ms(remaining: u64, bytes_per_sec: u64) -> Option<u64> {
+    (bytes_per_sec > 0).then(|| (remaining as u128 * 1000 / bytes_per_sec as u128) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn no_rate_before_min_span() {
+        let t = Instant::now();
+        let mut r = Throughput::starting_at(t);
+        assert_eq!(r.record_at(t + 100 * MS, 1_000_000), 0);
+        assert_eq!(r.record_at(t + 500 * MS, 1_000_000), 2_000_000);
+    }
+
+    #[test]
+    fn old_samples_leave_the_window() {
+        let t = Instant::now();
+        let mut r = Throughput::starting_at(t);
+        // fast start, then a stall: the rate falls once the burst is out of the window
+        r.record_at(t + 1000 * MS, 100_000_000);
+        assert_eq!(r.record_at(t + 2000 * MS, 100_000_000), 50_000_000);
+        assert_eq!(r.record_at(t + 8000 * MS, 100_000_000), 0);
+    }
+
+    #[test]
+    fn eta_needs_a_rate() {
+        assert_eq!(eta_ms(1_000, 0), None);
+        assert_eq!(eta_ms(3_000_000, 1_000_000), Some(3000));
+    }
+}
@@ -0,0 +1,259 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+use once_cell::sync::Lazy;
+use tauri::{AppHandle, Emitter};
+
+use crate::api::{
+    path_sizer::{
+        cache::SIZE_CACHE,
+        concurrency::Limiter,
+        keys::{make_child_cache_key, IgnoreSet},
+        walk::{file_size, walk_tree, TopN, WalkFlags},
+    },
+    types::{ApiError, CacheEntry, SelectionSizeEvent, SizerOptions},
+};
+
+static SELECTIONS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Running totals shared between the walkers and the progress ticker
+#[derive(Default)]
+struct Totals {
+    bytes: AtomicU64,
+    items: AtomicU64,
+    scanning: AtomicU64,
+}
+
+impl Totals {
+    fn event(&self, request_id: &str, cancelled: bool) -> SelectionSizeEvent {
+        SelectionSizeEvent {
+            request_id: request_id.to_string(),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            items: self.items.load(Ordering::Relaxed),
+            scanning: self.scanning.load(Ordering::Relaxed),
+            cancelled,
+        }
+    }
+}
+
+/// One folder counted in `scanning`; takes it off when dropped, so a walker
+/// that bails out early (or panics) can't leave the count behind
+struct Scanning(Arc<Totals>);
+
+impl Scanning {
+    fn start(totals: &Arc<Totals>) -> Self {
+        totals.scanning.fetch_add(1, Ordering::Relaxed);
+        Self(totals.clone())
+    }
+}
+
+impl Drop for Scanning {
+    fn drop(&mut self) {
+        self.0.scanning.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Events go to the `label` window only
+#[allow(clippy::too_many_arguments)]
+pub fn size_of_selection_impl(
+    app: AppHandle,
+    label: String,
+    paths: Vec<String>,
+    request_id: String,
+    show_hidden: bool,
+    ignores: Vec<String>,
+    opts: SizerOptions,
+    concurrency: Option<usize>,
+) -> Result<(), ApiError> {
+    let skip = Arc::new(IgnoreSet::new(show_hidden, &ignores).map_err(ApiError::invalid_input)?);
+    let cancel = Arc::new(AtomicBool::new(false));
+    if let Some(prev) = SELECTIONS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(request_id.clone(), cancel.clone())
+    {
+        prev.store(true, Ordering::SeqCst);
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let totals = Arc::new(Totals::default());
+        let mut to_scan: Vec<PathBuf> = Vec::new();
+
+        // 1) files directly, folders from completed cache entries
+        {
+            let mut cache = SIZE_CACHE.lock().ok();
+            for p in &paths {
+                let Ok(md) = std::fs::symlink_metadata(p) else {
+                    continue;
+                };
+                if md.is_dir() {
+                    let key = make_child_cache_key(p, show_hidden, &ignores, &opts);
+                    match cache.as_mut().and_then(|c| c.get(&key)) {
+                        Some(entry) if entry.completed => {
+                            totals.bytes.fetch_add(entry.bytes, Ordering::Relaxed);
+                            totals.items.fetch_add(entry.items, Ordering::Relaxed);
+                        }
+                        _ => to_scan.push(PathBuf::from(p)),
+                    }
+                } else {
+                    totals.bytes.fetch_add(
+                        file_size(Path::new(p), &md, opts.size_mode),
+                        Ordering::Relaxed,
+                    );
+                    totals.items.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        // 2) walk the rest, as many at once as a sizer scan of the first would
+        let slots = match to_scan.first() {
+            Some(first) => Limiter::new(concurrency, first),
+            None => Limiter::new(concurrency, Path::new(".")),
+        };
+        let mut tasks = Vec::with_capacity(to_scan.len());
+        for dir in to_scan {
+            let scanning = Scanning::start(&totals);
+            let slots = slots.clone();
+            let cancel = cancel.clone();
+            let totals = totals.clone();
+            let ignores = ignores.clone();
+            let skip = skip.clone();
+            let opts = opts.clone();
+            tasks.push(tauri::async_runtime::spawn(async move {
+                let _slot = slots.acquire().await;
+                if cancel.load(Ordering::SeqCst) {
+                    return;
+                }
+                let key = make_child_cache_key(
+                    dir.to_string_lossy().as_ref(),
+                    show_hidden,
+                    &ignores,
+                    &opts,
+                );
+                let _ = tauri::async_runtime::spawn_blocking(move || {
+                    let (mut seen_bytes, mut seen_items) = (0u64, 0u64);
+                    // a selected folder is anchored at its parent, like a sizer child
+                    let rel_root = dir.parent().unwrap_or(&dir);
+                    let t = walk_tree(
+                        &dir,
+                        None,
+                        rel_root,
+                        &skip,
+                        &opts,
+                        &WalkFlags {
+                            cancel: &cancel,
+                            paused: &AtomicBool::new(false),
+                            visible: None,
+                        },
+                        &mut TopN::new(0),
+                        |bytes, files, dirs| {
+                            totals
+                                .bytes
+                                .fetch_add(bytes - seen_bytes, Ordering::Relaxed);
+                            totals
+                                .items
+                                .fetch_add(files + dirs - seen_items, Ordering::Relaxed);
+                            (seen_bytes, seen_items) = (bytes, files + dirs);
+                        },
+                    );
+                    drop(scanning);
+
+                    // share the result with normal browsing
+                    if !t.finished && t.bytes == 0 {
+                        return;
+                    }
+                    if let Ok(mut cache) = SIZE_CACHE.lock() {
+                        cache.insert(
+                            key,
+                            CacheEntry {
+                                bytes: t.bytes,
+                                items: t.files + t.dirs,
+                                files: t.files,
+                                dirs: t.dirs,
+                                completed: t.finished && !t.estimated,
+                                estimated: t.estimated,
+                                errors: t.errors.count,
+                                complete_with_errors: t.finished
+                                    && !t.estimated
+                                    && t.errors.count > 0,
+                                updated_at: SystemTime::now(),
+                            },
+                        );
+                    }
+                })
+                .await;
+            }));
+        }
+
+        // 3) progress ticker while walkers run
+        let finished = Arc::new(AtomicBool::new(false));
+        let ticker = tauri::async_runtime::spawn({
+            let app = app.clone();
+            let label = label.clone();
+            let request_id = request_id.clone();
+            let totals = totals.clone();
+            let finished = finished.clone();
+            let cancel = cancel.clone();
+            async move {
+                let mut iv = tokio::time::interval(Duration::from_millis(150));
+                loop {
+                    iv.tick().await;
+                    if finished.load(Ordering::SeqCst) || cancel.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let _ = app.emit_to(
+                        label.as_str(),
+                        "selection_size:progress",
+                        totals.event(&request_id, false),
+                    );
+                }
+            }
+        });
+
+        for t in tasks {
+            let _ = t.await;
+        }
+        finished.store(true, Ordering::SeqCst);
+        let _ = ticker.await;
+
+        let cancelled = cancel.load(Ordering::SeqCst);
+        let _ = app.emit_to(
+            label.as_str(),
+            "selection_size:done",
+            totals.event(&request_id, cancelled),
+        );
+
+        if let Ok(mut sel) = SELECTIONS.lock() {
+            if sel
+                .get(&request_id)
+                .is_some_and(|c| Arc::ptr_eq(c, &cancel))
+            {
+                sel.remove(&request_id);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+pub fn cancel(request_id: &str) -> bool {
+    match SELECTIONS
+        .lock()
+        .ok()
+        .and_then(|mut s| s.remove(request_id))
+    {
+        Some(c) => {
+            c.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use tauri::AppHandle;
+
+use crate::api::{
+    fs_meta::volume_usage,
+    types::{ApiError, SizerOptions, VolumeEvent},
+};
+
+use super::{events::Audience, worker};
+
+/// Pseudo filesystems in `/` (normally other mounts anyway); a volume scan of
+/// the system root leaves them out
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SYSTEM_ROOT_SKIPS: &[&str] = &["proc", "sys", "dev", "run"];
+#[cfg(target_os = "macos")]
+const SYSTEM_ROOT_SKIPS: &[&str] = &["dev", ".vol"];
+#[cfg(all(
+    unix,
+    not(any(target_os = "linux", target_os = "android", target_os = "macos"))
+))]
+const SYSTEM_ROOT_SKIPS: &[&str] = &["proc", "dev"];
+
+/// Paging/hibernation files and volume metadata at the root of every drive,
+/// unreadable without elevation
+#[cfg(windows)]
+const VOLUME_ROOT_SKIPS: &[&str] = &[
+    "pagefile.sys",
+    "hiberfil.sys",
+    "swapfile.sys",
+    "System Volume Information",
+    "$Extend",
+];
+
+/// Whether a volume scan of `root` leaves out its entry `name`
+#[cfg(unix)]
+pub fn skipped_at_root(root: &Path, name: &str) -> bool {
+    root.has_root() && root.parent().is_none() && SYSTEM_ROOT_SKIPS.contains(&name)
+}
+
+#[cfg(windows)]
+pub fn skipped_at_root(_root: &Path, name: &str) -> bool {
+    VOLUME_ROOT_SKIPS
+        .iter()
+        .any(|s| s.eq_ignore_ascii_case(name))
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn skipped_at_root(_root: &Path, _name: &str) -> bool {
+    false
+}
+
+/// Size a whole volume with each top-level dir as a child scan. Mount points
+/// below it are always left out (`same_device`), so the children are cached
+/// under same_device keys, and under the caller's own keys too when their
+/// walk met no mount point (the totals being the same then).
+#[allow(clippy::too_many_arguments)]
+pub fn analyze_volume_impl(
+    app: AppHandle,
+    mount_point: String,
+    request_id: String,
+    show_hidden: bool,
+    ignores: Vec<String>,
+    mut opts: SizerOptions,
+    top_n: usize,
+    concurrency: Option<usize>,
+    batch_events: bool,
+    audience: Audience,
+) -> Result<String, ApiError> {
+    let browse_opts = (!opts.same_device).then(|| opts.clone());
+    opts.same_device = true;
+    worker::ensure_path_sizer_impl(
+        app,
+        mount_point,
+        request_id,
+        show_hidden,
+        ignores,
+        opts,
+        worker::ScanConfig {
+            top_n,
+            concurrency,
+            batch_events,
+            volume: true,
+            also_cache_as: browse_opts,
+            audience,
+            ..Default::default()
+        },
+    )
+}
+
+/// After a volume scan's summary: the filesystem's own used space against what
+/// the scan found, the difference being what it couldn't see
+pub fn emit_report(
+    app: &AppHandle,
+    audience: &Audience,
+    job_id: &str,
+    scan_key: &str,
+    path: &str,
+    scanned: u64,
+) {
+    let Some((total, used)) = volume_usage(Path::new(path)) else {
+        return;
+    };
+    audience.emit(
+        app,
+        "dir_size:volume",
+        VolumeEvent {
+            job_id: job_id.to_string(),
+            scan_key: scan_key.to_string(),
+            echo_path: path.to_string(),
+            total_bytes: total,
+            used_bytes: used,
+            scanned_bytes: scanned,
+            other_bytes: used.saturating_sub(scanned),
+        },
+    );
+}
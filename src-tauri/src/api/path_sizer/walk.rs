@@ -0,0 +1,992 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    fs::{DirEntry, Metadata, ReadDir},
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use rayon::{Scope, ThreadPool, ThreadPoolBuilder};
+
+use crate::api::{
+    fs_meta::{allocated_size, device_id, file_id, hardlink_id, is_junction},
+    path_sizer::{jobs, keys::IgnoreSet},
+    types::{SizeMode, SizerOptions, TopEntry},
+};
+
+/// How often a paused scan looks at its flags again
+pub const PAUSE_POLL: Duration = Duration::from_millis(100);
+
+/// What a walk checks between entries
+pub struct WalkFlags<'a> {
+    pub cancel: &'a AtomicBool,
+    pub paused: &'a AtomicBool,
+    /// the job's priority: while false the walk also parks as long as a visible
+    /// scan runs (None never yields)
+    pub visible: Option<&'a AtomicBool>,
+}
+
+impl WalkFlags<'_> {
+    /// Parked while paused, or while a background job yields to visible scans
+    pub fn held(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+            || self
+                .visible
+                .is_some_and(|v| !v.load(Ordering::SeqCst) && jobs::JOBS.visible_active())
+    }
+}
+
+/// How many error paths a scan keeps for the UI's tooltip
+pub const MAX_ERROR_PATHS: usize = 8;
+
+/// Entries that couldn't be read, plus the first few of their paths
+#[derive(Default)]
+pub struct ErrorTally {
+    pub count: u64,
+    pub paths: Vec<PathBuf>,
+}
+
+impl ErrorTally {
+    pub fn note(&mut self, path: Option<&Path>) {
+        self.count += 1;
+        if let Some(p) = path {
+            if self.paths.len() < MAX_ERROR_PATHS {
+                self.paths.push(p.to_path_buf());
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: ErrorTally) {
+        self.count += other.count;
+        let room = MAX_ERROR_PATHS.saturating_sub(self.paths.len());
+        self.paths.extend(other.paths.into_iter().take(room));
+    }
+}
+
+/// The `cap` largest entries seen, as a bounded min-heap; cap 0 keeps nothing.
+pub struct TopN {
+    cap: usize,
+    heap: BinaryHeap<Reverse<(u64, PathBuf, bool)>>,
+}
+
+impl TopN {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            cap,
+            heap: BinaryHeap::with_capacity(cap.min(1024) + 1),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.cap > 0
+    }
+
+    fn accepts(&self, bytes: u64) -> bool {
+        self.cap > 0
+            && (self.heap.len() < self.cap
+                || self.heap.peek().is_some_and(|Reverse(min)| bytes > min.0))
+    }
+
+    pub fn push(&mut self, bytes: u64, path: &Path, is_dir: bool) {
+        if !self.accepts(bytes) {
+            return;
+        }
+        self.heap.push(Reverse((bytes, path.to_path_buf(), is_dir)));
+        if self.heap.len() > self.cap {
+            self.heap.pop();
+        }
+    }
+
+    pub fn extend(&mut self, entries: &[TopEntry]) {
+        for e in entries {
+            self.push(e.bytes, Path::new(&e.path), e.is_dir);
+        }
+    }
+
+    /// Largest first
+    pub fn into_entries(self) -> Vec<TopEntry> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((bytes, path, is_dir))| TopEntry {
+                path: path.to_string_lossy().to_string(),
+                bytes,
+                is_dir,
+            })
+            .collect()
+    }
+}
+
+/// Size of one file as the scan's `size_mode` measures it
+pub fn file_size(path: &Path, md: &Metadata, mode: SizeMode) -> u64 {
+    match mode {
+        SizeMode::Apparent => md.len(),
+        SizeMode::DiskUsage => allocated_size(path, md),
+    }
+}
+
+#[derive(Default)]
+pub struct WalkTotals {
+    pub bytes: u64,
+    pub files: u64,
+    pub dirs: u64,
+    pub finished: bool,
+    /// dirs on another device, left out with `same_device`
+    pub skipped_mounts: Vec<PathBuf>,
+    /// `max_depth` stopped the walk above a directory, so the sum is a lower bound
+    pub estimated: bool,
+    /// broken symlinks met with `follow_symlinks`
+    pub unreadable: u64,
+    /// directory junctions left out (see `follow_junctions`)
+    pub junctions: u64,
+    /// dirs/files that couldn't be read (permission denied, vanished mid-walk)
+    pub errors: ErrorTally,
+    /// totals of the dirs up to `nested_cache_depth` levels below the walk root
+    pub nested: Vec<NestedTotals>,
+}
+
+/// One directory's share of a walk (see `SizerOptions::nested_cache_depth`)
+pub struct NestedTotals {
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub files: u64,
+    pub dirs: u64,
+    pub errors: u64,
+    /// left out a hard-linked file or followed link the walk had already met
+    /// elsewhere, so a walk of this dir alone would count more
+    pub partial: bool,
+}
+
+/// Most nested dirs one walk keeps totals for; deeper or further ones only
+/// add to their tracked ancestors
+const MAX_NESTED_DIRS: usize = 2048;
+
+/// Running totals of a tracked dir, linked to its closest tracked ancestor
+struct DirSum {
+    path: PathBuf,
+    bytes: AtomicU64,
+    files: AtomicU64,
+    dirs: AtomicU64,
+    errors: AtomicU64,
+    partial: AtomicBool,
+    parent: Option<Arc<DirSum>>,
+}
+
+impl DirSum {
+    /// Apply `f` to `sum` and each of its tracked ancestors
+    fn each(sum: Option<&Arc<DirSum>>, f: impl Fn(&DirSum)) {
+        let mut at = sum;
+        while let Some(s) = at {
+            f(s);
+            at = s.parent.as_ref();
+        }
+    }
+}
+
+/// How often the walking threads' counters are sampled for `on_progress`
+const PROGRESS_EVERY: Duration = Duration::from_millis(100);
+
+/// The threads reading directories, shared by every walk running at once so
+/// concurrent scans don't multiply them. Reads mostly wait on the disk, hence
+/// more threads than cores.
+static POOL: Lazy<ThreadPool> = Lazy::new(|| {
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get() * 2)
+        .unwrap_or(8)
+        .clamp(4, 32);
+    ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|i| format!("walk-{}", i))
+        .build()
+        .expect("walk thread pool")
+});
+
+/// A directory waiting to be read
+struct DirTask {
+    path: PathBuf,
+    depth: usize,
+    /// the walk root's immediate subfolder this dir is in (for `top`)
+    subdir: Option<PathBuf>,
+    /// the closest tracked dir at or above this one
+    sum: Option<Arc<DirSum>>,
+    /// entries still to go: where a read parked by a pause left off, or a
+    /// listing taken before the walk
+    rest: Option<Listing>,
+}
+
+/// A directory's entries, being read or read already
+enum Listing {
+    Reading(ReadDir),
+    Listed(std::vec::IntoIter<io::Result<DirEntry>>),
+}
+
+impl Iterator for Listing {
+    type Item = io::Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Listing::Reading(rd) => rd.next(),
+            Listing::Listed(entries) => entries.next(),
+        }
+    }
+}
+
+/// One directory task of a walk, counted from its spawn until it ends, even
+/// by a panic, so the walk never waits on a task that is gone
+struct Running<'s, 'a>(&'s Shared<'a>);
+
+impl Drop for Running<'_, '_> {
+    fn drop(&mut self) {
+        if self.0.pending.fetch_sub(1, Ordering::SeqCst) == 1 {
+            // under the lock, so the sampler can't miss it between check and wait
+            let _idle = self.0.idle_lock.lock();
+            self.0.idle.notify_all();
+        }
+    }
+}
+
+/// State shared by the threads of one walk
+struct Shared<'a> {
+    rel_root: &'a Path,
+    ignores: &'a IgnoreSet,
+    opts: &'a SizerOptions,
+    flags: &'a WalkFlags<'a>,
+    root_dev: Option<u64>,
+    depth_limit: Option<usize>,
+
+    /// dir tasks spawned and not yet ended
+    pending: AtomicUsize,
+    idle_lock: Mutex<()>,
+    idle: Condvar,
+    /// reads put aside while the walk is held, so they don't tie up the pool
+    parked: Mutex<Vec<DirTask>>,
+    stopped: AtomicBool,
+
+    bytes: AtomicU64,
+    files: AtomicU64,
+    dirs: AtomicU64,
+    estimated: AtomicBool,
+    unreadable: AtomicU64,
+    junctions: AtomicU64,
+    /// (device, inode) of files already counted (multi-linked, or all when following)
+    seen_links: Mutex<HashSet<(u64, u64)>>,
+    /// when following links: real dirs already entered, so link cycles and
+    /// several links to one tree are walked once
+    seen_dirs: Mutex<HashSet<(u64, u64)>>,
+    skipped_mounts: Mutex<Vec<PathBuf>>,
+    errors: Mutex<ErrorTally>,
+    top: Option<Mutex<&'a mut TopN>>,
+    /// levels below the root whose dirs get their own totals
+    nested_depth: usize,
+    nested: Mutex<Vec<Arc<DirSum>>>,
+    /// immediate subfolder -> bytes below it, for `top`
+    subdir_bytes: Mutex<HashMap<PathBuf, u64>>,
+}
+
+impl Shared<'_> {
+    fn spawn<'s>(&'s self, s: &Scope<'s>, task: DirTask) {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        s.spawn(move |s| {
+            let _running = Running(self);
+            if !self.stopped.load(Ordering::SeqCst) {
+                self.read(task, s);
+            }
+        });
+    }
+
+    /// Wait up to `timeout` for every task to end; true once they have
+    fn wait_idle(&self, timeout: Duration) -> bool {
+        let Ok(guard) = self.idle_lock.lock() else {
+            return self.pending.load(Ordering::SeqCst) == 0;
+        };
+        if self.pending.load(Ordering::SeqCst) > 0 {
+            let _ = self.idle.wait_timeout(guard, timeout);
+        }
+        self.pending.load(Ordering::SeqCst) == 0
+    }
+
+    fn park(&self, task: DirTask) {
+        if let Ok(mut parked) = self.parked.lock() {
+            parked.push(task);
+        }
+    }
+
+    /// Spawn the parked reads again; returns how many there were
+    fn resume<'s>(&'s self, s: &Scope<'s>) -> usize {
+        let parked = match self.parked.lock() {
+            Ok(mut parked) => std::mem::take(&mut *parked),
+            Err(_) => return 0,
+        };
+        let n = parked.len();
+        for task in parked {
+            self.spawn(s, task);
+        }
+        n
+    }
+
+    fn has_parked(&self) -> bool {
+        self.parked.lock().is_ok_and(|p| !p.is_empty())
+    }
+
+    fn note_error(&self, path: Option<&Path>, sum: Option<&Arc<DirSum>>) {
+        if let Ok(mut e) = self.errors.lock() {
+            e.note(path);
+        }
+        DirSum::each(sum, |s| {
+            s.errors.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+
+    fn first_visit(set: &Mutex<HashSet<(u64, u64)>>, id: Option<(u64, u64)>) -> bool {
+        match (id, set.lock()) {
+            (Some(id), Ok(mut seen)) => seen.insert(id),
+            _ => true,
+        }
+    }
+
+    /// Something under `sum` was already counted elsewhere in the walk, which
+    /// may have been outside it
+    fn mark_partial(sum: Option<&Arc<DirSum>>) {
+        DirSum::each(sum, |s| s.partial.store(true, Ordering::Relaxed));
+    }
+
+    /// Count one directory's entries and spawn its subdirectories; a held
+    /// walk parks the read where it is
+    fn read<'s>(&'s self, mut task: DirTask, s: &Scope<'s>) {
+        let mut rd = match task.rest.take() {
+            Some(rd) => rd,
+            None => match std::fs::read_dir(&task.path) {
+                Ok(rd) => Listing::Reading(rd),
+                Err(_) => {
+                    self.note_error(Some(&task.path), task.sum.as_ref());
+                    return;
+                }
+            },
+        };
+        let depth = task.depth + 1;
+
+        loop {
+            if self.flags.cancel.load(Ordering::SeqCst) {
+                self.stopped.store(true, Ordering::SeqCst);
+                return;
+            }
+            if self.flags.held() {
+                task.rest = Some(rd);
+                self.park(task);
+                return;
+            }
+            let Some(ent) = rd.next() else {
+                break;
+            };
+            let Ok(ent) = ent else {
+                self.note_error(Some(&task.path), task.sum.as_ref());
+                continue;
+            };
+            let Ok(ft) = ent.file_type() else {
+                self.note_error(Some(&ent.path()), task.sum.as_ref());
+                continue;
+            };
+            let path = ent.path();
+
+            // links are either followed (to their target) or left out;
+            // junctions have their own switch and are counted when left out
+            let md = if ft.is_symlink() {
+                let junction = is_junction(&path);
+                if junction && !self.opts.follow_junctions {
+                    self.junctions.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+                if !junction && !self.opts.follow_symlinks {
+                    continue;
+                }
+                match std::fs::metadata(&path) {
+                    Ok(md) => Some(md),
+                    Err(_) => {
+                        self.unreadable.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+                }
+            } else {
+                None
+            };
+            let is_dir = md.as_ref().map_or(ft.is_dir(), |m| m.is_dir());
+            let is_file = md.as_ref().map_or(ft.is_file(), |m| m.is_file());
+
+            let rel = path.strip_prefix(self.rel_root).unwrap_or(&path);
+            if self.ignores.is_skipped(rel, is_dir) {
+                continue;
+            }
+
+            let md = match md {
+                Some(md) => md,
+                None if is_file || self.root_dev.is_some() || self.opts.follows_links() => {
+                    match ent.metadata() {
+                        Ok(md) => md,
+                        Err(_) => {
+                            self.note_error(Some(&path), task.sum.as_ref());
+                            continue;
+                        }
+                    }
+                }
+                // plain dir, nothing to look up
+                None if is_dir => {
+                    self.enter_dir(s, path, depth, &task, None);
+                    continue;
+                }
+                None => continue,
+            };
+
+            if is_dir {
+                self.enter_dir(s, path, depth, &task, Some(&md));
+            } else if is_file {
+                self.count_file(&path, &md, &task);
+            }
+        }
+    }
+
+    fn enter_dir<'s>(
+        &'s self,
+        s: &Scope<'s>,
+        path: PathBuf,
+        depth: usize,
+        parent: &DirTask,
+        md: Option<&Metadata>,
+    ) {
+        if let (Some(root_dev), Some(md)) = (self.root_dev, md) {
+            if device_id(&path, md).is_some_and(|d| d != root_dev) {
+                if let Ok(mut m) = self.skipped_mounts.lock() {
+                    m.push(path);
+                }
+                return;
+            }
+        }
+        if self.opts.follows_links() {
+            let id = md.and_then(|md| file_id(&path, md));
+            if !Self::first_visit(&self.seen_dirs, id) {
+                Self::mark_partial(parent.sum.as_ref());
+                return;
+            }
+        }
+        self.dirs.fetch_add(1, Ordering::Relaxed);
+        DirSum::each(parent.sum.as_ref(), |s| {
+            s.dirs.fetch_add(1, Ordering::Relaxed);
+        });
+        if self.depth_limit == Some(depth) {
+            self.estimated.store(true, Ordering::Relaxed);
+            return;
+        }
+        let subdir = parent.subdir.clone().or_else(|| Some(path.clone()));
+        let sum = match self.track(&path, depth, parent.sum.as_ref()) {
+            Some(sum) => Some(sum),
+            None => parent.sum.clone(),
+        };
+        self.spawn(
+            s,
+            DirTask {
+                path,
+                depth,
+                subdir,
+                sum,
+                rest: None,
+            },
+        );
+    }
+
+    /// Start totals for `path` when it's shallow enough and the walk has room
+    fn track(
+        &self,
+        path: &Path,
+        depth: usize,
+        parent: Option<&Arc<DirSum>>,
+    ) -> Option<Arc<DirSum>> {
+        if depth > self.nested_depth {
+            return None;
+        }
+        let mut nested = self.nested.lock().ok()?;
+        if nested.len() >= MAX_NESTED_DIRS {
+            return None;
+        }
+        let sum = Arc::new(DirSum {
+            path: path.to_path_buf(),
+            bytes: AtomicU64::new(0),
+            files: AtomicU64::new(0),
+            dirs: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            partial: AtomicBool::new(false),
+            parent: parent.cloned(),
+        });
+        nested.push(sum.clone());
+        Some(sum)
+    }
+
+    fn count_file(&self, path: &Path, md: &Metadata, task: &DirTask) {
+        // followed links can reach one real file several times,
+        // whatever its link count
+        let id = if self.opts.follows_links() {
+            file_id(path, md)
+        } else if self.opts.dedupe_hardlinks {
+            hardlink_id(path, md)
+        } else {
+            None
+        };
+        if id.is_some() && !Self::first_visit(&self.seen_links, id) {
+            Self::mark_partial(task.sum.as_ref());
+            return;
+        }
+        let size = file_size(path, md, self.opts.size_mode);
+        self.bytes.fetch_add(size, Ordering::Relaxed);
+        self.files.fetch_add(1, Ordering::Relaxed);
+        DirSum::each(task.sum.as_ref(), |s| {
+            s.bytes.fetch_add(size, Ordering::Relaxed);
+            s.files.fetch_add(1, Ordering::Relaxed);
+        });
+
+        if let Some(top) = &self.top {
+            if let Ok(mut t) = top.lock() {
+                t.push(size, path, false);
+            }
+            if let (Some(sub), Ok(mut sums)) = (&task.subdir, self.subdir_bytes.lock()) {
+                *sums.entry(sub.to_path_buf()).or_default() += size;
+            }
+        }
+    }
+
+    fn counts(&self) -> (u64, u64, u64) {
+        (
+            self.bytes.load(Ordering::Relaxed),
+            self.files.load(Ordering::Relaxed),
+            self.dirs.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Sum the sizes of every file under `dir` and count files/dirs below it.
+/// Directories are read on the shared walk pool, several at once;
+/// `on_progress(bytes, files, dirs)` is called on this thread from counters
+/// sampled every 100ms, and once more at the end if anything changed since the
+/// last call. A held walk (see `WalkFlags::held`) leaves the pool until released.
+/// Ignore path patterns see entry paths relative to `rel_root` (the scan root).
+/// Multi-linked files (and, with `follow_symlinks`, any real file or dir reached
+/// twice) are counted once per walk.
+/// When `top` is enabled it collects the largest files and immediate subfolders.
+/// `listed`: `dir`'s entries when they were read already (see `precount`).
+#[allow(clippy::too_many_arguments)]
+pub fn walk_tree(
+    dir: &Path,
+    listed: Option<Vec<io::Result<DirEntry>>>,
+    rel_root: &Path,
+    ignores: &IgnoreSet,
+    opts: &SizerOptions,
+    flags: &WalkFlags,
+    top: &mut TopN,
+    mut on_progress: impl FnMut(u64, u64, u64),
+) -> WalkTotals {
+    let root_md = std::fs::metadata(dir).ok();
+    let root_dev = match (&root_md, opts.same_device) {
+        (Some(md), true) => device_id(dir, md),
+        _ => None,
+    };
+    // max_depth counts from the scan root; `dir` may already sit below it
+    let depth_limit = opts.max_depth.map(|d| {
+        let offset = dir
+            .strip_prefix(rel_root)
+            .map(|r| r.components().count())
+            .unwrap_or(0);
+        (d as usize).saturating_sub(offset)
+    });
+    let top_enabled = top.enabled();
+
+    let shared = Shared {
+        rel_root,
+        ignores,
+        opts,
+        flags,
+        root_dev,
+        depth_limit,
+        pending: AtomicUsize::new(0),
+        idle_lock: Mutex::new(()),
+        idle: Condvar::new(),
+        parked: Mutex::new(Vec::new()),
+        stopped: AtomicBool::new(false),
+        bytes: AtomicU64::new(0),
+        files: AtomicU64::new(0),
+        dirs: AtomicU64::new(0),
+        estimated: AtomicBool::new(false),
+        unreadable: AtomicU64::new(0),
+        junctions: AtomicU64::new(0),
+        seen_links: Mutex::new(HashSet::new()),
+        seen_dirs: Mutex::new(HashSet::new()),
+        skipped_mounts: Mutex::new(Vec::new()),
+        errors: Mutex::new(ErrorTally::default()),
+        top: top_enabled.then(|| Mutex::new(top)),
+        subdir_bytes: Mutex::new(HashMap::new()),
+        // a shallow walk's subtotals would be estimates too
+        nested_depth: match opts.max_depth {
+            Some(_) => 0,
+            None => opts.nested_cache_depth as usize,
+        },
+        nested: Mutex::new(Vec::new()),
+    };
+
+    if opts.follows_links() {
+        let id = root_md.as_ref().and_then(|md| file_id(dir, md));
+        Shared::first_visit(&shared.seen_dirs, id);
+    }
+    let mut reported = (0, 0, 0);
+    POOL.in_place_scope(|s| {
+        let shared = &shared;
+        if depth_limit == Some(0) {
+            shared.estimated.store(true, Ordering::Relaxed);
+        } else {
+            shared.spawn(
+                s,
+                DirTask {
+                    path: dir.to_path_buf(),
+                    depth: 0,
+                    subdir: None,
+                    sum: None,
+                    rest: listed.map(|entries| Listing::Listed(entries.into_iter())),
+                },
+            );
+        }
+
+        // sample the counters until the last task ends, releasing parked
+        // reads once the walk isn't held anymore
+        loop {
+            let idle = shared.wait_idle(PROGRESS_EVERY);
+            if flags.cancel.load(Ordering::SeqCst) {
+                shared.stopped.store(true, Ordering::SeqCst);
+            }
+            let stopped = shared.stopped.load(Ordering::SeqCst);
+            let now = shared.counts();
+            if now != reported && !stopped {
+                on_progress(now.0, now.1, now.2);
+                reported = now;
+            }
+            let resumed = if stopped || flags.held() {
+                0
+            } else {
+                shared.resume(s)
+            };
+            if idle && resumed == 0 && (stopped || !shared.has_parked()) {
+                break;
+            }
+        }
+    });
+
+    let canceled = shared.stopped.load(Ordering::SeqCst);
+    let (bytes, files, dirs) = shared.counts();
+    if !canceled && (bytes, files, dirs) != reported {
+        on_progress(bytes, files, dirs);
+    }
+
+    let subdir_bytes = shared.subdir_bytes.into_inner().unwrap_or_default();
+    if let Some(top) = shared.top {
+        if let Ok(top) = top.into_inner() {
+            for (path, bytes) in subdir_bytes {
+                top.push(bytes, &path, true);
+            }
+        }
+    }
+
+    WalkTotals {
+        bytes,
+        files,
+        dirs,
+        finished: !canceled,
+        skipped_mounts: shared.skipped_mounts.into_inner().unwrap_or_default(),
+        estimated: shared.estimated.into_inner(),
+        unreadable: shared.unreadable.into_inner(),
+        junctions: shared.junctions.into_inner(),
+        errors: shared.errors.into_inner().unwrap_or_default(),
+        nested: shared
+            .nested
+            .into_inner()
+            .unwrap_or_default()
+            .iter()
+            .map(|s| NestedTotals {
+                path: s.path.clone(),
+                bytes: s.bytes.load(Ordering::Relaxed),
+                files: s.files.load(Ordering::Relaxed),
+                dirs: s.dirs.load(Ordering::Relaxed),
+                errors: s.errors.load(Ordering::Relaxed),
+                partial: s.partial.load(Ordering::Relaxed),
+            })
+            .collect(),
+    }
+}
+
+/// Dirs with more entries than this don't keep their listing for the walk
+const MAX_LISTED: usize = 4096;
+/// Listings a precount keeps at most; each one holds its dir open
+const MAX_LISTINGS: usize = 64;
+
+/// What `precount` found
+#[derive(Default)]
+pub struct Precount {
+    /// (items, bytes) below the dirs; None when cancelled or out of time
+    pub totals: Option<(u64, u64)>,
+    /// the dirs' own entries, for their walks to start from (see `walk_tree`);
+    /// left out for large dirs and past `MAX_LISTINGS`
+    pub listings: HashMap<PathBuf, Vec<io::Result<DirEntry>>>,
+}
+
+/// One entry's share of a precount
+enum Counted {
+    Dir(PathBuf),
+    File(u64),
+    Skipped,
+}
+
+/// Quick count of what's below `dirs` (subfolders of the scan root
+/// `rel_root`), for a progress estimate. Uses the walk's filters (ignores,
+/// links, `same_device`, `max_depth`) but no hardlink dedupe. Stops at
+/// `deadline` or on cancel, with no totals.
+pub fn precount(
+    dirs: &[PathBuf],
+    rel_root: &Path,
+    ignores: &IgnoreSet,
+    opts: &SizerOptions,
+    cancel: &AtomicBool,
+    deadline: Instant,
+) -> Precount {
+    let root_dev = if opts.same_device {
+        std::fs::metadata(rel_root)
+            .ok()
+            .and_then(|md| device_id(rel_root, &md))
+    } else {
+        None
+    };
+    let mut seen_dirs = HashSet::new();
+    // the given dirs sit at depth 1, their entries at 2, ...
+    let mut stack: Vec<(PathBuf, usize)> = dirs.iter().map(|d| (d.clone(), 1)).collect();
+    let mut found = Precount::default();
+    let (mut items, mut bytes) = (0u64, 0u64);
+    while let Some((dir, depth)) = stack.pop() {
+        if opts.max_depth.is_some_and(|max| depth >= max as usize) {
+            continue;
+        }
+        let Ok(rd) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut listing = (depth == 1 && found.listings.len() < MAX_LISTINGS).then(Vec::new);
+        for ent in rd {
+            if cancel.load(Ordering::Relaxed) || Instant::now() >= deadline {
+                return found;
+            }
+            let counted = match &ent {
+                Ok(ent) => count_entry(ent, rel_root, ignores, opts, root_dev, &mut seen_dirs),
+                Err(_) => Counted::Skipped,
+            };
+            match counted {
+                Counted::Dir(path) => {
+                    items += 1;
+                    stack.push((path, depth + 1));
+                }
+                Counted::File(size) => {
+                    items += 1;
+                    bytes += size;
+                }
+                Counted::Skipped => {}
+            }
+            if listing.as_ref().is_some_and(|l| l.len() >= MAX_LISTED) {
+                listing = None;
+            }
+            if let Some(l) = &mut listing {
+                l.push(ent);
+            }
+        }
+        if let Some(listing) = listing {
+            found.listings.insert(dir, listing);
+        }
+    }
+    found.totals = Some((items, bytes));
+    found
+}
+
+/// How the walk would take `ent`, as far as a precount cares
+fn count_entry(
+    ent: &DirEntry,
+    rel_root: &Path,
+    ignores: &IgnoreSet,
+    opts: &SizerOptions,
+    root_dev: Option<u64>,
+    seen_dirs: &mut HashSet<(u64, u64)>,
+) -> Counted {
+    let Ok(ft) = ent.file_type() else {
+        return Counted::Skipped;
+    };
+    let path = ent.path();
+    let md = if ft.is_symlink() {
+        let follow = if is_junction(&path) {
+            opts.follow_junctions
+        } else {
+            opts.follow_symlinks
+        };
+        if !follow {
+            return Counted::Skipped;
+        }
+        std::fs::metadata(&path)
+    } else {
+        ent.metadata()
+    };
+    let Ok(md) = md else {
+        return Counted::Skipped;
+    };
+    let rel = path.strip_prefix(rel_root).unwrap_or(&path);
+    if ignores.is_skipped(rel, md.is_dir()) {
+        return Counted::Skipped;
+    }
+    if md.is_file() {
+        return Counted::File(file_size(&path, &md, opts.size_mode));
+    }
+    if !md.is_dir() {
+        return Counted::Skipped;
+    }
+    if root_dev.is_some_and(|dev| device_id(&path, &md).is_some_and(|d| d != dev)) {
+        return Counted::Skipped;
+    }
+    if opts.follows_links() && file_id(&path, &md).is_some_and(|id| !seen_dirs.insert(id)) {
+        return Counted::Skipped;
+    }
+    Counted::Dir(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `dirs` folders of `files` one-byte files each, under a fresh temp dir
+    fn tree(name: &str, dirs: usize, files: usize) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("walk-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for d in 0..dirs {
+            let dir = root.join(format!("d{}", d / 100)).join(format!("d{}", d));
+            std::fs::create_dir_all(&dir).unwrap();
+            for f in 0..files {
+                std::fs::write(dir.join(format!("f{}", f)), b"x").unwrap();
+            }
+        }
+        root
+    }
+
+    fn walk(root: &Path, paused: &AtomicBool) -> WalkTotals {
+        walk_listed(root, None, root, paused)
+    }
+
+    fn walk_listed(
+        dir: &Path,
+        listed: Option<Vec<io::Result<DirEntry>>>,
+        root: &Path,
+        paused: &AtomicBool,
+    ) -> WalkTotals {
+        let ignores = IgnoreSet::new(true, &[]).unwrap();
+        walk_tree(
+            dir,
+            listed,
+            root,
+            &ignores,
+            &SizerOptions::default(),
+            &WalkFlags {
+                cancel: &AtomicBool::new(false),
+                paused,
+                visible: None,
+            },
+            &mut TopN::new(0),
+            |_, _, _| {},
+        )
+    }
+
+    #[test]
+    fn paused_walk_finishes_once_released() {
+        let root = tree("paused", 30, 10);
+        let paused = AtomicBool::new(true);
+        let t = std::thread::scope(|s| {
+            s.spawn(|| {
+                std::thread::sleep(Duration::from_millis(300));
+                paused.store(false, Ordering::SeqCst);
+            });
+            walk(&root, &paused)
+        });
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(t.finished);
+        // d0 holds all 30 leaves
+        assert_eq!((t.bytes, t.files, t.dirs), (300, 300, 31));
+    }
+
+    #[test]
+    fn precount_agrees_with_the_walk() {
+        let root = tree("precount", 30, 10);
+        let child = root.join("d0");
+        let mut counted = precount(
+            std::slice::from_ref(&child),
+            &root,
+            &IgnoreSet::new(true, &[]).unwrap(),
+            &SizerOptions::default(),
+            &AtomicBool::new(false),
+            Instant::now() + Duration::from_secs(60),
+        );
+        let listed = counted.listings.remove(&child);
+        assert!(listed.is_some());
+        let t = walk_listed(&child, listed, &root, &AtomicBool::new(false));
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(counted.totals, Some((t.files + t.dirs, t.bytes)));
+        assert_eq!((t.bytes, t.files, t.dirs), (300, 300, 30));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn nested_dir_missing_a_shared_link_is_partial() {
+        let root = tree("links", 2, 1);
+        let (a, b) = (root.join("d0").join("d0"), root.join("d0").join("d1"));
+        std::fs::hard_link(a.join("f0"), b.join("link")).unwrap();
+        let t = walk(&root, &AtomicBool::new(false));
+        let alone = |dir: &Path| walk_listed(dir, None, &root, &AtomicBool::new(false)).bytes;
+        let (a_alone, b_alone) = (alone(&a), alone(&b));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        // one link is counted once overall; whichever sibling missed it says so
+        assert_eq!(t.bytes, 2);
+        for n in &t.nested {
+            let whole = if n.path == a {
+                a_alone
+            } else if n.path == b {
+                b_alone
+            } else {
+                continue;
+            };
+            assert!(n.partial || n.bytes == whole, "{:?}", n.path);
+        }
+        assert!(t.nested.iter().any(|n| n.partial));
+    }
+
+    /// `cargo test walk_500k -- --ignored --nocapture`: the pool against one
+    /// thread reading the same tree
+    #[test]
+    #[ignore]
+    fn walk_500k_files() {
+        let root = tree("500k", 1000, 500);
+        let started = Instant::now();
+        let t = walk(&root, &AtomicBool::new(false));
+        let pooled = started.elapsed();
+
+        let started = Instant::now();
+        let mut files = 0u64;
+        for ent in walkdir::WalkDir::new(&root).into_iter().flatten() {
+            if ent.metadata().is_ok_and(|md| md.is_file()) {
+                files += 1;
+            }
+        }
+        let single = started.elapsed();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        println!("pool: {:?}, one thread: {:?}", pooled, single);
+        assert_eq!((t.files, files), (500_000, 500_000));
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+    DebounceEventResult, Debouncer,
+};
+use once_cell::sync::Lazy;
+use tauri::AppHandle;
+
+use super::{
+    cache::{invalidate_related, Invalidation},
+    keys::normalize_maybe_missing,
+};
+
+/// Folders the UI has open; only these are watched (recursively)
+const MAX_ROOTS: usize = 16;
+/// A path is reported once it's been quiet this long, so a build writing 10k
+/// files yields a few batches rather than 10k events
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Oldest first
+static ROOTS: Lazy<Mutex<Vec<PathBuf>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static WATCHER: Lazy<Mutex<Option<Debouncer<RecommendedWatcher>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Set up the watcher; it watches nothing until `set_enabled(true)`.
+///
+/// A debounced batch of changed paths marks the cached sizes at, above or
+/// below them stale (`Invalidation::Stale`).
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    let debouncer = new_debouncer(DEBOUNCE, move |res: DebounceEventResult| {
+        let events = match res {
+            Ok(events) => events,
+            Err(e) => {
+                tracing::warn!(error = %e, "size watcher error");
+                return;
+            }
+        };
+        if !ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut changed: Vec<PathBuf> = events
+            .iter()
+            .map(|e| normalize_maybe_missing(&e.path))
+            .collect();
+        changed.sort();
+        changed.dedup();
+        invalidate_related(&app, &changed, Invalidation::Stale);
+    });
+    match debouncer {
+        Ok(debouncer) => {
+            if let Ok(mut w) = WATCHER.lock() {
+                *w = Some(debouncer);
+            }
+        }
+        Err(e) => tracing::warn!(error = %e, "size watcher unavailable"),
+    }
+}
+
+/// Watch or stop watching every root
+pub fn set_enabled(enabled: bool) {
+    if ENABLED.swap(enabled, Ordering::Relaxed) == enabled {
+        return;
+    }
+    let roots = ROOTS.lock().map(|r| r.clone()).unwrap_or_default();
+    for root in &roots {
+        register(root, enabled);
+    }
+}
+
+/// Watch `path`; past MAX_ROOTS the longest-watched root is dropped
+pub fn watch(path: &str) {
+    let root = normalize_maybe_missing(Path::new(path));
+    let dropped = {
+        let Ok(mut roots) = ROOTS.lock() else {
+            return;
+        };
+        let known = roots.contains(&root);
+        roots.retain(|r| *r != root);
+        roots.push(root.clone());
+        if !known {
+            register(&root, ENABLED.load(Ordering::Relaxed));
+        }
+        (roots.len() > MAX_ROOTS).then(|| roots.remove(0))
+    };
+    if let Some(dropped) = dropped {
+        register(&dropped, false);
+    }
+}
+
+/// Returns whether `path` was watched
+pub fn unwatch(path: &str) -> bool {
+    let root = normalize_maybe_missing(Path::new(path));
+    let Ok(mut roots) = ROOTS.lock() else {
+        return false;
+    };
+    let before = roots.len();
+    roots.retain(|r| *r != root);
+    let was_watched = roots.len() != before;
+    if was_watched {
+        register(&root, false);
+    }
+    was_watched
+}
+
+/// Add or remove the OS watch for `root`, when watching is on
+fn register(root: &Path, on: bool) {
+    if !ENABLED.load(Ordering::Relaxed) && on {
+        return;
+    }
+    let Ok(mut w) = WATCHER.lock() else {
+        return;
+    };
+    let Some(debouncer) = w.as_mut() else {
+        return;
+    };
+    let done = if on {
+        debouncer.watcher().watch(root, RecursiveMode::Recursive)
+    } else {
+        debouncer.watcher().unwatch(root)
+    };
+    match done {
+        Err(e) if on => {
+            tracing::warn!(path = %root.display(), error = %e, "size watch not set up")
+        }
+        _ => {}
+    }
+}
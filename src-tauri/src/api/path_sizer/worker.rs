@@ -1,47 +1,187 @@
 use std::{
     collections::HashMap,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
     },
     time::{Duration, Instant, SystemTime},
 };
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 
 use crate::api::{
+    fs_meta::{device_id, is_junction},
     path_sizer::{
-        cache::SIZE_CACHE,
+        cache::{
+            forget_path, left_out_for, store_left_out, store_top, top_for, LeftOut, SIZE_CACHE,
+        },
+        concurrency::Limiter,
+        events::{emit_children, Audience, Delivery, ScanEvents},
         jobs,
-        keys::{make_cache_key, make_scan_key, should_skip},
+        keys::{has_path_patterns, make_cache_key, make_child_cache_key, make_scan_key, IgnoreSet},
+        rate::{eta_ms, Throughput},
+        volume,
+        walk::{
+            file_size, precount, walk_tree, ErrorTally, NestedTotals, TopN, WalkFlags, PAUSE_POLL,
+        },
+    },
+    storage::{self, Storage},
+    types::{
+        ApiError, CacheEntry, CacheKey, ChildEvent, ErrorsEvent, Job, JobExtras, ProgressEvent,
+        ScanErrorEvent, SizerOptions, SummaryEvent, TopEntry, TopEvent,
     },
-    types::{CacheEntry, ChildEvent, Job, ProgressEvent, SummaryEvent},
 };
 
+/// What one child's scan task hands back to the root
+struct ChildResult {
+    name: String,
+    /// (bytes, files, dirs)
+    totals: (u64, u64, u64),
+    skipped_mounts: Vec<PathBuf>,
+    estimated: bool,
+    unreadable: u64,
+    junctions: u64,
+    errors: ErrorTally,
+    top: Vec<TopEntry>,
+}
+
+/// How one scan runs, as opposed to what it measures (`SizerOptions`)
+#[derive(Default)]
+pub struct ScanConfig {
+    /// report the N largest files/folders; 0 skips it
+    pub top_n: usize,
+    pub concurrency: Option<usize>,
+    /// ignore completed cache entries and restart a running scan of the key
+    pub force: bool,
+    pub batch_events: bool,
+    /// whole-volume analysis (see `volume.rs`): system entries at the root are
+    /// left out, the root itself isn't cached and a `dir_size:volume` report
+    /// follows the summary
+    pub volume: bool,
+    /// other options the children's results are cached under too, for the
+    /// ones whose walk met nothing these would count differently (mount
+    /// points, for a volume scan's `same_device`)
+    pub also_cache_as: Option<SizerOptions>,
+    /// pre-count the tree for `expected_*` in progress events, giving up
+    /// after this long
+    pub estimate_budget: Option<Duration>,
+    /// yield to visible scans (see `Registry::visible_active`)
+    pub background: bool,
+    /// the windows to send events to; none by default (`dir_size` awaits instead)
+    pub audience: Audience,
+}
+
 pub fn ensure_path_sizer_impl(
     app: AppHandle,
     path: String,
     job_id: String, // kept for compat; UI filters by scan_key
     show_hidden: bool,
     ignores: Vec<String>,
-) -> Result<(), String> {
+    mut opts: SizerOptions,
+    config: ScanConfig,
+) -> Result<String, ApiError> {
+    let ScanConfig {
+        top_n,
+        concurrency,
+        force,
+        batch_events,
+        volume,
+        also_cache_as,
+        estimate_budget,
+        background,
+        audience,
+    } = config;
+    let skip = Arc::new(IgnoreSet::new(show_hidden, &ignores).map_err(ApiError::invalid_input)?);
+    // nested dirs are cached as roots of their own scans, where path
+    // patterns would be anchored elsewhere than in this walk
+    if has_path_patterns(&ignores) {
+        opts.nested_cache_depth = 0;
+    }
+
     // Keys
-    let cache_key = make_cache_key(&path, show_hidden, &ignores);
-    let scan_key = make_scan_key(&path, show_hidden, &ignores);
+    let cache_key = make_cache_key(&path, show_hidden, &ignores, &opts);
+    let mut scan_key = make_scan_key(&path, show_hidden, &ignores, &opts);
+    if volume {
+        scan_key.push_str("|volume");
+    }
+    // this caller's events echo its own ids, in its own format, even once
+    // it joins a job someone else started
+    audience.set_delivery(&Delivery {
+        job_id: job_id.clone(),
+        echo_path: path.clone(),
+        batched: batch_events,
+        top_n,
+    });
+
+    // Beyond std::fs (Android storage grants): no walk, just the total
+    let storage = storage::backend(&app);
+    if !storage.is_native(Path::new(&path)) {
+        size_through_storage(app, audience, storage, path, job_id, scan_key.clone(), skip);
+        return Ok(scan_key);
+    }
+
+    // A missing or unreadable root gets an error instead of a job, and nothing
+    // cached may pass for its size anymore
+    if let Some(e) = root_error(Path::new(&path)) {
+        jobs::JOBS.cancel(&scan_key);
+        forget_path(&cache_key.path);
+        emit_root_error(
+            &app,
+            &audience,
+            &job_id,
+            &scan_key,
+            &path,
+            error_code(&e),
+            e.to_string(),
+        );
+        return Ok(scan_key);
+    }
+
+    // A forced rescan replaces a running job instead of joining it
+    if force {
+        jobs::JOBS.cancel(&scan_key);
+    } else if !volume
+        && serve_from_cache(
+            &app, &audience, &path, &job_id, &scan_key, &cache_key, &ignores, &skip, &opts, top_n,
+        )
+    {
+        // everything was cached: no job needed
+        return Ok(scan_key);
+    }
 
     // Ensure single job per scan_key
     let cancel = Arc::new(AtomicBool::new(false));
-    let inserted = jobs::insert_if_absent(
+    let paused = Arc::new(AtomicBool::new(false));
+    let progress = Arc::new(AtomicU64::new(0));
+    let visible = Arc::new(AtomicBool::new(!background));
+    let inserted = jobs::JOBS.insert_if_absent(
         scan_key.clone(),
         Job {
-            _key: cache_key.clone(),
-            _cancel: cancel.clone(),
+            key: cache_key.clone(),
+            cancel: cancel.clone(),
+            paused: paused.clone(),
+            bytes: progress.clone(),
+            visible: visible.clone(),
+            outcome: tokio::sync::watch::channel(None).0,
+            audience: audience.clone(),
+            extras: JobExtras {
+                top_n,
+                estimate_budget,
+            },
         },
     );
-    if !inserted {
-        // A job for this scan_key is already running; do nothing.
-        return Ok(());
-    }
+    let Some(JobExtras {
+        top_n,
+        estimate_budget,
+    }) = inserted
+    else {
+        // A job for this scan_key is already running; this caller's windows
+        // were added to its audience. Asked for on screen, it's no longer background work.
+        if !background {
+            jobs::JOBS.promote(&scan_key);
+        }
+        return Ok(scan_key);
+    };
 
     // Spawn worker
     tauri::async_runtime::spawn({
@@ -50,129 +190,351 @@ pub fn ensure_path_sizer_impl(
         let job_id = job_id.clone();
         let scan_key = scan_key.clone();
         let ignores = ignores.clone();
+        let skip = skip.clone();
+        let opts = opts.clone();
         let cancel = cancel.clone();
+        let paused = paused.clone();
+        let progress = progress.clone();
+        let visible = visible.clone();
 
         async move {
+            let started = Instant::now();
+            tracing::info!(
+                path = path.as_str(),
+                scan_key = scan_key.as_str(),
+                "scan started"
+            );
             let root = PathBuf::from(&path);
+            let (events, flusher) = ScanEvents::new(&app, &audience, &job_id, &scan_key, &path);
 
             // 1) enumerate immediate children + sum root files
             let mut child_dirs: Vec<String> = Vec::new();
             let mut root_files_total: u64 = 0;
+            let mut root_files_count: u64 = 0;
+            // children on another device are reported at 0 and never walked
+            let mut mount_children: Vec<String> = Vec::new();
+            let root_dev = if opts.same_device {
+                std::fs::metadata(&root)
+                    .ok()
+                    .and_then(|md| device_id(&root, &md))
+            } else {
+                None
+            };
+            // broken links (follow_symlinks), here and in every child walk
+            let mut unreadable: u64 = 0;
+            // junctions left out, reported at 0 like mounts
+            let mut junction_children: Vec<String> = Vec::new();
+            // largest files/folders of the whole scan, merged from every child
+            let mut top = TopN::new(top_n);
+            // unreadable entries, here and in every child walk
+            let mut errors = ErrorTally::default();
 
-            if let Ok(rd) = std::fs::read_dir(&root) {
-                for ent in rd.flatten() {
-                    if cancel.load(Ordering::SeqCst) {
-                        break;
+            // an unreadable root is an error, not an empty folder of 0 B
+            // (checked before spawning too; this catches it vanishing since)
+            let rd = match std::fs::read_dir(&root) {
+                Ok(rd) => rd,
+                Err(e) => {
+                    emit_root_error(
+                        &app,
+                        &audience,
+                        &job_id,
+                        &scan_key,
+                        &path,
+                        error_code(&e),
+                        e.to_string(),
+                    );
+                    jobs::JOBS.set_outcome(
+                        &scan_key,
+                        &cancel,
+                        Err(ApiError::from(e).with_path(&path)),
+                    );
+                    jobs::JOBS.remove(&scan_key, &cancel);
+                    return;
+                }
+            };
+            for ent in rd {
+                if cancel.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(ent) = ent else {
+                    errors.note(None);
+                    continue;
+                };
+                let name = ent.file_name().to_string_lossy().to_string();
+                if volume && volume::skipped_at_root(&root, &name) {
+                    continue;
+                }
+                let is_link = ent.file_type().is_ok_and(|t| t.is_symlink());
+                let junction = is_link && is_junction(&ent.path());
+                if junction && !opts.follow_junctions {
+                    if !skip.is_skipped(Path::new(&name), true) {
+                        junction_children.push(name);
                     }
-                    let name = ent.file_name().to_string_lossy().to_string();
-                    if should_skip(&name, show_hidden, &ignores) {
-                        continue;
+                    continue;
+                }
+                let md = if opts.follow_symlinks || junction {
+                    std::fs::metadata(ent.path())
+                } else {
+                    ent.metadata()
+                };
+                let Ok(md) = md else {
+                    if (opts.follow_symlinks || junction) && is_link {
+                        unreadable += 1;
+                    } else {
+                        errors.note(Some(&ent.path()));
                     }
-                    match ent.metadata() {
-                        Ok(md) if md.is_dir() => child_dirs.push(name),
-                        Ok(md) if md.is_file() => {
-                            root_files_total = root_files_total.saturating_add(md.len());
+                    continue;
+                };
+                if skip.is_skipped(Path::new(&name), md.is_dir()) {
+                    continue;
+                }
+                match md {
+                    md if md.is_dir() => {
+                        let other_device = root_dev.is_some_and(|root_dev| {
+                            device_id(&ent.path(), &md).is_some_and(|d| d != root_dev)
+                        });
+                        if other_device {
+                            mount_children.push(name);
+                        } else {
+                            child_dirs.push(name);
                         }
-                        _ => {}
                     }
+                    md if md.is_file() => {
+                        let size = file_size(&ent.path(), &md, opts.size_mode);
+                        root_files_total = root_files_total.saturating_add(size);
+                        root_files_count += 1;
+                        top.push(size, &ent.path(), false);
+                    }
+                    _ => {}
                 }
             }
 
+            progress.fetch_add(root_files_total, Ordering::Relaxed);
+
             // 2) concurrency & local state
-            let sem = Arc::new(tokio::sync::Semaphore::new(4));
+            let slots = Limiter::new(concurrency, &root);
             let mut tasks = Vec::with_capacity(child_dirs.len());
-            let mut child_totals: HashMap<String, u64> = HashMap::new();
+            // name -> (bytes, files, dirs)
+            let mut child_totals: HashMap<String, (u64, u64, u64)> = HashMap::new();
+            let mut skipped_mounts: Vec<PathBuf> = Vec::new();
+            // some child was cut short by max_depth
+            let mut estimated = false;
 
-            // 3) snapshot cache for quick emits
-            let cache_snapshot = SIZE_CACHE
+            for name in mount_children {
+                events.child(ChildEvent {
+                    job_id: job_id.clone(),
+                    scan_key: scan_key.clone(),
+                    echo_path: path.clone(),
+                    name: name.clone(),
+                    bytes: 0,
+                    items: 0,
+                    files: 0,
+                    dirs: 0,
+                    other_device: true,
+                    junction: false,
+                    errors: 0,
+                });
+                skipped_mounts.push(root.join(&name));
+                child_totals.insert(name, (0, 0, 0));
+            }
+            let mut junctions = junction_children.len() as u64;
+            for name in junction_children {
+                events.child(ChildEvent {
+                    job_id: job_id.clone(),
+                    scan_key: scan_key.clone(),
+                    echo_path: path.clone(),
+                    name,
+                    bytes: 0,
+                    items: 0,
+                    files: 0,
+                    dirs: 0,
+                    other_device: false,
+                    junction: true,
+                    errors: 0,
+                });
+            }
+
+            // 3) snapshot the children's cache entries for quick emits
+            let child_keys: Vec<CacheKey> = child_dirs
+                .iter()
+                .map(|name| {
+                    let child_abs = root.join(name);
+                    make_child_cache_key(
+                        child_abs.to_string_lossy().as_ref(),
+                        show_hidden,
+                        &ignores,
+                        &opts,
+                    )
+                })
+                .collect();
+            // (a forced rescan ignores what's cached and overwrites it)
+            let cache_snapshot: HashMap<CacheKey, CacheEntry> = SIZE_CACHE
                 .lock()
                 .ok()
-                .map(|c| c.clone())
+                .filter(|_| !force)
+                .map(|mut c| {
+                    child_keys
+                        .iter()
+                        .filter_map(|k| c.get(k).map(|e| (k.clone(), e.clone())))
+                        .collect()
+                })
                 .unwrap_or_default();
 
-            for name in child_dirs {
+            // (expected bytes, expected items) for progress events, when asked for
+            // and the pre-count finishes in time
+            let mut expected: (Option<u64>, Option<u64>) = (None, None);
+            // children's own entries the pre-count read, for their walks
+            let mut listings = HashMap::new();
+            if let Some(budget) = estimate_budget {
+                let mut bytes = root_files_total;
+                let mut items = root_files_count + child_totals.len() as u64;
+                let mut uncounted = Vec::new();
+                for (name, key) in child_dirs.iter().zip(&child_keys) {
+                    items += 1;
+                    match cache_snapshot.get(key).filter(|e| e.completed) {
+                        Some(e) => {
+                            bytes += e.bytes;
+                            items += e.items;
+                        }
+                        None => uncounted.push(root.join(name)),
+                    }
+                }
+                let counted = tauri::async_runtime::spawn_blocking({
+                    let (root, skip, opts) = (root.clone(), skip.clone(), opts.clone());
+                    let cancel = cancel.clone();
+                    let deadline = Instant::now() + budget;
+                    move || precount(&uncounted, &root, &skip, &opts, &cancel, deadline)
+                })
+                .await
+                .unwrap_or_default();
+                if let Some((n, b)) = counted.totals {
+                    expected = (Some(bytes + b), Some(items + n));
+                }
+                listings = counted.listings;
+            }
+
+            // bytes actually walked (cache hits left out) and their rate, for ETAs
+            let walked_bytes = Arc::new(AtomicU64::new(0));
+            let scan_rate = Arc::new(Mutex::new(Throughput::new()));
+
+            for (name, child_cachekey) in child_dirs.into_iter().zip(child_keys) {
                 if cancel.load(Ordering::SeqCst) {
                     break;
                 }
 
-                let child_abs = root.join(&name);
-                let child_cachekey =
-                    make_cache_key(child_abs.to_string_lossy().as_ref(), show_hidden, &ignores);
-
                 // Snapshot hit?
                 if let Some(entry) = cache_snapshot.get(&child_cachekey) {
                     if entry.completed {
-                        let _ = app.emit(
-                            "dir_size:child",
-                            ChildEvent {
-                                job_id: job_id.clone(),
-                                scan_key: scan_key.clone(),
-                                name: name.clone(),
-                                bytes: entry.bytes,
-                            },
-                        );
-                        child_totals.insert(name.clone(), entry.bytes);
+                        events.child(ChildEvent {
+                            job_id: job_id.clone(),
+                            scan_key: scan_key.clone(),
+                            echo_path: path.clone(),
+                            name: name.clone(),
+                            bytes: entry.bytes,
+                            items: entry.items,
+                            files: entry.files,
+                            dirs: entry.dirs,
+                            other_device: false,
+                            junction: false,
+                            errors: entry.errors,
+                        });
+                        child_totals.insert(name.clone(), (entry.bytes, entry.files, entry.dirs));
+                        errors.count += entry.errors;
+                        progress.fetch_add(entry.bytes, Ordering::Relaxed);
+                        merge_cached_top(&mut top, &root.join(&name), entry.bytes, &child_cachekey);
                         continue;
                     } else if entry.bytes > 0 {
-                        let _ = app.emit(
-                            "dir_size:progress",
-                            ProgressEvent {
-                                job_id: job_id.clone(),
-                                scan_key: scan_key.clone(),
-                                name: name.clone(),
-                                bytes: entry.bytes,
-                            },
-                        );
+                        events.progress(ProgressEvent {
+                            job_id: job_id.clone(),
+                            scan_key: scan_key.clone(),
+                            echo_path: path.clone(),
+                            name: name.clone(),
+                            bytes: entry.bytes,
+                            items: entry.items,
+                            files: entry.files,
+                            dirs: entry.dirs,
+                            expected_bytes: expected.0,
+                            expected_items: expected.1,
+                            bytes_per_sec: 0,
+                            eta_ms: None,
+                        });
                     }
                 }
 
                 // Live cache check (it may have been updated by another parent)
-                let mut skip_scan = false;
-                let mut cached_bytes = 0u64;
-                if let Ok(cache) = SIZE_CACHE.lock() {
+                let mut cached: Option<CacheEntry> = None;
+                if let Some(mut cache) = SIZE_CACHE.lock().ok().filter(|_| !force) {
                     if let Some(entry) = cache.get(&child_cachekey) {
                         if entry.completed {
-                            skip_scan = true;
-                            cached_bytes = entry.bytes;
+                            cached = Some(entry.clone());
                         } else if entry.bytes > 0 {
-                            let _ = app.emit(
-                                "dir_size:progress",
-                                ProgressEvent {
-                                    job_id: job_id.clone(),
-                                    scan_key: scan_key.clone(),
-                                    name: name.clone(),
-                                    bytes: entry.bytes,
-                                },
-                            );
+                            events.progress(ProgressEvent {
+                                job_id: job_id.clone(),
+                                scan_key: scan_key.clone(),
+                                echo_path: path.clone(),
+                                name: name.clone(),
+                                bytes: entry.bytes,
+                                items: entry.items,
+                                files: entry.files,
+                                dirs: entry.dirs,
+                                expected_bytes: expected.0,
+                                expected_items: expected.1,
+                                bytes_per_sec: 0,
+                                eta_ms: None,
+                            });
                         }
                     }
                 }
-                if skip_scan {
-                    let _ = app.emit(
-                        "dir_size:child",
-                        ChildEvent {
-                            job_id: job_id.clone(),
-                            scan_key: scan_key.clone(),
-                            name: name.clone(),
-                            bytes: cached_bytes,
-                        },
-                    );
-                    child_totals.insert(name.clone(), cached_bytes);
+                if let Some(entry) = cached {
+                    events.child(ChildEvent {
+                        job_id: job_id.clone(),
+                        scan_key: scan_key.clone(),
+                        echo_path: path.clone(),
+                        name: name.clone(),
+                        bytes: entry.bytes,
+                        items: entry.items,
+                        files: entry.files,
+                        dirs: entry.dirs,
+                        other_device: false,
+                        junction: false,
+                        errors: entry.errors,
+                    });
+                    child_totals.insert(name.clone(), (entry.bytes, entry.files, entry.dirs));
+                    errors.count += entry.errors;
+                    progress.fetch_add(entry.bytes, Ordering::Relaxed);
+                    merge_cached_top(&mut top, &root.join(&name), entry.bytes, &child_cachekey);
                     continue;
                 }
 
-                // 4) scan this child with a permit
-                // 3) scan with a permit
-                let permit = sem.clone().acquire_owned().await.unwrap();
+                // 4) scan this child once a slot is free; a paused scan starts none,
+                // nor does a background one while visible scans run
+                let held = || {
+                    paused.load(Ordering::SeqCst)
+                        || (!visible.load(Ordering::SeqCst) && jobs::JOBS.visible_active())
+                };
+                while held() && !cancel.load(Ordering::SeqCst) {
+                    tokio::time::sleep(PAUSE_POLL).await;
+                }
+                let permit = slots.acquire().await;
 
                 // per-task clones (NEVER move the originals)
                 let cancel_t = cancel.clone();
-                let app2 = app.clone();
+                let paused_t = paused.clone();
+                let visible_t = visible.clone();
+                let progress_t = progress.clone();
+                let walked_t = walked_bytes.clone();
+                let scan_rate_t = scan_rate.clone();
+                let events2 = events.clone();
                 let job_id2 = job_id.clone();
                 let scan_key2 = scan_key.clone();
+                let echo2 = path.clone();
                 let root2 = root.clone();
                 let name2 = name.clone();
+                let skip2 = skip.clone();
+                let opts2 = opts.clone();
                 let ignores2 = ignores.clone();
+                let also_cache_as2 = also_cache_as.clone();
+                let listed = listings.remove(&root.join(&name));
 
                 // IMPORTANT: make dedicated copies for each place they’re needed
                 let child_cachekey_for_final = child_cachekey.clone(); // used after .await
@@ -187,145 +549,202 @@ pub fn ensure_path_sizer_impl(
 
                     if cancel_t.load(Ordering::SeqCst) {
                         // we still own name2 here; return it and stop
-                        return (name2, 0u64);
+                        return ChildResult {
+                            name: name2,
+                            totals: (0, 0, 0),
+                            skipped_mounts: Vec::new(),
+                            estimated: false,
+                            unreadable: 0,
+                            junctions: 0,
+                            errors: ErrorTally::default(),
+                            top: Vec::new(),
+                        };
                     }
 
                     let dir_path = root2.join(&name2);
                     let dir_path_for_block = dir_path.clone();
-                    let ignores_for_block = ignores2.clone();
+                    let skip_for_block = skip2.clone();
+                    let root_for_block = root2.clone();
+                    let opts_for_block = opts2.clone();
                     let cancel_block = cancel_t.clone();
+                    let paused_block = paused_t.clone();
+                    let visible_block = visible_t.clone();
 
-                    let app_progress = app2.clone();
+                    // this child's last completed size (a forced or stale rescan)
+                    let prior = SIZE_CACHE.lock().ok().and_then(|c| {
+                        c.peek(&child_cachekey_for_progress)
+                            .filter(|e| e.completed)
+                            .map(|e| e.bytes)
+                    });
+
+                    let events_progress = events2.clone();
                     let job_id_progress = job_id2.clone();
                     let scan_key_progress = scan_key2.clone();
+                    let echo_progress = echo2.clone();
 
                     // heavy work in blocking thread
-                    let (bytes, finished) = tauri::async_runtime::spawn_blocking(move || {
-                        let mut sum: u64 = 0;
-                        let mut last_emit_at = Instant::now()
-                            .checked_sub(Duration::from_millis(200))
-                            .unwrap_or_else(Instant::now);
-                        let mut last_emitted: u64 = 0;
-                        let mut files_since: u32 = 0;
-                        let mut canceled = false;
-
-                        for entry in walkdir::WalkDir::new(&dir_path_for_block)
-                            .follow_links(false)
-                            .into_iter()
-                            .filter_map(|e| e.ok())
-                        {
-                            if cancel_block.load(Ordering::SeqCst) {
-                                canceled = true;
-                                break;
-                            }
-
-                            let fname = entry.file_name().to_string_lossy();
-                            if should_skip(&fname, show_hidden, &ignores_for_block) {
-                                continue;
-                            }
-
-                            if entry.file_type().is_file() {
-                                if let Ok(md) = entry.metadata() {
-                                    sum = sum.saturating_add(md.len());
-                                    files_since += 1;
-
-                                    let due_time =
-                                        last_emit_at.elapsed() >= Duration::from_millis(100);
-                                    let big_jump =
-                                        sum.saturating_sub(last_emitted) >= 8 * 1024 * 1024;
-                                    let many_files = files_since >= 200;
-
-                                    if due_time || big_jump || many_files {
-                                        // write partial into cache so nav-in shows > 0B
-                                        if let Ok(mut cache) = SIZE_CACHE.lock() {
-                                            cache.insert(
-                                                child_cachekey_for_progress.clone(),
-                                                CacheEntry {
-                                                    bytes: sum,
-                                                    items: 0,
-                                                    completed: false,
-                                                    _updated_at: SystemTime::now(),
-                                                },
-                                            );
-                                        }
-                                        let _ = app_progress.emit(
-                                            "dir_size:progress",
-                                            ProgressEvent {
-                                                job_id: job_id_progress.clone(),
-                                                scan_key: scan_key_progress.clone(),
-                                                name: name_for_progress.clone(),
+                    let (walked, child_top) = tauri::async_runtime::spawn_blocking(move || {
+                        let mut child_top = TopN::new(top_n);
+                        // this child's share of the job's running byte count
+                        let mut counted = 0u64;
+                        let mut rate = Throughput::new();
+                        let totals = walk_tree(
+                            &dir_path_for_block,
+                            listed,
+                            &root_for_block,
+                            &skip_for_block,
+                            &opts_for_block,
+                            &WalkFlags {
+                                cancel: &cancel_block,
+                                paused: &paused_block,
+                                visible: Some(&visible_block),
+                            },
+                            &mut child_top,
+                            |sum, files, dirs| {
+                                progress_t.fetch_add(sum - counted, Ordering::Relaxed);
+                                let walked = walked_t.fetch_add(sum - counted, Ordering::Relaxed)
+                                    + (sum - counted);
+                                counted = sum;
+                                let bytes_per_sec = rate.record(sum);
+                                // write partial into cache so nav-in shows > 0B
+                                if cancel_block.load(Ordering::SeqCst) {
+                                    return;
+                                }
+                                // a shallow scan's partials would only shadow a full one's
+                                if opts_for_block.max_depth.is_none() {
+                                    if let Ok(mut cache) = SIZE_CACHE.lock() {
+                                        cache.insert(
+                                            child_cachekey_for_progress.clone(),
+                                            CacheEntry {
                                                 bytes: sum,
+                                                items: files + dirs,
+                                                files,
+                                                dirs,
+                                                completed: false,
+                                                estimated: false,
+                                                errors: 0,
+                                                complete_with_errors: false,
+                                                updated_at: SystemTime::now(),
                                             },
                                         );
-                                        last_emit_at = Instant::now();
-                                        last_emitted = sum;
-                                        files_since = 0;
                                     }
                                 }
-                            }
-                        }
+                                let eta_ms = match prior {
+                                    Some(p) => eta_ms(p.saturating_sub(sum), bytes_per_sec),
+                                    None => expected.0.and_then(|e| {
+                                        let scan_bps = scan_rate_t
+                                            .lock()
+                                            .map(|mut r| r.record(walked))
+                                            .unwrap_or(0);
+                                        let done = progress_t.load(Ordering::Relaxed);
+                                        eta_ms(e.saturating_sub(done), scan_bps)
+                                    }),
+                                };
+                                events_progress.progress(ProgressEvent {
+                                    job_id: job_id_progress.clone(),
+                                    scan_key: scan_key_progress.clone(),
+                                    echo_path: echo_progress.clone(),
+                                    name: name_for_progress.clone(),
+                                    bytes: sum,
+                                    items: files + dirs,
+                                    files,
+                                    dirs,
+                                    expected_bytes: expected.0,
+                                    expected_items: expected.1,
+                                    bytes_per_sec,
+                                    eta_ms,
+                                });
+                            },
+                        );
+                        (totals, child_top.into_entries())
+                    })
+                    .await
+                    .unwrap_or_default();
+                    let (bytes, files, dirs) = (walked.bytes, walked.files, walked.dirs);
+                    let (finished, estimated) = (walked.finished, walked.estimated);
+                    let error_count = walked.errors.count;
+                    store_nested(&walked.nested, finished, show_hidden, &ignores2, &opts2);
+                    // what the walk found is what these options would find too
+                    let also = also_cache_as2.filter(|_| walked.skipped_mounts.is_empty());
+                    if let Some(also) = &also {
+                        store_nested(&walked.nested, finished, show_hidden, &ignores2, also);
+                    }
 
-                        if last_emitted != sum {
-                            if let Ok(mut cache) = SIZE_CACHE.lock() {
-                                cache.insert(
-                                    child_cachekey_for_progress.clone(),
-                                    CacheEntry {
-                                        bytes: sum,
-                                        items: 0,
-                                        completed: false,
-                                        _updated_at: SystemTime::now(),
-                                    },
+                    // final cache write uses the *final* key clone; a cancelled walk
+                    // leaves the last partial (completed: false) as it was, and
+                    // doesn't write over an invalidation that cancelled it.
+                    // A depth-limited walk is stored as an estimate, never completed.
+                    // A child that couldn't be read at all has no size to store.
+                    let unreadable_dir = files + dirs == 0 && error_count > 0;
+                    if finished && !unreadable_dir {
+                        if top_n > 0 {
+                            store_top(child_cachekey_for_final.clone(), child_top.clone());
+                        }
+                        let entry = CacheEntry {
+                            bytes,
+                            items: files + dirs,
+                            files,
+                            dirs,
+                            completed: !estimated,
+                            estimated,
+                            errors: error_count,
+                            complete_with_errors: !estimated && error_count > 0,
+                            updated_at: SystemTime::now(),
+                        };
+                        if let Ok(mut cache) = SIZE_CACHE.lock() {
+                            if let Some(also) = &also {
+                                let key = make_child_cache_key(
+                                    dir_path.to_string_lossy().as_ref(),
+                                    show_hidden,
+                                    &ignores2,
+                                    also,
                                 );
+                                cache.insert(key, entry.clone());
                             }
-                            let _ = app_progress.emit(
-                                "dir_size:progress",
-                                ProgressEvent {
-                                    job_id: job_id_progress,
-                                    scan_key: scan_key_progress,
-                                    name: name_for_progress,
-                                    bytes: sum,
-                                },
-                            );
+                            cache.insert(child_cachekey_for_final, entry);
                         }
-
-                        (sum, !canceled)
-                    })
-                    .await
-                    .unwrap_or((0, false));
-
-                    // final cache write uses the *final* key clone
-                    if let Ok(mut cache) = SIZE_CACHE.lock() {
-                        cache.insert(
-                            child_cachekey_for_final,
-                            CacheEntry {
-                                bytes,
-                                items: 0,
-                                completed: finished,
-                                _updated_at: SystemTime::now(),
-                            },
-                        );
                     }
 
                     // emit final child using the final name clone
-                    let _ = app2.emit(
-                        "dir_size:child",
-                        ChildEvent {
-                            job_id: job_id2,
-                            scan_key: scan_key2,
-                            name: name_for_final_emit.clone(),
-                            bytes,
-                        },
-                    );
+                    events2.child(ChildEvent {
+                        job_id: job_id2,
+                        scan_key: scan_key2,
+                        echo_path: echo2,
+                        name: name_for_final_emit.clone(),
+                        bytes,
+                        items: files + dirs,
+                        files,
+                        dirs,
+                        other_device: false,
+                        junction: false,
+                        errors: error_count,
+                    });
 
                     // return the original name2 (moved here; we don't use it after this)
-                    (name2, bytes)
+                    ChildResult {
+                        name: name2,
+                        totals: (bytes, files, dirs),
+                        skipped_mounts: walked.skipped_mounts,
+                        estimated,
+                        unreadable: walked.unreadable,
+                        junctions: walked.junctions,
+                        errors: walked.errors,
+                        top: child_top,
+                    }
                 }));
             }
 
             // Collect results
             for t in tasks {
-                if let Ok((name, bytes)) = t.await {
-                    child_totals.insert(name, bytes);
+                if let Ok(r) = t.await {
+                    unreadable += r.unreadable;
+                    junctions += r.junctions;
+                    errors.merge(r.errors);
+                    skipped_mounts.extend(r.skipped_mounts);
+                    estimated |= r.estimated;
+                    top.extend(&r.top);
+                    top.push(r.totals.0, &root.join(&r.name), true);
+                    child_totals.insert(r.name, r.totals);
                     if cancel.load(Ordering::SeqCst) {
                         break;
                     }
@@ -334,40 +753,428 @@ pub fn ensure_path_sizer_impl(
 
             // Canceled? drop job & bail
             if cancel.load(Ordering::SeqCst) {
-                jobs::remove(&scan_key);
+                tracing::info!(
+                    path = path.as_str(),
+                    scan_key = scan_key.as_str(),
+                    "scan cancelled"
+                );
+                jobs::JOBS.remove(&scan_key, &cancel);
                 return;
             }
 
+            // children's batched events go out before the summary
+            drop(events);
+            let _ = flusher.await;
+
             // Summary
-            let total: u64 = root_files_total + child_totals.values().copied().sum::<u64>();
+            let total: u64 = root_files_total + child_totals.values().map(|t| t.0).sum::<u64>();
+            // root-level files and each child dir count, plus everything below them
+            let files = root_files_count + child_totals.values().map(|t| t.1).sum::<u64>();
+            let dirs = child_totals.len() as u64 + child_totals.values().map(|t| t.2).sum::<u64>();
 
-            // Cache root
-            if let Ok(mut cache) = SIZE_CACHE.lock() {
+            // Cache root (a volume's total leaves out its system entries)
+            if let Some(mut cache) = SIZE_CACHE.lock().ok().filter(|_| !volume) {
                 cache.insert(
-                    cache_key,
+                    cache_key.clone(),
                     CacheEntry {
                         bytes: total,
-                        items: 0,
-                        completed: true,
-                        _updated_at: SystemTime::now(),
+                        items: files + dirs,
+                        files,
+                        dirs,
+                        completed: !estimated,
+                        estimated,
+                        errors: errors.count,
+                        complete_with_errors: !estimated && errors.count > 0,
+                        updated_at: SystemTime::now(),
+                    },
+                );
+            }
+
+            let skipped_mounts: Vec<String> = skipped_mounts
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect();
+            if !volume && !estimated {
+                store_left_out(
+                    cache_key.clone(),
+                    LeftOut {
+                        skipped_mounts: skipped_mounts.clone(),
+                        unreadable,
+                        junctions,
                     },
                 );
             }
 
+            jobs::JOBS.set_outcome(&scan_key, &cancel, Ok((total, files + dirs)));
+
             // Emit summary
-            let _ = app.emit(
+            let elapsed = started.elapsed();
+            tracing::info!(
+                path = path.as_str(),
+                bytes = total,
+                items = files + dirs,
+                errors = errors.count,
+                elapsed_ms = elapsed.as_millis() as u64,
+                "scan finished"
+            );
+            audience.emit(
+                &app,
                 "dir_size:summary",
                 SummaryEvent {
                     job_id: job_id.clone(),
                     scan_key: scan_key.clone(),
+                    echo_path: path.clone(),
                     bytes: total,
+                    items: files + dirs,
+                    files,
+                    dirs,
+                    skipped_mounts,
+                    estimated,
+                    unreadable,
+                    junctions,
+                    errors: errors.count,
+                    elapsed_ms: elapsed.as_millis() as u64,
+                    bytes_per_sec: (walked_bytes.load(Ordering::Relaxed) as u128 * 1000
+                        / elapsed.as_millis().max(1)) as u64,
                 },
             );
 
+            if errors.count > 0 {
+                audience.emit(
+                    &app,
+                    "dir_size:errors",
+                    ErrorsEvent {
+                        job_id: job_id.clone(),
+                        scan_key: scan_key.clone(),
+                        echo_path: path.clone(),
+                        count: errors.count,
+                        paths: errors
+                            .paths
+                            .iter()
+                            .map(|p| p.to_string_lossy().to_string())
+                            .collect(),
+                    },
+                );
+            }
+
+            if volume {
+                volume::emit_report(&app, &audience, &job_id, &scan_key, &path, total);
+            }
+
+            if top.enabled() {
+                let entries = top.into_entries();
+                if !volume {
+                    store_top(cache_key, entries.clone());
+                }
+                audience.emit(
+                    &app,
+                    "dir_size:top",
+                    TopEvent {
+                        job_id: job_id.clone(),
+                        scan_key: scan_key.clone(),
+                        echo_path: path.clone(),
+                        entries,
+                    },
+                );
+            }
+
             // Done
-            jobs::remove(&scan_key);
+            jobs::JOBS.remove(&scan_key, &cancel);
         }
     });
 
-    Ok(())
+    Ok(scan_key)
+}
+
+/// Why a scan root can't be sized, as (code, message); None when it can
+pub fn root_error(root: &Path) -> Option<std::io::Error> {
+    match std::fs::metadata(root) {
+        Ok(md) if !md.is_dir() => Some(std::io::Error::new(
+            std::io::ErrorKind::NotADirectory,
+            format!("{} is not a directory", root.display()),
+        )),
+        Ok(_) => std::fs::read_dir(root).err(),
+        Err(e) => Some(e),
+    }
+}
+
+/// `code` of a `dir_size:error`
+fn error_code(e: &std::io::Error) -> &'static str {
+    match e.kind() {
+        std::io::ErrorKind::NotFound => "not_found",
+        std::io::ErrorKind::PermissionDenied => "permission_denied",
+        std::io::ErrorKind::NotADirectory => "not_a_directory",
+        _ => "io",
+    }
+}
+
+fn emit_root_error(
+    app: &AppHandle,
+    audience: &Audience,
+    job_id: &str,
+    scan_key: &str,
+    path: &str,
+    code: &str,
+    message: String,
+) {
+    tracing::warn!(path, code, error = message.as_str(), "scan root unreadable");
+    audience.emit(
+        app,
+        "dir_size:error",
+        ScanErrorEvent {
+            job_id: job_id.to_string(),
+            scan_key: scan_key.to_string(),
+            echo_path: path.to_string(),
+            code: code.to_string(),
+            message,
+        },
+    );
+}
+
+/// Size a tree the storage backend reaches but `std::fs` doesn't: one summary
+/// (or error) once it's summed, with no children, progress or caching
+fn size_through_storage(
+    app: AppHandle,
+    audience: Audience,
+    storage: Arc<dyn Storage>,
+    path: String,
+    job_id: String,
+    scan_key: String,
+    skip: Arc<IgnoreSet>,
+) {
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        match storage.tree_size(Path::new(&path), &skip) {
+            Ok((bytes, files, dirs)) => {
+                let elapsed = started.elapsed();
+                audience.emit(
+                    &app,
+                    "dir_size:summary",
+                    SummaryEvent {
+                        job_id,
+                        scan_key,
+                        echo_path: path,
+                        bytes,
+                        items: files + dirs,
+                        files,
+                        dirs,
+                        skipped_mounts: Vec::new(),
+                        estimated: false,
+                        unreadable: 0,
+                        junctions: 0,
+                        errors: 0,
+                        elapsed_ms: elapsed.as_millis() as u64,
+                        bytes_per_sec: (bytes as u128 * 1000 / elapsed.as_millis().max(1)) as u64,
+                    },
+                );
+            }
+            Err(e) => {
+                let code = match e {
+                    ApiError::NotFound(_) => "not_found",
+                    ApiError::PermissionDenied(_) => "permission_denied",
+                    _ => "io",
+                };
+                emit_root_error(
+                    &app,
+                    &audience,
+                    &job_id,
+                    &scan_key,
+                    &path,
+                    code,
+                    e.to_string(),
+                );
+            }
+        }
+    });
+}
+
+/// Cache the dirs a child's walk kept totals for. A cancelled walk's totals
+/// are partial and only fill gaps, never replacing what's cached.
+fn store_nested(
+    nested: &[NestedTotals],
+    finished: bool,
+    show_hidden: bool,
+    ignores: &[String],
+    opts: &SizerOptions,
+) {
+    if nested.is_empty() {
+        return;
+    }
+    let Ok(mut cache) = SIZE_CACHE.lock() else {
+        return;
+    };
+    for n in nested {
+        // a partial total is still shown, but rescanned when that dir is opened
+        let completed = finished && !n.partial;
+        let key = make_cache_key(
+            n.path.to_string_lossy().as_ref(),
+            show_hidden,
+            ignores,
+            opts,
+        );
+        if !finished && cache.peek(&key).is_some() {
+            continue;
+        }
+        cache.insert(
+            key,
+            CacheEntry {
+                bytes: n.bytes,
+                items: n.files + n.dirs,
+                files: n.files,
+                dirs: n.dirs,
+                completed,
+                estimated: false,
+                errors: n.errors,
+                complete_with_errors: completed && n.errors > 0,
+                updated_at: SystemTime::now(),
+            },
+        );
+    }
+}
+
+/// Whether a scan under `opts` can leave out mounts (`same_device`),
+/// junctions (Windows without `follow_junctions`) or links it couldn't follow
+fn may_leave_out(opts: &SizerOptions) -> bool {
+    opts.same_device || opts.follows_links() || (cfg!(windows) && !opts.follow_junctions)
+}
+
+/// A child served from the size cache still feeds the report: itself, plus the
+/// top entries its last scan left behind (when there was one).
+fn merge_cached_top(top: &mut TopN, child: &Path, bytes: u64, key: &CacheKey) {
+    if !top.enabled() {
+        return;
+    }
+    top.push(bytes, child, true);
+    if let Some(entries) = top_for(key) {
+        top.extend(&entries);
+    }
+}
+
+/// Fast path for a root whose own entry and every immediate child's entry are
+/// completed: emit the children and the summary straight from the cache, without
+/// a job or any walking. Returns false (emitting nothing) when anything is
+/// missing or partial, or a wanted top report isn't cached.
+#[allow(clippy::too_many_arguments)]
+fn serve_from_cache(
+    app: &AppHandle,
+    audience: &Audience,
+    path: &str,
+    job_id: &str,
+    scan_key: &str,
+    cache_key: &CacheKey,
+    ignores: &[String],
+    skip: &IgnoreSet,
+    opts: &SizerOptions,
+    top_n: usize,
+) -> bool {
+    let root = Path::new(path);
+    let Ok(rd) = std::fs::read_dir(root) else {
+        return false;
+    };
+    let mut names = Vec::new();
+    for ent in rd {
+        let Ok(ent) = ent else {
+            return false;
+        };
+        let md = if opts.follow_symlinks {
+            std::fs::metadata(ent.path())
+        } else {
+            ent.metadata()
+        };
+        let Ok(md) = md else {
+            continue;
+        };
+        let name = ent.file_name().to_string_lossy().to_string();
+        if md.is_dir() && !skip.is_skipped(Path::new(&name), true) {
+            names.push(name);
+        }
+    }
+
+    // what the scan left out is only known from one run in this session
+    let left_out = match left_out_for(cache_key) {
+        Some(left_out) => left_out,
+        None if may_leave_out(opts) => return false,
+        None => LeftOut::default(),
+    };
+    let top = match top_n {
+        0 => None,
+        _ => match top_for(cache_key) {
+            Some(entries) => Some(entries),
+            None => return false,
+        },
+    };
+
+    let (summary, children) = {
+        let Ok(mut cache) = SIZE_CACHE.lock() else {
+            return false;
+        };
+        let Some(summary) = cache.get(cache_key).filter(|e| e.completed).cloned() else {
+            return false;
+        };
+        let mut children = Vec::with_capacity(names.len());
+        for name in names {
+            let child_key = make_child_cache_key(
+                root.join(&name).to_string_lossy().as_ref(),
+                cache_key.show_hidden,
+                ignores,
+                opts,
+            );
+            match cache.get(&child_key) {
+                Some(e) if e.completed => children.push((name, e.clone())),
+                _ => return false,
+            }
+        }
+        (summary, children)
+    };
+
+    let children = children
+        .into_iter()
+        .map(|(name, e)| ChildEvent {
+            job_id: job_id.to_string(),
+            scan_key: scan_key.to_string(),
+            echo_path: path.to_string(),
+            name,
+            bytes: e.bytes,
+            items: e.items,
+            files: e.files,
+            dirs: e.dirs,
+            other_device: false,
+            junction: false,
+            errors: e.errors,
+        })
+        .collect();
+    emit_children(app, audience, job_id, scan_key, path, children);
+    audience.emit(
+        app,
+        "dir_size:summary",
+        SummaryEvent {
+            job_id: job_id.to_string(),
+            scan_key: scan_key.to_string(),
+            echo_path: path.to_string(),
+            bytes: summary.bytes,
+            items: summary.items,
+            files: summary.files,
+            dirs: summary.dirs,
+            skipped_mounts: left_out.skipped_mounts,
+            estimated: false,
+            unreadable: left_out.unreadable,
+            junctions: left_out.junctions,
+            errors: summary.errors,
+            elapsed_ms: 0,
+            bytes_per_sec: 0,
+        },
+    );
+    if let Some(entries) = top {
+        audience.emit(
+            app,
+            "dir_size:top",
+            TopEvent {
+                job_id: job_id.to_string(),
+                scan_key: scan_key.to_string(),
+                echo_path: path.to_string(),
+                entries,
+            },
+        );
+    }
+    true
 }
@@ -0,0 +1,123 @@
+// src/api/preview.rs
+use crate::api::{
+    sandbox,
+    types::{FileRange, TextPreview},
+};
+use base64::Engine;
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+};
+use tauri::AppHandle;
+
+/// Hard ceiling for a single read, whatever the caller asks for.
+const MAX_READ: u64 = 16 * 1024 * 1024;
+
+fn read_at_most(file: &mut File, max: u64) -> Result<Vec<u8>, String> {
+    let mut buf = Vec::new();
+    file.take(max)
+        .read_to_end(&mut buf)
+        .map_err(|e| e.to_string())?;
+    Ok(buf)
+}
+
+fn decode_utf16(bytes: &[u8], little_endian: bool) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| {
+            if little_endian {
+                u16::from_le_bytes([c[0], c[1]])
+            } else {
+                u16::from_be_bytes([c[0], c[1]])
+            }
+        })
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// UTF-8 that was cut mid-character by the byte limit is still UTF-8.
+fn decode_utf8(bytes: &[u8]) -> Option<String> {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => Some(s.to_string()),
+        Err(e) if e.error_len().is_none() => {
+            Some(String::from_utf8_lossy(&bytes[..e.valid_up_to()]).to_string())
+        }
+        Err(_) => None,
+    }
+}
+
+fn normalize_newlines(s: &str) -> String {
+    s.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Returns (text, encoding, binary)
+fn decode(bytes: &[u8]) -> (String, &'static str, bool) {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        let text = decode_utf8(rest).unwrap_or_else(|| String::from_utf8_lossy(rest).to_string());
+        return (text, "utf-8-bom", false);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return (decode_utf16(rest, true), "utf-16le", false);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return (decode_utf16(rest, false), "utf-16be", false);
+    }
+    if bytes.contains(&0) {
+        return (String::new(), "binary", true);
+    }
+    if let Some(text) = decode_utf8(bytes) {
+        return (text, "utf-8", false);
+    }
+    // latin-1: every byte maps to the code point of the same value
+    (bytes.iter().map(|&b| b as char).collect(), "latin-1", false)
+}
+
+#[tauri::command]
+pub fn read_text_preview(
+    app: AppHandle,
+    path: String,
+    max_bytes: u64,
+) -> Result<TextPreview, String> {
+    sandbox::check(&app, &path)?;
+    let mut file = File::open(&path).map_err(|e| e.to_string())?;
+    let total_size = file.metadata().map_err(|e| e.to_string())?.len();
+    let limit = max_bytes.min(MAX_READ);
+
+    let bytes = read_at_most(&mut file, limit)?;
+    let truncated = (bytes.len() as u64) < total_size;
+    let (text, encoding, binary) = decode(&bytes);
+
+    Ok(TextPreview {
+        text: normalize_newlines(&text),
+        encoding: encoding.to_string(),
+        truncated,
+        binary,
+        total_size,
+    })
+}
+
+#[tauri::command]
+pub fn read_file_range(
+    app: AppHandle,
+    path: String,
+    offset: u64,
+    len: u64,
+) -> Result<FileRange, String> {
+    sandbox::check(&app, &path)?;
+    let mut file = File::open(&path).map_err(|e| e.to_string())?;
+    let total_size = file.metadata().map_err(|e| e.to_string())?.len();
+
+    let offset = offset.min(total_size);
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| e.to_string())?;
+    let bytes = read_at_most(&mut file, len.min(MAX_READ))?;
+    let read = bytes.len() as u64;
+
+    Ok(FileRange {
+        data: base64::engine::general_purpose::STANDARD.encode(&bytes),
+        offset,
+        len: read,
+        total_size,
+        eof: offset + read >= total_size,
+    })
+}
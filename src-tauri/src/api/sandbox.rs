@@ -0,0 +1,82 @@
+// src/api/sandbox.rs
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
+};
+
+use tauri::{AppHandle, Manager, State};
+
+use crate::api::{path_sizer::keys::normalize_maybe_missing, types::ApiError};
+
+/// Canonical roots path-taking commands are confined to; empty = anywhere
+#[derive(Default)]
+pub struct AllowedRoots(Mutex<Vec<PathBuf>>);
+
+/// Where `path` really points: symlinks in the part that exists are resolved,
+/// so a link inside an allowed root can't lead out of it
+fn resolve(path: &Path) -> Option<PathBuf> {
+    let resolved = normalize_maybe_missing(path);
+    // `..` past a missing component can't be resolved; don't guess
+    let lexical = resolved
+        .components()
+        .all(|c| !matches!(c, Component::ParentDir | Component::CurDir));
+    lexical.then_some(resolved)
+}
+
+/// Reject `path` with `forbidden_path` unless it lies under an allowed root.
+/// Does no IO beyond resolving the path.
+pub fn check(app: &AppHandle, path: impl AsRef<Path>) -> Result<(), ApiError> {
+    let path = path.as_ref();
+    let Some(state) = app.try_state::<AllowedRoots>() else {
+        return Ok(());
+    };
+    let roots = state.0.lock().map_err(|e| e.to_string())?;
+    if roots.is_empty() {
+        return Ok(());
+    }
+    match resolve(path) {
+        Some(p) if roots.iter().any(|r| p.starts_with(r)) => Ok(()),
+        _ => Err(ApiError::forbidden(path)),
+    }
+}
+
+/// Whether any roots are set, i.e. whether `check` can reject anything
+pub fn restricted(app: &AppHandle) -> bool {
+    app.try_state::<AllowedRoots>()
+        .is_some_and(|s| s.0.lock().map(|r| !r.is_empty()).unwrap_or(true))
+}
+
+pub fn check_all(app: &AppHandle, paths: &[String]) -> Result<(), ApiError> {
+    paths.iter().try_for_each(|p| check(app, p))
+}
+
+/// Confine path-taking commands to `roots` (canonicalized; each must exist).
+/// An empty list lifts the restriction.
+#[tauri::command]
+pub fn set_allowed_roots(
+    state: State<'_, AllowedRoots>,
+    roots: Vec<String>,
+) -> Result<Vec<String>, ApiError> {
+    let canonical = roots
+        .iter()
+        .map(|r| std::fs::canonicalize(r).map_err(|e| ApiError::from(e).with_path(r)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let shown = canonical
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    tracing::info!(roots = ?canonical, "allowed roots changed");
+    *state.0.lock().map_err(|e| e.to_string())? = canonical;
+    Ok(shown)
+}
+
+#[tauri::command]
+pub fn get_allowed_roots(state: State<'_, AllowedRoots>) -> Result<Vec<String>, ApiError> {
+    Ok(state
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect())
+}
@@ -0,0 +1,113 @@
+// src/api/settings.rs
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::api::{
+    path_sizer::set_sizer_concurrency,
+    types::{ApiError, Settings, SettingsPatch, SizerOptions},
+};
+
+pub struct SettingsState {
+    file: Option<PathBuf>,
+    current: Mutex<Settings>,
+}
+
+/// Load `<app config>/settings.json` into managed state. A missing or broken
+/// file gives the defaults; fields it lacks (added since) take theirs.
+pub fn init(app: &AppHandle) {
+    let file = app
+        .path()
+        .app_config_dir()
+        .ok()
+        .map(|d| d.join("settings.json"));
+    let settings: Settings = file.as_deref().and_then(load_json).unwrap_or_default();
+    set_sizer_concurrency(settings.sizer_concurrency);
+    app.manage(SettingsState {
+        file,
+        current: Mutex::new(settings),
+    });
+}
+
+/// The settings now, or the defaults before `init`
+pub fn current(app: &AppHandle) -> Settings {
+    app.try_state::<SettingsState>()
+        .and_then(|s| s.current.lock().ok().map(|c| c.clone()))
+        .unwrap_or_default()
+}
+
+/// Fill omitted scan parameters from the settings
+pub fn scan_defaults(
+    app: &AppHandle,
+    show_hidden: Option<bool>,
+    ignores: Option<Vec<String>>,
+    options: Option<SizerOptions>,
+) -> (bool, Vec<String>, SizerOptions) {
+    let s = current(app);
+    (
+        show_hidden.unwrap_or(s.show_hidden),
+        ignores.unwrap_or(s.ignores),
+        options.unwrap_or(SizerOptions {
+            size_mode: s.size_mode,
+            ..Default::default()
+        }),
+    )
+}
+
+/// None when `file` is missing or isn't valid JSON for `T`
+pub(crate) fn load_json<T: DeserializeOwned>(file: &Path) -> Option<T> {
+    serde_json::from_slice(&fs::read(file).ok()?).ok()
+}
+
+/// Write `value` to `file` through a temp file, so a crash can't leave half
+pub(crate) fn save_json<T: Serialize>(file: &Path, value: &T) -> Result<(), ApiError> {
+    let data = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).map_err(|e| ApiError::from(e).with_path(dir))?;
+    }
+    let tmp = file.with_extension("json.part");
+    fs::write(&tmp, data).map_err(|e| ApiError::from(e).with_path(&tmp))?;
+    fs::rename(&tmp, file).map_err(|e| ApiError::from(e).with_path(file))
+}
+
+#[tauri::command]
+pub fn get_settings(state: State<'_, SettingsState>) -> Result<Settings, ApiError> {
+    Ok(state.current.lock().map_err(|e| e.to_string())?.clone())
+}
+
+/// Apply the fields present in `partial`, save, and tell every window
+/// (`settings:changed`). Returns the new settings.
+#[tauri::command]
+pub fn update_settings(
+    app: AppHandle,
+    state: State<'_, SettingsState>,
+    partial: SettingsPatch,
+) -> Result<Settings, ApiError> {
+    update(&app, &state, partial)
+}
+
+/// `update_settings` for the backend's own changes
+pub fn update(
+    app: &AppHandle,
+    state: &SettingsState,
+    partial: SettingsPatch,
+) -> Result<Settings, ApiError> {
+    let updated = {
+        let mut current = state.current.lock().map_err(|e| e.to_string())?;
+        let mut next = current.clone();
+        partial.apply_to(&mut next);
+        if let Some(file) = &state.file {
+            save_json(file, &next)?;
+        }
+        *current = next.clone();
+        next
+    };
+    set_sizer_concurrency(updated.sizer_concurrency);
+    let _ = app.emit("settings:changed", updated.clone());
+    Ok(updated)
+}
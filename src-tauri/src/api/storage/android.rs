@@ -0,0 +1,344 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Local, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tauri::{
+    plugin::{PluginHandle, PluginInvokeError},
+    Wry,
+};
+
+use super::{bad_name, desktop::StdFs, saf_path, Storage};
+use crate::api::{
+    path_sizer::keys::IgnoreSet,
+    types::{ApiError, FileEntry},
+};
+
+/// A folder granted through the picker
+struct Tree {
+    uri: String,
+    /// the path its top document stands for
+    root: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Grant {
+    tree_uri: String,
+    tree_doc_id: String,
+}
+
+/// What `StoragePlugin` works on: a document below a granted tree, or with
+/// no tree, a plain file path (app-private storage)
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Doc {
+    tree_uri: Option<String>,
+    /// "/"-joined names below the tree's top document, or the file path
+    path: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SafEntry {
+    name: String,
+    is_dir: bool,
+    size: u64,
+    /// ms since the epoch
+    modified: Option<i64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OnDoc {
+    doc: Doc,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Transfer {
+    src: Doc,
+    dest_dir: Doc,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rename {
+    from: Doc,
+    to: Doc,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RequestAccess<'a> {
+    initial_path: Option<&'a str>,
+}
+
+#[derive(Deserialize, Default)]
+struct Trees {
+    trees: Vec<Grant>,
+}
+
+#[derive(Deserialize)]
+struct Listing {
+    entries: Vec<SafEntry>,
+}
+
+/// Storage Access Framework calls through the `StoragePlugin` Kotlin class,
+/// for paths inside a granted tree; everything else goes to `std::fs`
+pub struct Saf {
+    plugin: PluginHandle<Wry>,
+    trees: Mutex<Vec<Tree>>,
+}
+
+/// The plugin rejects with an `io::ErrorKind`-like code ("not_found", ...)
+fn api_error(e: PluginInvokeError, path: &Path) -> ApiError {
+    let (code, message) = match e {
+        PluginInvokeError::InvokeRejected(r) => (
+            r.code.unwrap_or_default(),
+            r.message.unwrap_or_else(|| "storage call failed".into()),
+        ),
+        other => (String::new(), other.to_string()),
+    };
+    if code == "cancelled" {
+        return ApiError::cancelled().with_path(path);
+    }
+    let kind = match code.as_str() {
+        "not_found" => ErrorKind::NotFound,
+        "permission_denied" => ErrorKind::PermissionDenied,
+        "already_exists" => ErrorKind::AlreadyExists,
+        "unsupported" => ErrorKind::Unsupported,
+        _ => ErrorKind::Other,
+    };
+    ApiError::from(std::io::Error::new(kind, message)).with_path(path)
+}
+
+impl Saf {
+    pub fn new(plugin: PluginHandle<Wry>) -> Self {
+        let saf = Self {
+            plugin,
+            trees: Mutex::new(Vec::new()),
+        };
+        let granted: Trees = saf
+            .plugin
+            .run_mobile_plugin("persistedTrees", ())
+            .unwrap_or_default();
+        for g in granted.trees {
+            saf.add_tree(g);
+        }
+        saf
+    }
+
+    fn add_tree(&self, g: Grant) -> Option<PathBuf> {
+        let root = saf_path::tree_root(&g.tree_doc_id)?;
+        if let Ok(mut trees) = self.trees.lock() {
+            trees.retain(|t| t.root != root);
+            trees.push(Tree {
+                uri: g.tree_uri,
+                root: root.clone(),
+            });
+        }
+        Some(root)
+    }
+
+    /// The innermost granted tree holding `path`
+    fn locate(&self, path: &Path) -> Option<Doc> {
+        let trees = self.trees.lock().ok()?;
+        trees
+            .iter()
+            .filter_map(|t| Some((t, saf_path::relative(&t.root, path)?)))
+            .max_by_key(|(t, _)| t.root.components().count())
+            .map(|(t, rel)| Doc {
+                tree_uri: Some(t.uri.clone()),
+                path: rel,
+            })
+    }
+
+    fn doc(&self, path: &Path) -> Doc {
+        self.locate(path).unwrap_or_else(|| Doc {
+            tree_uri: None,
+            path: path.to_string_lossy().to_string(),
+        })
+    }
+
+    fn call<T: DeserializeOwned>(
+        &self,
+        command: &str,
+        payload: impl Serialize,
+        path: &Path,
+    ) -> Result<T, ApiError> {
+        self.plugin
+            .run_mobile_plugin(command, payload)
+            .map_err(|e| api_error(e, path))
+    }
+
+    /// A command answering with nothing but success
+    fn run(&self, command: &str, payload: impl Serialize, path: &Path) -> Result<(), ApiError> {
+        self.call::<serde_json::Value>(command, payload, path)
+            .map(|_| ())
+    }
+
+    fn list_raw(&self, path: &Path) -> Result<Vec<SafEntry>, ApiError> {
+        let listing: Listing = self.call(
+            "list",
+            OnDoc {
+                doc: self.doc(path),
+            },
+            path,
+        )?;
+        Ok(listing.entries)
+    }
+}
+
+impl Storage for Saf {
+    fn is_native(&self, path: &Path) -> bool {
+        self.locate(path).is_none()
+    }
+
+    fn list(&self, path: &Path, skip: &IgnoreSet) -> Result<Vec<FileEntry>, ApiError> {
+        if self.is_native(path) {
+            return StdFs.list(path, skip);
+        }
+        Ok(self
+            .list_raw(path)?
+            .into_iter()
+            .filter(|e| !skip.is_skipped(Path::new(&e.name), e.is_dir))
+            .map(|e| FileEntry {
+                modified: e
+                    .modified
+                    .and_then(DateTime::<Utc>::from_timestamp_millis)
+                    .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()),
+                size: if e.is_dir { 0 } else { e.size },
+                is_dir: e.is_dir,
+                name: e.name,
+                child_count: None,
+                child_count_approx: false,
+                mode_octal: None,
+                mode_string: None,
+                owner: None,
+                group: None,
+                executable: false,
+            })
+            .collect())
+    }
+
+    fn count_children(&self, dir: &Path, skip: &IgnoreSet, cap: u64) -> Option<(u64, bool)> {
+        if self.is_native(dir) {
+            return StdFs.count_children(dir, skip, cap);
+        }
+        let n = self
+            .list_raw(dir)
+            .ok()?
+            .iter()
+            .filter(|e| !skip.is_skipped(Path::new(&e.name), e.is_dir))
+            .count() as u64;
+        Some((n.min(cap), n >= cap))
+    }
+
+    fn create_dir_all(&self, dir: &Path) -> Result<(), ApiError> {
+        if self.is_native(dir) {
+            return StdFs.create_dir_all(dir);
+        }
+        self.run("mkdirs", OnDoc { doc: self.doc(dir) }, dir)
+    }
+
+    fn copy_into(
+        &self,
+        src: &Path,
+        dest_dir: &Path,
+        preserve_metadata: bool,
+    ) -> Result<(), ApiError> {
+        if self.is_native(src) && self.is_native(dest_dir) {
+            return StdFs.copy_into(src, dest_dir, preserve_metadata);
+        }
+        src.file_name().ok_or_else(|| bad_name(src))?;
+        let transfer = Transfer {
+            src: self.doc(src),
+            dest_dir: self.doc(dest_dir),
+        };
+        self.run("copy", transfer, src)
+            .map_err(|e| e.with_path(dest_dir))
+    }
+
+    fn move_into(
+        &self,
+        src: &Path,
+        dest_dir: &Path,
+        preserve_metadata: bool,
+    ) -> Result<(), ApiError> {
+        if self.is_native(src) && self.is_native(dest_dir) {
+            return StdFs.move_into(src, dest_dir, preserve_metadata);
+        }
+        src.file_name().ok_or_else(|| bad_name(src))?;
+        let transfer = Transfer {
+            src: self.doc(src),
+            dest_dir: self.doc(dest_dir),
+        };
+        self.run("move", transfer, src)
+            .map_err(|e| e.with_path(dest_dir))
+    }
+
+    fn remove(&self, path: &Path) -> Result<(), ApiError> {
+        if self.is_native(path) {
+            return StdFs.remove(path);
+        }
+        self.run(
+            "delete",
+            OnDoc {
+                doc: self.doc(path),
+            },
+            path,
+        )
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), ApiError> {
+        if self.is_native(from) && self.is_native(to) {
+            return StdFs.rename(from, to);
+        }
+        let rename = Rename {
+            from: self.doc(from),
+            to: self.doc(to),
+        };
+        self.run("rename", rename, from)
+            .map_err(|e| e.with_path(to))
+    }
+
+    /// One `list` per folder; unreadable folders count as empty
+    fn tree_size(&self, path: &Path, skip: &IgnoreSet) -> Result<(u64, u64, u64), ApiError> {
+        let (mut bytes, mut files, mut dirs) = (0u64, 0u64, 0u64);
+        let mut pending = vec![self
+            .list_raw(path)?
+            .into_iter()
+            .map(|e| (path.to_path_buf(), e))
+            .collect::<Vec<_>>()];
+        while let Some(level) = pending.pop() {
+            for (parent, e) in level {
+                if skip.is_skipped(Path::new(&e.name), e.is_dir) {
+                    continue;
+                }
+                if e.is_dir {
+                    dirs += 1;
+                    let dir = parent.join(&e.name);
+                    if let Ok(children) = self.list_raw(&dir) {
+                        pending.push(children.into_iter().map(|c| (dir.clone(), c)).collect());
+                    }
+                } else {
+                    files += 1;
+                    bytes = bytes.saturating_add(e.size);
+                }
+            }
+        }
+        Ok((bytes, files, dirs))
+    }
+
+    fn request_access(&self, initial_path: Option<&str>) -> Result<PathBuf, ApiError> {
+        let here = Path::new(initial_path.unwrap_or_default());
+        let grant: Grant = self.call("requestAccess", RequestAccess { initial_path }, here)?;
+        let doc_id = grant.tree_doc_id.clone();
+        self.add_tree(grant)
+            .ok_or_else(|| ApiError::unsupported(format!("can't map folder {} to a path", doc_id)))
+    }
+}
@@ -0,0 +1,233 @@
+use chrono::{DateTime, Local};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use super::{bad_name, Storage};
+use crate::api::{
+    fs_meta::OwnerNames,
+    path_sizer::keys::IgnoreSet,
+    types::{ApiError, FileEntry},
+    xattrs::copy_xattrs,
+};
+
+/// Plain `std::fs`: everything on desktop, app-private storage on Android
+pub struct StdFs;
+
+/// `remove_dir_all` / `remove_file` as fits, the error naming the path
+fn remove(path: &Path) -> Result<(), ApiError> {
+    let res = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    res.map_err(|e| ApiError::from(e).with_path(path))
+}
+
+/// Give everything under `target` the xattrs, permissions and times of its
+/// counterpart under `src`. Best effort: the target fs may not keep them all.
+fn copy_metadata(src: &Path, target: &Path) {
+    // entries before their folder, so a read-only folder is applied last
+    for entry in walkdir::WalkDir::new(src)
+        .follow_links(false)
+        .contents_first(true)
+        .into_iter()
+        .flatten()
+    {
+        let Ok(rel) = entry.path().strip_prefix(src) else {
+            continue;
+        };
+        // a lone file is `src` itself; joining "" would add a trailing slash
+        let to = if rel.as_os_str().is_empty() {
+            target.to_path_buf()
+        } else {
+            target.join(rel)
+        };
+        // before the permissions: a read-only copy would refuse them
+        copy_xattrs(entry.path(), &to);
+        let Ok(md) = entry.metadata() else {
+            continue;
+        };
+        if md.file_type().is_symlink() {
+            continue;
+        }
+        let _ = fs::set_permissions(&to, md.permissions());
+        let mut times = fs::FileTimes::new();
+        if let Ok(t) = md.modified() {
+            times = times.set_modified(t);
+        }
+        if let Ok(t) = md.accessed() {
+            times = times.set_accessed(t);
+        }
+        if let Ok(f) = fs::File::open(&to) {
+            let _ = f.set_times(times);
+        }
+    }
+}
+
+fn copy_one(src: &Path, dest_dir: &Path, preserve_metadata: bool) -> Result<(), ApiError> {
+    let file_name = src.file_name().ok_or_else(|| bad_name(src))?;
+    let target = dest_dir.join(file_name);
+    let failed = |e: ApiError| e.with_path(src).with_path(&target);
+
+    if src.is_dir() {
+        let mut opts = fs_extra::dir::CopyOptions::new();
+        opts.overwrite = true;
+        opts.copy_inside = true;
+        fs_extra::dir::copy(src, &target, &opts).map_err(|e| failed(e.into()))?;
+    } else {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| failed(e.into()))?;
+        }
+        fs::copy(src, &target).map_err(|e| failed(e.into()))?;
+    }
+    if preserve_metadata {
+        copy_metadata(src, &target);
+    }
+    Ok(())
+}
+
+impl Storage for StdFs {
+    fn list(&self, path: &Path, skip: &IgnoreSet) -> Result<Vec<FileEntry>, ApiError> {
+        let mut out = Vec::new();
+        let dir = fs::read_dir(path).map_err(|e| ApiError::from(e).with_path(path))?;
+        // uid/gid -> name lookups cached for the whole listing
+        let mut names = OwnerNames::default();
+
+        for entry in dir {
+            let entry = entry.map_err(|e| ApiError::from(e).with_path(path))?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let is_dir_entry = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if skip.is_skipped(Path::new(&name), is_dir_entry) {
+                continue;
+            }
+            let md = entry
+                .metadata()
+                .map_err(|e| ApiError::from(e).with_path(entry.path()))?;
+
+            let is_dir = md.is_dir();
+            let size = if is_dir { 0 } else { md.len() };
+            let modified = md.modified().ok().map(|t: SystemTime| {
+                let dt: DateTime<Local> = t.into();
+                dt.format("%Y-%m-%d %H:%M").to_string()
+            });
+            let perms = names.describe(&md, &name);
+
+            out.push(FileEntry {
+                name,
+                is_dir,
+                size,
+                modified,
+                child_count: None,
+                child_count_approx: false,
+                mode_octal: perms.mode_octal,
+                mode_string: perms.mode_string,
+                owner: perms.owner,
+                group: perms.group,
+                executable: perms.executable,
+            });
+        }
+
+        Ok(out)
+    }
+
+    fn count_children(&self, dir: &Path, skip: &IgnoreSet, cap: u64) -> Option<(u64, bool)> {
+        let rd = fs::read_dir(dir).ok()?;
+        let mut n = 0u64;
+        for ent in rd.flatten() {
+            let name = ent.file_name().to_string_lossy().to_string();
+            let is_dir = ent.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if skip.is_skipped(Path::new(&name), is_dir) {
+                continue;
+            }
+            n += 1;
+            if n >= cap {
+                return Some((n, true));
+            }
+        }
+        Some((n, false))
+    }
+
+    fn create_dir_all(&self, dir: &Path) -> Result<(), ApiError> {
+        if dir.exists() {
+            return Ok(());
+        }
+        fs::create_dir_all(dir).map_err(|e| ApiError::from(e).with_path(dir))
+    }
+
+    fn copy_into(
+        &self,
+        src: &Path,
+        dest_dir: &Path,
+        preserve_metadata: bool,
+    ) -> Result<(), ApiError> {
+        copy_one(src, dest_dir, preserve_metadata)
+    }
+
+    fn move_into(
+        &self,
+        src: &Path,
+        dest_dir: &Path,
+        preserve_metadata: bool,
+    ) -> Result<(), ApiError> {
+        let file_name = src.file_name().ok_or_else(|| bad_name(src))?;
+        let target: PathBuf = dest_dir.join(file_name);
+        match fs::rename(src, &target) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::debug!(path = %src.display(), error = %e, "rename failed, copying instead");
+                // fallback: copy then delete
+                copy_one(src, dest_dir, preserve_metadata)?;
+                remove(src)
+            }
+        }
+    }
+
+    fn remove(&self, path: &Path) -> Result<(), ApiError> {
+        remove(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), ApiError> {
+        fs::rename(from, to).map_err(|e| ApiError::from(e).with_path(from).with_path(to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn lone_file_keeps_its_metadata() {
+        let root = std::env::temp_dir().join(format!("copy-meta-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let dest = root.join("dest");
+        fs::create_dir_all(&dest).unwrap();
+        let src = root.join("file.txt");
+        fs::write(&src, b"x").unwrap();
+        let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        fs::File::options()
+            .write(true)
+            .open(&src)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&src, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+
+        copy_one(&src, &dest, true).unwrap();
+        let copied = fs::metadata(dest.join("file.txt")).unwrap();
+        assert_eq!(copied.modified().unwrap(), mtime);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(copied.permissions().mode() & 0o777, 0o640);
+        }
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,123 @@
+//! Where listings and file operations happen: `std::fs` on desktop. On
+//! Android, scoped storage blocks `std::fs` outside app-private folders, so
+//! paths inside a folder granted through the system picker
+//! (`request_storage_access`) go through the Storage Access Framework.
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use tauri::{
+    plugin::{Builder, TauriPlugin},
+    AppHandle, Manager, Wry,
+};
+
+use crate::api::{
+    path_sizer::keys::IgnoreSet,
+    types::{ApiError, FileEntry},
+};
+
+#[cfg(target_os = "android")]
+mod android;
+mod desktop;
+#[cfg(any(target_os = "android", test))]
+mod saf_path;
+
+pub trait Storage: Send + Sync {
+    /// Whether `std::fs` reaches `path`. The sizer walks only such paths;
+    /// others are summed through `tree_size`.
+    fn is_native(&self, _path: &Path) -> bool {
+        true
+    }
+
+    /// The entries of `path` minus the skipped ones, unsorted
+    fn list(&self, path: &Path, skip: &IgnoreSet) -> Result<Vec<FileEntry>, ApiError>;
+
+    /// Immediate children of `dir`, stopping at `cap`; None when unreadable
+    fn count_children(&self, dir: &Path, skip: &IgnoreSet, cap: u64) -> Option<(u64, bool)>;
+
+    /// No-op when `dir` exists
+    fn create_dir_all(&self, dir: &Path) -> Result<(), ApiError>;
+
+    /// Copy `src` (recursively) into `dest_dir`, replacing what's there.
+    /// `preserve_metadata`: carry times, permissions and xattrs along.
+    fn copy_into(
+        &self,
+        src: &Path,
+        dest_dir: &Path,
+        preserve_metadata: bool,
+    ) -> Result<(), ApiError>;
+
+    /// `preserve_metadata` as for `copy_into`, for moves that end up copying
+    fn move_into(
+        &self,
+        src: &Path,
+        dest_dir: &Path,
+        preserve_metadata: bool,
+    ) -> Result<(), ApiError>;
+
+    fn remove(&self, path: &Path) -> Result<(), ApiError>;
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), ApiError>;
+
+    /// (bytes, files, dirs) below `path`, for paths that aren't `is_native`
+    fn tree_size(&self, path: &Path, _skip: &IgnoreSet) -> Result<(u64, u64, u64), ApiError> {
+        Err(ApiError::unsupported("no tree sizes here").with_path(path))
+    }
+
+    /// Ask the user for access to a folder; returns it as a path
+    fn request_access(&self, _initial_path: Option<&str>) -> Result<PathBuf, ApiError> {
+        Err(ApiError::unsupported_platform(
+            "storage access is only granted on Android",
+        ))
+    }
+}
+
+struct Backend(Arc<dyn Storage>);
+
+pub fn bad_name(src: &Path) -> ApiError {
+    ApiError::invalid_input("bad source name").with_path(src)
+}
+
+/// Registers the backend for this platform; on Android that needs the
+/// `StoragePlugin` Kotlin class, hence a plugin
+pub fn init() -> TauriPlugin<Wry> {
+    Builder::new("storage")
+        .setup(|app, _api| {
+            #[cfg(target_os = "android")]
+            let backend: Arc<dyn Storage> = Arc::new(android::Saf::new(
+                _api.register_android_plugin("com.ymoravia.nc", "StoragePlugin")?,
+            ));
+            #[cfg(not(target_os = "android"))]
+            let backend: Arc<dyn Storage> = Arc::new(desktop::StdFs);
+            app.manage(Backend(backend));
+            Ok(())
+        })
+        .build()
+}
+
+/// The backend, or plain `std::fs` before `init`
+pub fn backend(app: &AppHandle) -> Arc<dyn Storage> {
+    app.try_state::<Backend>()
+        .map(|b| b.0.clone())
+        .unwrap_or_else(|| Arc::new(desktop::StdFs))
+}
+
+/// Android: let the user pick a folder to grant access to (the picker opens
+/// at `initial_path` when given); the grant survives restarts. Returns the
+/// folder as a path for the other commands. Fails elsewhere: there's nothing
+/// to grant.
+#[tauri::command]
+pub async fn request_storage_access(
+    app: AppHandle,
+    initial_path: Option<String>,
+) -> Result<String, ApiError> {
+    let storage = backend(&app);
+    let granted = tauri::async_runtime::spawn_blocking(move || {
+        storage.request_access(initial_path.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    tracing::info!(path = %granted.display(), "storage access granted");
+    Ok(granted.to_string_lossy().to_string())
+}
@@ -0,0 +1,82 @@
+//! Between the path-like strings the frontend uses and SAF locations: a
+//! granted tree plus the names leading down from its top document.
+use std::path::{Component, Path, PathBuf};
+
+/// Where the external storage provider mounts its volumes
+const PRIMARY: &str = "/storage/emulated/0";
+const VOLUMES: &str = "/storage";
+
+/// The filesystem path of a tree's top document, from its document id
+/// ("primary:Download", "1A2B-3C4D:Music", "raw:/storage/..."). None for
+/// providers whose ids aren't paths (e.g. "msf:42").
+pub fn tree_root(doc_id: &str) -> Option<PathBuf> {
+    let (volume, rel) = doc_id.split_once(':')?;
+    let base = match volume {
+        "raw" => return Some(PathBuf::from(rel)),
+        "primary" => PathBuf::from(PRIMARY),
+        v if is_volume_id(v) => Path::new(VOLUMES).join(v),
+        _ => return None,
+    };
+    Some(
+        rel.split('/')
+            .filter(|s| !s.is_empty())
+            .fold(base, |p, s| p.join(s)),
+    )
+}
+
+/// e.g. "1A2B-3C4D", the id of a removable volume
+fn is_volume_id(v: &str) -> bool {
+    v.len() == 9
+        && v.char_indices().all(|(i, c)| match i {
+            4 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// `path` below `root` as "/"-joined names ("" for the root itself); None
+/// when it's elsewhere or climbs out with ".."
+pub fn relative(root: &Path, path: &Path) -> Option<String> {
+    let rest = path.strip_prefix(root).ok()?;
+    let mut names = Vec::new();
+    for c in rest.components() {
+        match c {
+            Component::Normal(n) => names.push(n.to_string_lossy().to_string()),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(names.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_ids_map_to_paths() {
+        let p = |s: &str| Some(PathBuf::from(s));
+        assert_eq!(tree_root("primary:"), p("/storage/emulated/0"));
+        assert_eq!(
+            tree_root("primary:Download/x"),
+            p("/storage/emulated/0/Download/x")
+        );
+        assert_eq!(tree_root("1A2B-3C4D:Music"), p("/storage/1A2B-3C4D/Music"));
+        assert_eq!(
+            tree_root("raw:/storage/emulated/0/Download"),
+            p("/storage/emulated/0/Download")
+        );
+        assert_eq!(tree_root("msf:42"), None);
+    }
+
+    #[test]
+    fn relative_stays_inside_the_tree() {
+        let root = Path::new("/storage/emulated/0/Download");
+        assert_eq!(relative(root, root), Some(String::new()));
+        assert_eq!(
+            relative(root, &root.join("a/b.txt")),
+            Some("a/b.txt".into())
+        );
+        assert_eq!(relative(root, &root.join("../DCIM")), None);
+        assert_eq!(relative(root, Path::new("/storage/emulated/0/DCIM")), None);
+    }
+}
@@ -0,0 +1,221 @@
+// src/api/thumbnails.rs
+use crate::api::{
+    sandbox,
+    types::{Thumbnail, ThumbnailError},
+};
+use base64::Engine;
+use once_cell::sync::Lazy;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Semaphore;
+
+const MIN_EDGE: u32 = 16;
+const MAX_EDGE: u32 = 1024;
+const CACHE_CAP_BYTES: u64 = 256 * 1024 * 1024;
+const PRUNE_EVERY: u32 = 16;
+
+/// Max number of decodes running at once (scrolling a folder of big photos
+/// would otherwise queue hundreds of blocking tasks).
+static DECODE_SEM: Lazy<Arc<Semaphore>> = Lazy::new(|| Arc::new(Semaphore::new(4)));
+static WRITES_SINCE_PRUNE: AtomicU32 = AtomicU32::new(PRUNE_EVERY);
+/// Numbers temp files, so two generations of the same thumbnail can't share one
+static TMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+fn thumb_dir(app: &AppHandle) -> Result<PathBuf, ThumbnailError> {
+    let base = app.path().app_cache_dir().map_err(|e| ThumbnailError::Io {
+        message: e.to_string(),
+    })?;
+    Ok(base.join("thumbnails"))
+}
+
+/// canonical path + mtime + edge -> stable file name inside the cache dir.
+/// A changed mtime yields a new name; the stale file is left for the pruner.
+/// blake3 rather than std's hasher, whose output may change between releases
+/// and would orphan the whole cache.
+fn thumb_name(canon: &Path, mtime: SystemTime, size: u32) -> String {
+    let nanos = mtime
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let mut h = blake3::Hasher::new();
+    h.update(canon.to_string_lossy().as_bytes());
+    h.update(&nanos.to_le_bytes());
+    h.update(&size.to_le_bytes());
+    format!("{}_{}.png", &h.finalize().to_hex()[..16], size)
+}
+
+fn touch(path: &Path) {
+    if let Ok(f) = fs::File::options().write(true).open(path) {
+        let _ = f.set_modified(SystemTime::now());
+    }
+}
+
+/// Drop least-recently-used thumbnails (by mtime, refreshed on every hit)
+/// until the directory fits under the cap.
+fn prune(dir: &Path, cap: u64) {
+    let Ok(rd) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = rd
+        .flatten()
+        .filter_map(|e| {
+            let md = e.metadata().ok()?;
+            if !md.is_file() {
+                return None;
+            }
+            Some((md.modified().unwrap_or(UNIX_EPOCH), md.len(), e.path()))
+        })
+        .collect();
+
+    let mut total: u64 = files.iter().map(|f| f.1).sum();
+    if total <= cap {
+        return;
+    }
+    files.sort_by_key(|f| f.0);
+    for (_, len, path) in files {
+        if total <= cap {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total = total.saturating_sub(len);
+        }
+    }
+}
+
+fn generate(src: &Path, out: &Path, size: u32) -> Result<(u32, u32), ThumbnailError> {
+    let img = image::ImageReader::open(src)?
+        .with_guessed_format()?
+        .decode()
+        .map_err(|e| ThumbnailError::DecodeFailed {
+            message: e.to_string(),
+        })?;
+    let thumb = if img.width() > size || img.height() > size {
+        img.thumbnail(size, size)
+    } else {
+        img
+    };
+
+    // write to a temp name first so a concurrent reader never sees half a file;
+    // unique per write, since two requests may generate the same thumbnail
+    let tmp = out.with_extension(format!(
+        "{}.{}.part",
+        std::process::id(),
+        TMP_SEQ.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = thumb
+        .save_with_format(&tmp, image::ImageFormat::Png)
+        .map_err(|e| ThumbnailError::Io {
+            message: e.to_string(),
+        })
+        .and_then(|()| Ok(fs::rename(&tmp, out)?));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written?;
+    Ok((thumb.width(), thumb.height()))
+}
+
+fn read_inline(path: &Path) -> Result<String, ThumbnailError> {
+    let bytes = fs::read(path)?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+#[tauri::command]
+pub async fn get_thumbnail(
+    app: AppHandle,
+    path: String,
+    size: u32,
+    inline: Option<bool>,
+) -> Result<Thumbnail, ThumbnailError> {
+    sandbox::check(&app, &path).map_err(|e| ThumbnailError::ForbiddenPath {
+        message: e.to_string(),
+    })?;
+    let size = size.clamp(MIN_EDGE, MAX_EDGE);
+    let inline = inline.unwrap_or(false);
+    let dir = thumb_dir(&app)?;
+
+    let src = fs::canonicalize(&path)?;
+    let mtime = fs::metadata(&src)?.modified()?;
+    let out = dir.join(thumb_name(&src, mtime, size));
+
+    // Cache hit: refresh LRU stamp and return
+    if out.is_file() {
+        touch(&out);
+        if let Ok((width, height)) = image::image_dimensions(&out) {
+            let data = if inline {
+                Some(read_inline(&out)?)
+            } else {
+                None
+            };
+            return Ok(Thumbnail {
+                path: out.to_string_lossy().to_string(),
+                data,
+                width,
+                height,
+            });
+        }
+        // unreadable cache file: fall through and regenerate
+        let _ = fs::remove_file(&out);
+    }
+
+    let _permit = DECODE_SEM
+        .clone()
+        .acquire_owned()
+        .await
+        .map_err(|e| ThumbnailError::Io {
+            message: e.to_string(),
+        })?;
+
+    let out_for_block = out.clone();
+    let (width, height) = tauri::async_runtime::spawn_blocking(move || {
+        fs::create_dir_all(&dir)?;
+        let dims = generate(&src, &out_for_block, size)?;
+        if WRITES_SINCE_PRUNE.fetch_add(1, Ordering::Relaxed) + 1 >= PRUNE_EVERY {
+            WRITES_SINCE_PRUNE.store(0, Ordering::Relaxed);
+            prune(&dir, CACHE_CAP_BYTES);
+        }
+        Ok::<_, ThumbnailError>(dims)
+    })
+    .await
+    .map_err(|e| ThumbnailError::Io {
+        message: e.to_string(),
+    })??;
+
+    let data = if inline {
+        Some(read_inline(&out)?)
+    } else {
+        None
+    };
+    Ok(Thumbnail {
+        path: out.to_string_lossy().to_string(),
+        data,
+        width,
+        height,
+    })
+}
+
+/// Command: wipe the thumbnail cache, returns number of files removed
+#[tauri::command]
+pub fn clear_thumbnail_cache(app: AppHandle) -> Result<u64, ThumbnailError> {
+    let dir = thumb_dir(&app)?;
+    let mut removed = 0u64;
+    let rd = match fs::read_dir(&dir) {
+        Ok(rd) => rd,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    for ent in rd.flatten() {
+        if fs::remove_file(ent.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
@@ -0,0 +1,154 @@
+// src/api/transfer.rs
+use std::{path::Path, sync::Mutex};
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::api::{
+    fs_ops::{copy_paths, move_paths},
+    sandbox, settings,
+    types::{ApiError, ConflictPolicy, PasteResult, PasteStatus, PendingTransfer, TransferMode},
+};
+
+/// The cut/copy register; kept here rather than in a window so any window (or
+/// the same one after a reload) can paste it
+#[derive(Default)]
+pub struct TransferState(Mutex<Option<(Vec<String>, TransferMode)>>);
+
+fn with_status(pending: &Option<(Vec<String>, TransferMode)>) -> Option<PendingTransfer> {
+    pending.as_ref().map(|(paths, mode)| PendingTransfer {
+        paths: paths.clone(),
+        mode: *mode,
+        missing: paths
+            .iter()
+            .filter(|p| !Path::new(p).exists())
+            .cloned()
+            .collect(),
+    })
+}
+
+/// Replace the register and tell every window (`transfer:changed`, with the
+/// register or null), so cut markers stay in sync
+fn replace(
+    app: &AppHandle,
+    state: &TransferState,
+    next: Option<(Vec<String>, TransferMode)>,
+) -> Result<Option<PendingTransfer>, ApiError> {
+    let shown = {
+        let mut current = state.0.lock().map_err(|e| e.to_string())?;
+        *current = next;
+        with_status(&current)
+    };
+    let _ = app.emit("transfer:changed", shown.clone());
+    Ok(shown)
+}
+
+/// `mode`: "copy" | "cut"
+#[tauri::command]
+pub fn set_pending_transfer(
+    app: AppHandle,
+    state: State<'_, TransferState>,
+    paths: Vec<String>,
+    mode: String,
+) -> Result<Option<PendingTransfer>, ApiError> {
+    let mode = match mode.as_str() {
+        "copy" => TransferMode::Copy,
+        "cut" => TransferMode::Cut,
+        _ => return Err(ApiError::invalid_input(format!("unknown mode: {}", mode))),
+    };
+    sandbox::check_all(&app, &paths)?;
+    let next = (!paths.is_empty()).then_some((paths, mode));
+    replace(&app, &state, next)
+}
+
+#[tauri::command]
+pub fn get_pending_transfer(
+    state: State<'_, TransferState>,
+) -> Result<Option<PendingTransfer>, ApiError> {
+    Ok(with_status(&*state.0.lock().map_err(|e| e.to_string())?))
+}
+
+#[tauri::command]
+pub fn clear_pending_transfer(
+    app: AppHandle,
+    state: State<'_, TransferState>,
+) -> Result<(), ApiError> {
+    replace(&app, &state, None).map(|_| ())
+}
+
+/// Copy or move (for a cut) the registered paths into `dest_dir`, one result
+/// per path. Paths gone since they were registered come back `missing`, names
+/// already in `dest_dir` follow the `paste_conflict` setting. A cut register
+/// is cleared once nothing failed.
+#[tauri::command]
+pub fn paste_pending(
+    app: AppHandle,
+    state: State<'_, TransferState>,
+    dest_dir: String,
+    op_id: String,
+) -> Result<Vec<PasteResult>, ApiError> {
+    let Some((paths, mode)) = state.0.lock().map_err(|e| e.to_string())?.clone() else {
+        return Ok(Vec::new());
+    };
+    sandbox::check(&app, &dest_dir)?;
+    let policy = settings::current(&app).paste_conflict;
+    tracing::info!(
+        op_id = op_id.as_str(),
+        count = paths.len(),
+        dest = dest_dir.as_str(),
+        "paste started"
+    );
+
+    let results: Vec<PasteResult> = paths
+        .iter()
+        .map(|path| {
+            let result = |status, error| PasteResult {
+                path: path.clone(),
+                status,
+                error,
+            };
+            let src = Path::new(path);
+            if !src.exists() {
+                return result(PasteStatus::Missing, None);
+            }
+            let target = src.file_name().map(|name| Path::new(&dest_dir).join(name));
+            if target.as_deref().is_some_and(Path::exists) {
+                match policy {
+                    ConflictPolicy::Overwrite => {}
+                    ConflictPolicy::Skip => return result(PasteStatus::Skipped, None),
+                    ConflictPolicy::Fail => {
+                        let error = target.map(ApiError::already_exists);
+                        return result(PasteStatus::Failed, error);
+                    }
+                }
+            }
+            let one = vec![path.clone()];
+            let done = match mode {
+                TransferMode::Copy => copy_paths(app.clone(), one, dest_dir.clone(), None),
+                TransferMode::Cut => move_paths(app.clone(), one, dest_dir.clone(), None),
+            };
+            match done {
+                Ok(()) => result(PasteStatus::Done, None),
+                Err(e) => result(PasteStatus::Failed, Some(e)),
+            }
+        })
+        .collect();
+
+    let failed = results
+        .iter()
+        .filter(|r| r.status == PasteStatus::Failed)
+        .count();
+    tracing::info!(op_id = op_id.as_str(), failed, "paste finished");
+    if mode == TransferMode::Cut && failed == 0 {
+        // unless another cut or copy replaced the register meanwhile
+        let unchanged = state
+            .0
+            .lock()
+            .map_err(|e| e.to_string())?
+            .as_ref()
+            .is_some_and(|(p, m)| *p == paths && *m == mode);
+        if unchanged {
+            replace(&app, &state, None)?;
+        }
+    }
+    Ok(results)
+}
@@ -0,0 +1,461 @@
+// src/api/trash.rs
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use tauri::{AppHandle, Emitter, Window};
+
+use crate::api::{
+    path_sizer::{
+        self,
+        cache::{invalidate_related, Invalidation},
+        keys::normalize_maybe_missing,
+    },
+    sandbox, settings,
+    types::{ApiError, SizerOptions, TrashDoneEvent, TrashInfo, TrashLocation, TrashProgressEvent},
+};
+
+/// How long a `get_trash_info` result is reused
+const INFO_TTL: Duration = Duration::from_secs(10);
+
+static LAST_INFO: Lazy<Mutex<Option<(Instant, TrashInfo)>>> = Lazy::new(|| Mutex::new(None));
+
+/// One trash folder: `items` holds what was trashed; `info` (freedesktop only)
+/// the matching `<name>.trashinfo` records
+struct TrashDir {
+    items: PathBuf,
+    info: Option<PathBuf>,
+}
+
+impl TrashDir {
+    fn freedesktop(root: PathBuf) -> Self {
+        Self {
+            items: root.join("files"),
+            info: Some(root.join("info")),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn uid() -> u32 {
+    // SAFETY: getuid has no preconditions and can't fail
+    unsafe { libc::getuid() }
+}
+
+/// Home trash first, then one per mounted volume that has one
+#[cfg(target_os = "linux")]
+fn trash_dirs(app: &AppHandle) -> Result<Vec<TrashDir>, ApiError> {
+    use tauri::Manager;
+
+    let mut dirs = Vec::new();
+    if let Ok(data) = app.path().data_dir() {
+        dirs.push(TrashDir::freedesktop(data.join("Trash")));
+    }
+    let uid = uid();
+    let mounts = fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+    for mount_point in mount_points(&mounts) {
+        let mount_point = PathBuf::from(mount_point);
+        // $topdir/.Trash/$uid (admin-created, shared) and $topdir/.Trash-$uid
+        for root in [
+            mount_point.join(".Trash").join(uid.to_string()),
+            mount_point.join(format!(".Trash-{}", uid)),
+        ] {
+            if root.join("files").is_dir() {
+                dirs.push(TrashDir::freedesktop(root));
+            }
+        }
+    }
+    Ok(dirs)
+}
+
+/// Home trash first, then `/Volumes/*/.Trashes/$uid`
+#[cfg(target_os = "macos")]
+fn trash_dirs(app: &AppHandle) -> Result<Vec<TrashDir>, ApiError> {
+    use tauri::Manager;
+
+    let mut dirs = Vec::new();
+    if let Ok(home) = app.path().home_dir() {
+        dirs.push(TrashDir {
+            items: home.join(".Trash"),
+            info: None,
+        });
+    }
+    let uid = uid();
+    for volume in fs::read_dir("/Volumes").into_iter().flatten().flatten() {
+        let items = volume.path().join(".Trashes").join(uid.to_string());
+        if items.is_dir() {
+            dirs.push(TrashDir { items, info: None });
+        }
+    }
+    Ok(dirs)
+}
+
+/// The `$Recycle.Bin` of every drive that has one
+#[cfg(windows)]
+fn trash_dirs(_app: &AppHandle) -> Result<Vec<TrashDir>, ApiError> {
+    Ok(recycle_bin::drives()
+        .into_iter()
+        .map(|drive| drive.join("$Recycle.Bin"))
+        .filter(|items| items.is_dir())
+        .map(|items| TrashDir { items, info: None })
+        .collect())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn trash_dirs(_app: &AppHandle) -> Result<Vec<TrashDir>, ApiError> {
+    Err(ApiError::unsupported_platform(
+        "the trash can't be read on this platform",
+    ))
+}
+
+/// The Recycle Bin through the shell, since `$Recycle.Bin` holds renamed
+/// files per user SID rather than the items as the user trashed them
+#[cfg(windows)]
+mod recycle_bin {
+    use std::{
+        io,
+        os::windows::ffi::OsStrExt,
+        path::{Path, PathBuf},
+    };
+
+    use windows_sys::Win32::{
+        Storage::FileSystem::GetLogicalDrives,
+        UI::Shell::{
+            SHEmptyRecycleBinW, SHQueryRecycleBinW, SHERB_NOCONFIRMATION, SHERB_NOPROGRESSUI,
+            SHERB_NOSOUND, SHQUERYRBINFO,
+        },
+    };
+
+    /// `C:\`, `D:\`, ...
+    pub fn drives() -> Vec<PathBuf> {
+        // SAFETY: GetLogicalDrives has no preconditions
+        let mask = unsafe { GetLogicalDrives() };
+        (0..26u8)
+            .filter(|i| mask & (1u32 << i) != 0)
+            .map(|i| PathBuf::from(format!("{}:\\", (b'A' + i) as char)))
+            .collect()
+    }
+
+    fn wide(path: &Path) -> Vec<u16> {
+        path.as_os_str().encode_wide().chain(Some(0)).collect()
+    }
+
+    fn check(hr: i32) -> io::Result<()> {
+        match hr as u32 {
+            0 => Ok(()),
+            // HRESULT_FROM_WIN32
+            code if code & 0xFFFF_0000 == 0x8007_0000 => {
+                Err(io::Error::from_raw_os_error((code & 0xFFFF) as i32))
+            }
+            code => Err(io::Error::other(format!("shell error {:#010x}", code))),
+        }
+    }
+
+    /// (bytes, top-level items) in the bin of `drive`
+    pub fn query(drive: &Path) -> io::Result<(u64, u64)> {
+        let root = wide(drive);
+        let mut info = SHQUERYRBINFO {
+            cbSize: std::mem::size_of::<SHQUERYRBINFO>() as u32,
+            ..Default::default()
+        };
+        // SAFETY: `root` is NUL-terminated and `info` has its size set; both
+        // outlive the call
+        check(unsafe { SHQueryRecycleBinW(root.as_ptr(), &mut info) })?;
+        Ok((info.i64Size.max(0) as u64, info.i64NumItems.max(0) as u64))
+    }
+
+    /// Empty the bin of `drive` without any shell UI
+    pub fn empty(drive: &Path) -> io::Result<()> {
+        // the shell fails on a bin that's already empty
+        if query(drive)?.1 == 0 {
+            return Ok(());
+        }
+        let root = wide(drive);
+        let flags = SHERB_NOCONFIRMATION | SHERB_NOPROGRESSUI | SHERB_NOSOUND;
+        // SAFETY: `root` is NUL-terminated and outlives the call; no owner
+        // window is needed without UI
+        check(unsafe { SHEmptyRecycleBinW(std::ptr::null_mut(), root.as_ptr(), flags) })
+    }
+}
+
+/// Mount points of a `/proc/self/mounts` listing, with its octal escapes
+/// (`\040` for a space, ...) undone
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn mount_points(mounts: &str) -> Vec<String> {
+    mounts
+        .lines()
+        .filter_map(|line| line.split(' ').nth(1))
+        .map(|field| {
+            let mut out = String::with_capacity(field.len());
+            let mut rest = field;
+            while let Some(i) = rest.find('\\') {
+                out.push_str(&rest[..i]);
+                let code = rest.get(i + 1..i + 4);
+                match code.and_then(|c| u8::from_str_radix(c, 8).ok()) {
+                    Some(b) => {
+                        out.push(b as char);
+                        rest = &rest[i + 4..];
+                    }
+                    None => {
+                        out.push('\\');
+                        rest = &rest[i + 1..];
+                    }
+                }
+            }
+            out.push_str(rest);
+            out
+        })
+        .collect()
+}
+
+/// Trash folders that exist and that commands may touch
+fn reachable_dirs(app: &AppHandle) -> Result<Vec<TrashDir>, ApiError> {
+    let mut dirs = trash_dirs(app)?;
+    let mut seen = Vec::new();
+    dirs.retain(|d| {
+        let key = normalize_maybe_missing(&d.items);
+        let keep =
+            d.items.is_dir() && !seen.contains(&key) && sandbox::check(app, &d.items).is_ok();
+        seen.push(key);
+        keep
+    });
+    Ok(dirs)
+}
+
+fn top_level_entries(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .collect()
+}
+
+/// (bytes, top-level items) of one trash folder
+#[cfg(not(windows))]
+async fn measure_dir(
+    app: &AppHandle,
+    dir: &TrashDir,
+    opts: &SizerOptions,
+) -> Result<(u64, u64), ApiError> {
+    let items = top_level_entries(&dir.items).len() as u64;
+    if items == 0 {
+        return Ok((0, 0));
+    }
+    let path = dir.items.to_string_lossy().to_string();
+    let (bytes, ..) =
+        path_sizer::measure(app.clone(), path, true, Vec::new(), opts.clone(), true).await?;
+    Ok((bytes, items))
+}
+
+/// (bytes, top-level items) of one drive's Recycle Bin, as the shell counts them
+#[cfg(windows)]
+async fn measure_dir(
+    _app: &AppHandle,
+    dir: &TrashDir,
+    _opts: &SizerOptions,
+) -> Result<(u64, u64), ApiError> {
+    let drive = dir.items.parent().unwrap_or(&dir.items);
+    recycle_bin::query(drive).map_err(|e| ApiError::from(e).with_path(&dir.items))
+}
+
+/// Size every trash folder with a forced sizer scan, so it can be cancelled
+/// like any other (`cancel_path_sizer` on the folder)
+async fn measure_all(app: &AppHandle) -> Result<TrashInfo, ApiError> {
+    let (_, _, opts) = settings::scan_defaults(app, Some(true), Some(Vec::new()), None);
+    let mut info = TrashInfo {
+        bytes: 0,
+        items: 0,
+        locations: Vec::new(),
+    };
+    for dir in reachable_dirs(app)? {
+        let (bytes, items) = measure_dir(app, &dir, &opts).await?;
+        info.bytes += bytes;
+        info.items += items;
+        info.locations.push(TrashLocation {
+            path: dir.items.to_string_lossy().to_string(),
+            bytes,
+            items,
+        });
+    }
+    Ok(info)
+}
+
+async fn trash_info(app: &AppHandle, fresh: bool) -> Result<TrashInfo, ApiError> {
+    if !fresh {
+        let cached = LAST_INFO.lock().map_err(|e| e.to_string())?.clone();
+        if let Some((_, info)) = cached.filter(|(at, _)| at.elapsed() < INFO_TTL) {
+            return Ok(info);
+        }
+    }
+    let info = measure_all(app).await?;
+    *LAST_INFO.lock().map_err(|e| e.to_string())? = Some((Instant::now(), info.clone()));
+    Ok(info)
+}
+
+/// Bytes and top-level items in the trash, in total and per trash folder
+/// (the home trash, then e.g. `.Trash-1000` on mounted drives). Reuses a result
+/// up to 10s old unless `fresh`.
+#[tauri::command]
+pub async fn get_trash_info(app: AppHandle, fresh: Option<bool>) -> Result<TrashInfo, ApiError> {
+    trash_info(&app, fresh.unwrap_or(false)).await
+}
+
+/// What `purge` removes one by one: each top-level entry, or on Windows each
+/// drive's whole Recycle Bin
+#[cfg(not(windows))]
+fn purge_entries(dirs: &[TrashDir]) -> Vec<(&TrashDir, PathBuf)> {
+    dirs.iter()
+        .flat_map(|d| top_level_entries(&d.items).into_iter().map(move |p| (d, p)))
+        .collect()
+}
+
+#[cfg(windows)]
+fn purge_entries(dirs: &[TrashDir]) -> Vec<(&TrashDir, PathBuf)> {
+    dirs.iter().map(|d| (d, d.items.clone())).collect()
+}
+
+#[cfg(not(windows))]
+fn remove_entry(path: &Path) -> std::io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// `path` is a drive's `$Recycle.Bin`
+#[cfg(windows)]
+fn remove_entry(path: &Path) -> std::io::Result<()> {
+    recycle_bin::empty(path.parent().unwrap_or(path))
+}
+
+/// Progress and the result go to the `label` window only
+fn purge(app: &AppHandle, label: &str, dirs: &[TrashDir], op_id: &str) -> Result<(), ApiError> {
+    let entries = purge_entries(dirs);
+    let total = entries.len() as u64;
+    let mut done = 0u64;
+    let mut failed: Vec<ApiError> = Vec::new();
+    let mut last_emit = Instant::now();
+
+    for (dir, path) in entries {
+        match remove_entry(&path) {
+            Ok(()) => {
+                if let (Some(info), Some(name)) = (&dir.info, path.file_name()) {
+                    let mut record = name.to_os_string();
+                    record.push(".trashinfo");
+                    let _ = fs::remove_file(info.join(record));
+                }
+            }
+            Err(e) => {
+                tracing::warn!(
+                    path = %path.display(),
+                    op_id,
+                    error = %e,
+                    "trash entry not removed"
+                );
+                failed.push(ApiError::from(e).with_path(&path));
+            }
+        }
+        done += 1;
+        if last_emit.elapsed() >= Duration::from_millis(100) || done == total {
+            let _ = app.emit_to(
+                label,
+                "trash:progress",
+                TrashProgressEvent {
+                    op_id: op_id.to_string(),
+                    done,
+                    total,
+                    current: path.to_string_lossy().to_string(),
+                },
+            );
+            last_emit = Instant::now();
+        }
+    }
+
+    for dir in dirs {
+        // freedesktop's cache of trashed folder sizes; stale now
+        if let Some(root) = dir.info.as_deref().and_then(Path::parent) {
+            let _ = fs::remove_file(root.join("directorysizes"));
+        }
+    }
+    let _ = app.emit_to(
+        label,
+        "trash:done",
+        TrashDoneEvent {
+            op_id: op_id.to_string(),
+            removed: done - failed.len() as u64,
+            failed: failed.len() as u64,
+        },
+    );
+    tracing::info!(
+        op_id,
+        removed = done - failed.len() as u64,
+        failed = failed.len() as u64,
+        "trash emptied"
+    );
+    match failed.into_iter().next() {
+        Some(first) => Err(first),
+        None => Ok(()),
+    }
+}
+
+/// Permanently delete everything in the trash. With `preview`, nothing is
+/// deleted: resolves with what would go, for a confirmation dialog. Otherwise
+/// emits `trash:progress` / `trash:done` keyed by op_id to the calling window
+/// and resolves with what was in the trash, or with the first entry that
+/// couldn't be removed (the rest are still attempted).
+///
+/// On Windows each drive's Recycle Bin is emptied through the shell in one
+/// go, so progress and the done counts are per drive rather than per item.
+///
+/// There is no undo journal in the backend yet, so nothing else needs to
+/// forget the purged items.
+#[tauri::command]
+pub async fn empty_trash(
+    app: AppHandle,
+    window: Window,
+    op_id: String,
+    preview: Option<bool>,
+) -> Result<TrashInfo, ApiError> {
+    let info = trash_info(&app, true).await?;
+    if preview.unwrap_or(false) {
+        return Ok(info);
+    }
+
+    let dirs = reachable_dirs(&app)?;
+    let roots: Vec<PathBuf> = dirs
+        .iter()
+        .map(|d| normalize_maybe_missing(&d.items))
+        .collect();
+    let purged = {
+        let app = app.clone();
+        let label = window.label().to_string();
+        tauri::async_runtime::spawn_blocking(move || purge(&app, &label, &dirs, &op_id))
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    *LAST_INFO.lock().map_err(|e| e.to_string())? = None;
+    invalidate_related(&app, &roots, Invalidation::Drop);
+    purged.map(|()| info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mount_points_undo_escapes() {
+        let mounts = "/dev/sda1 / ext4 rw 0 0\n\
+                      /dev/sdb1 /media/me/My\\040Disk vfat rw 0 0\n\
+                      tmpfs /run/back\\134slash tmpfs rw 0 0\n";
+        assert_eq!(
+            mount_points(mounts),
+            ["/", "/media/me/My Disk", "/run/back\\slash"]
+        );
+    }
+}
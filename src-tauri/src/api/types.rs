@@ -1,47 +1,299 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use crate::api::path_sizer::events::Audience;
 use std::{
+    collections::{BTreeMap, HashMap},
     path::PathBuf,
-    sync::{atomic::AtomicBool, Arc},
-    time::SystemTime,
+    sync::{
+        atomic::{AtomicBool, AtomicU64},
+        Arc,
+    },
+    time::{Duration, SystemTime},
 };
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct FileEntry {
     pub name: String,
     pub is_dir: bool,
     pub size: u64,
     pub modified: Option<String>,
+    /// Immediate children of a directory (only when requested)
+    pub child_count: Option<u64>,
+    /// true when counting stopped at the cap, i.e. the real count is >= child_count
+    pub child_count_approx: bool,
+    /// Permission bits (unix only)
+    pub mode_octal: Option<u32>,
+    /// `ls -l` style rendering, e.g. "drwxr-xr-x" (unix only)
+    pub mode_string: Option<String>,
+    /// Owner/group names, or the numeric id when it can't be resolved (unix only)
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    pub executable: bool,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ListSort {
+    pub key: String,
+    pub dir: String,
+    pub dirs_first: bool,
+}
+
+#[derive(Serialize)]
+pub struct DirListing {
+    pub entries: Vec<FileEntry>,
+    /// Number of entries matching the filters, before paging
+    pub total: usize,
+    pub offset: usize,
+    pub sort: ListSort,
+    /// Set when the listing is served from (or created as) a snapshot
+    pub snapshot_id: Option<String>,
+}
+
+/// Cached size as returned to the UI
+#[derive(Serialize, Clone)]
+pub struct CachedSize {
+    pub bytes: u64,
+    pub items: u64,
+    pub completed: bool,
+    /// time since the entry was last written
+    pub age_ms: u64,
+    /// taken from the same path scanned with other show_hidden/ignores options
+    pub approximate: bool,
+    /// written by a depth-limited scan
+    pub estimated: bool,
+    pub errors: u64,
+    /// finished, but some entries couldn't be read (show with a warning)
+    pub complete_with_errors: bool,
+}
+
+#[derive(Serialize)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub partial_entries: usize,
+    pub max_entries: usize,
+    /// rough: keys + entries + path strings, not allocator overhead
+    pub approx_bytes: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+/// (bytes, items, completed), the pre-v2 positional shape
+pub type LegacyCachedSize = (u64, u64, bool);
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum CachedSizeReply {
+    Legacy(LegacyCachedSize),
+    Full(CachedSize),
+}
+
+impl CachedSizeReply {
+    /// Final for these exact options (not partial, not borrowed from others)
+    pub fn is_settled(&self) -> bool {
+        match self {
+            CachedSizeReply::Legacy((_, _, completed)) => *completed,
+            CachedSizeReply::Full(c) => c.completed && !c.approximate,
+        }
+    }
+}
+
+/// get_or_ensure_sizes: the cached values plus the scan filling in the rest
+#[derive(Serialize)]
+pub struct SizesReply {
+    pub sizes: Vec<Option<CachedSizeReply>>,
+    /// scan to listen on; None when every path was already settled
+    pub scan_key: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct CacheEntry {
     pub bytes: u64,
+    /// files + dirs below the entry (the entry itself not counted)
     pub items: u64,
+    pub files: u64,
+    pub dirs: u64,
     pub completed: bool,
-    pub _updated_at: SystemTime,
+    /// from a depth-limited scan; never completed, a full scan replaces it
+    pub estimated: bool,
+    /// entries below that couldn't be read, so `bytes` may be short
+    pub errors: u64,
+    /// finished, but with `errors` > 0
+    pub complete_with_errors: bool,
+    pub updated_at: SystemTime,
 }
 
 #[derive(Serialize, Clone)]
 pub struct ChildEvent {
     pub job_id: String,
     pub scan_key: String,
+    /// the path as the call that started the scan spelled it (scan_key
+    /// carries it normalized)
+    pub echo_path: String,
     pub name: String,
     pub bytes: u64,
+    pub items: u64,
+    pub files: u64,
+    pub dirs: u64,
+    /// child is a mount point left out by `same_device` (bytes are 0)
+    pub other_device: bool,
+    /// child is a directory junction left out (see `follow_junctions`; bytes are 0)
+    pub junction: bool,
+    /// entries below the child that couldn't be read
+    pub errors: u64,
 }
 
 #[derive(Serialize, Clone)]
 pub struct SummaryEvent {
     pub job_id: String,
     pub scan_key: String,
+    pub echo_path: String,
     pub bytes: u64,
+    pub items: u64,
+    pub files: u64,
+    pub dirs: u64,
+    /// mount points this scan walked into and left out (`same_device`)
+    pub skipped_mounts: Vec<String>,
+    /// depth-limited scan: `bytes` is a lower bound
+    pub estimated: bool,
+    /// broken symlinks skipped with `follow_symlinks`
+    pub unreadable: u64,
+    /// directory junctions left out (Windows, without `follow_junctions`)
+    pub junctions: u64,
+    /// entries that couldn't be read; details follow in `dir_size:errors`
+    pub errors: u64,
+    pub elapsed_ms: u64,
+    /// average over the scan, counting bytes walked (not taken from the cache)
+    pub bytes_per_sec: u64,
+}
+
+/// First few unreadable paths of a finished scan, for a tooltip
+#[derive(Serialize, Clone)]
+pub struct ErrorsEvent {
+    pub job_id: String,
+    pub scan_key: String,
+    pub echo_path: String,
+    pub count: u64,
+    pub paths: Vec<String>,
+}
+
+/// The scan root itself couldn't be read; no summary follows
+#[derive(Serialize, Clone)]
+pub struct ScanErrorEvent {
+    pub job_id: String,
+    pub scan_key: String,
+    pub echo_path: String,
+    /// "not_found" | "permission_denied" | "not_a_directory" | "io"
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct TopEntry {
+    pub path: String,
+    pub bytes: u64,
+    pub is_dir: bool,
+}
+
+/// Largest files and folders of a finished scan, emitted after its summary
+#[derive(Serialize, Clone)]
+pub struct TopEvent {
+    pub job_id: String,
+    pub scan_key: String,
+    pub echo_path: String,
+    pub entries: Vec<TopEntry>,
 }
 
 #[derive(Serialize, Clone)]
 pub struct ProgressEvent {
     pub job_id: String,
     pub scan_key: String,
+    pub echo_path: String,
     pub bytes: u64,
     pub name: String,
+    pub items: u64,
+    pub files: u64,
+    pub dirs: u64,
+    /// whole scan's expected total from the pre-count (`estimate_total`);
+    /// None without one or when it ran out of time
+    pub expected_bytes: Option<u64>,
+    pub expected_items: Option<u64>,
+    /// this child's walk rate over the last few seconds; 0 while unknown
+    pub bytes_per_sec: u64,
+    /// time left for this child (from its last completed size) or else the
+    /// whole scan (from `expected_bytes`); None without either or a rate
+    pub eta_ms: Option<u64>,
+}
+
+/// One child in a `dir_size:children` batch
+#[derive(Serialize, Clone)]
+pub struct ChildSize {
+    pub name: String,
+    pub bytes: u64,
+    pub items: u64,
+    pub files: u64,
+    pub dirs: u64,
+    pub other_device: bool,
+    pub junction: bool,
+    pub errors: u64,
+}
+
+/// One child's latest partial in a `dir_size:progress_batch`
+#[derive(Serialize, Clone)]
+pub struct ProgressSize {
+    pub name: String,
+    pub bytes: u64,
+    pub items: u64,
+    pub files: u64,
+    pub dirs: u64,
+    pub bytes_per_sec: u64,
+    pub eta_ms: Option<u64>,
+}
+
+/// Batched `dir_size:child` events
+#[derive(Serialize, Clone)]
+pub struct ChildrenEvent {
+    pub job_id: String,
+    pub scan_key: String,
+    pub echo_path: String,
+    pub children: Vec<ChildSize>,
+}
+
+/// Batched `dir_size:progress` events, one entry per child
+#[derive(Serialize, Clone)]
+pub struct ProgressBatchEvent {
+    pub job_id: String,
+    pub scan_key: String,
+    pub echo_path: String,
+    pub progress: Vec<ProgressSize>,
+    pub expected_bytes: Option<u64>,
+    pub expected_items: Option<u64>,
+}
+
+/// Whole-volume scan, after its summary: the filesystem's used space next to
+/// what the scan found; `other_bytes` is the part it couldn't attribute
+#[derive(Serialize, Clone)]
+pub struct VolumeEvent {
+    pub job_id: String,
+    pub scan_key: String,
+    pub echo_path: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub scanned_bytes: u64,
+    pub other_bytes: u64,
+}
+
+/// Paths whose cached sizes were dropped; the UI re-requests what is visible
+#[derive(Serialize, Clone)]
+pub struct InvalidatedEvent {
+    pub paths: Vec<String>,
+}
+
+/// Paths whose cached sizes changed on disk since they were written (kept as
+/// `completed: false` hints); see `auto_invalidate`
+#[derive(Serialize, Clone)]
+pub struct StaleEvent {
+    pub paths: Vec<String>,
 }
 
 #[derive(Hash, Eq, PartialEq, Clone)]
@@ -49,9 +301,707 @@ pub struct CacheKey {
     pub path: PathBuf,
     pub show_hidden: bool,
     pub ignores_sig: String,
+    /// non-default SizerOptions, empty for the defaults
+    pub opts_sig: String,
+}
+
+/// Extra knobs for sizing scans; all of them take part in the cache key
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct SizerOptions {
+    /// count a file with several hard links once per scanned folder
+    pub dedupe_hardlinks: bool,
+    pub size_mode: SizeMode,
+    /// don't descend into directories on another device (mounts, shares)
+    pub same_device: bool,
+    /// quick estimate: walk at most this many levels below the scan root
+    pub max_depth: Option<u32>,
+    /// size symlinked files and dirs as if they were here (each target once)
+    pub follow_symlinks: bool,
+    /// walk into Windows directory junctions and folder mount points, which
+    /// are otherwise reported at 0 (each target once)
+    pub follow_junctions: bool,
+    /// levels below each scanned child whose dirs get cache entries of their
+    /// own, so opening them is instant (not in the key; 0 turns it off)
+    pub nested_cache_depth: u32,
+}
+
+impl Default for SizerOptions {
+    fn default() -> Self {
+        Self {
+            dedupe_hardlinks: true,
+            size_mode: SizeMode::Apparent,
+            same_device: false,
+            max_depth: None,
+            follow_symlinks: false,
+            follow_junctions: false,
+            nested_cache_depth: 2,
+        }
+    }
+}
+
+impl SizerOptions {
+    /// Some kind of link is followed, so a real file or dir can be met twice
+    pub fn follows_links(&self) -> bool {
+        self.follow_symlinks || self.follow_junctions
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeMode {
+    /// logical length (`len()`)
+    Apparent,
+    /// space allocated on disk (blocks, compression, sparseness)
+    DiskUsage,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// replace the existing item
+    Overwrite,
+    /// leave both as they are and report the item as skipped
+    Skip,
+    /// report the item as failed with `already_exists`
+    Fail,
+}
+
+/// Backend settings (`<app config>/settings.json`); commands use them for
+/// parameters the caller leaves out
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    /// ignore patterns for listings and scans
+    pub ignores: Vec<String>,
+    pub show_hidden: bool,
+    /// child folders walked at once per scan (1-32; 0 = pick per volume)
+    pub sizer_concurrency: usize,
+    pub size_mode: SizeMode,
+    pub confirm_delete: bool,
+    /// delete moves to the trash unless asked to delete permanently
+    pub delete_to_trash: bool,
+    /// `record_visit` keeps a history of visited folders
+    pub track_history: bool,
+    /// page zoom of every window, restored on startup
+    pub zoom: f64,
+    /// allow devtools in release builds that have them
+    pub enable_devtools: bool,
+    /// what `paste_pending` does when the destination already has the name
+    pub paste_conflict: ConflictPolicy,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ignores: Vec::new(),
+            // listings were unfiltered before there were settings
+            show_hidden: true,
+            sizer_concurrency: 0,
+            size_mode: SizeMode::Apparent,
+            confirm_delete: true,
+            delete_to_trash: false,
+            track_history: true,
+            zoom: 1.0,
+            enable_devtools: false,
+            // copy_paths / move_paths have always replaced what was there
+            paste_conflict: ConflictPolicy::Overwrite,
+        }
+    }
+}
+
+/// What the `window_ctl` commands leave the calling window in
+#[derive(Serialize)]
+pub struct WindowState {
+    pub zoom: f64,
+    pub always_on_top: bool,
+    pub fullscreen: bool,
+    pub compact: bool,
+    pub decorated: bool,
+    /// logical size
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A sidebar favorite, kept in `<app data>/bookmarks.json`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Bookmark {
+    pub id: String,
+    /// as the user entered it, for display
+    pub path: String,
+    /// resolved form; one bookmark per canonical path
+    pub canonical: String,
+    pub label: String,
+    /// "home" | "drive" | "folder" | "file"
+    pub icon: String,
+    /// RFC 3339
+    pub created_at: String,
+    /// the path no longer exists (checked on every read)
+    #[serde(default, skip_deserializing)]
+    pub missing: bool,
+}
+
+/// A visited location, kept in `<app data>/history.json`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LocationVisit {
+    pub path: String,
+    pub visits: u64,
+    /// ms since the epoch
+    pub last_visit: i64,
+    /// frecency: visits, weighted down as the last one ages
+    #[serde(default, skip_deserializing)]
+    pub score: f64,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferMode {
+    Copy,
+    Cut,
+}
+
+/// The cut/copy register shared by every window
+#[derive(Serialize, Clone)]
+pub struct PendingTransfer {
+    pub paths: Vec<String>,
+    pub mode: TransferMode,
+    /// registered paths that no longer exist (checked on every read)
+    pub missing: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteStatus {
+    Done,
+    /// gone since it was registered
+    Missing,
+    /// the destination had the name and the policy is `skip`
+    Skipped,
+    Failed,
+}
+
+/// What `paste_pending` did with one registered path
+#[derive(Serialize, Clone)]
+pub struct PasteResult {
+    pub path: String,
+    pub status: PasteStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiError>,
+}
+
+/// `update_settings`: only the fields present change
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct SettingsPatch {
+    pub ignores: Option<Vec<String>>,
+    pub show_hidden: Option<bool>,
+    pub sizer_concurrency: Option<usize>,
+    pub size_mode: Option<SizeMode>,
+    pub confirm_delete: Option<bool>,
+    pub delete_to_trash: Option<bool>,
+    pub track_history: Option<bool>,
+    pub zoom: Option<f64>,
+    pub enable_devtools: Option<bool>,
+    pub paste_conflict: Option<ConflictPolicy>,
+}
+
+impl SettingsPatch {
+    pub fn apply_to(self, s: &mut Settings) {
+        if let Some(v) = self.ignores {
+            s.ignores = v;
+        }
+        if let Some(v) = self.show_hidden {
+            s.show_hidden = v;
+        }
+        if let Some(v) = self.sizer_concurrency {
+            s.sizer_concurrency = v;
+        }
+        if let Some(v) = self.size_mode {
+            s.size_mode = v;
+        }
+        if let Some(v) = self.confirm_delete {
+            s.confirm_delete = v;
+        }
+        if let Some(v) = self.delete_to_trash {
+            s.delete_to_trash = v;
+        }
+        if let Some(v) = self.track_history {
+            s.track_history = v;
+        }
+        if let Some(v) = self.zoom {
+            s.zoom = v;
+        }
+        if let Some(v) = self.enable_devtools {
+            s.enable_devtools = v;
+        }
+        if let Some(v) = self.paste_conflict {
+            s.paste_conflict = v;
+        }
+    }
+}
+
+/// How a job ended: (bytes, items) of its root, or why there are none
+pub type ScanOutcome = Result<(u64, u64), ApiError>;
+
+/// What a job works out besides the sizes. A caller asking for more than a
+/// running job does replaces it with one doing both (see `Registry::insert_if_absent`).
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct JobExtras {
+    /// entries kept for the top report; 0 keeps none
+    pub top_n: usize,
+    /// pre-count budget for `expected_*`
+    pub estimate_budget: Option<Duration>,
+}
+
+impl JobExtras {
+    pub fn covers(&self, other: &JobExtras) -> bool {
+        self.top_n >= other.top_n
+            && (self.estimate_budget.is_some() || other.estimate_budget.is_none())
+    }
+
+    pub fn merge(self, other: JobExtras) -> JobExtras {
+        JobExtras {
+            top_n: self.top_n.max(other.top_n),
+            estimate_budget: self.estimate_budget.max(other.estimate_budget),
+        }
+    }
 }
 
 pub struct Job {
-    pub _key: CacheKey,
-    pub _cancel: Arc<AtomicBool>,
+    pub key: CacheKey,
+    pub cancel: Arc<AtomicBool>,
+    pub paused: Arc<AtomicBool>,
+    /// bytes counted so far, for `list_active_scans`
+    pub bytes: Arc<AtomicU64>,
+    /// visible priority (the folder on screen); background jobs yield to these
+    pub visible: Arc<AtomicBool>,
+    /// set when the job ends, for callers awaiting it (`dir_size`); dropped
+    /// unset when it's cancelled
+    pub outcome: tokio::sync::watch::Sender<Option<ScanOutcome>>,
+    /// windows receiving its events; callers joining the job add theirs
+    pub audience: Audience,
+    pub extras: JobExtras,
+}
+
+#[derive(Serialize)]
+pub struct ActiveScan {
+    pub scan_key: String,
+    pub path: String,
+    pub bytes: u64,
+    /// "running" | "paused" | "yielding" (background, waiting on visible scans)
+    pub state: String,
+    /// "visible" | "background"
+    pub priority: String,
+}
+
+#[derive(Serialize)]
+pub struct Thumbnail {
+    /// Absolute path of the cached thumbnail (PNG).
+    pub path: String,
+    /// Base64 PNG bytes, only filled when the caller asked for inline data.
+    pub data: Option<String>,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum ThumbnailError {
+    NotFound { message: String },
+    DecodeFailed { message: String },
+    Io { message: String },
+    ForbiddenPath { message: String },
+}
+
+impl From<std::io::Error> for ThumbnailError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::NotFound => ThumbnailError::NotFound {
+                message: e.to_string(),
+            },
+            _ => ThumbnailError::Io {
+                message: e.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct TextPreview {
+    pub text: String,
+    /// "utf-8", "utf-8-bom", "utf-16le", "utf-16be", "latin-1" or "binary"
+    pub encoding: String,
+    pub truncated: bool,
+    pub binary: bool,
+    pub total_size: u64,
+}
+
+#[derive(Serialize)]
+pub struct FileRange {
+    /// Base64 of the raw bytes read
+    pub data: String,
+    pub offset: u64,
+    pub len: u64,
+    pub total_size: u64,
+    pub eof: bool,
+}
+
+#[derive(Serialize)]
+pub struct XattrPair {
+    pub name: String,
+    /// Base64, values may be binary
+    pub value: String,
+}
+
+#[derive(Serialize)]
+pub struct Xattrs {
+    pub attrs: Vec<XattrPair>,
+    /// Finder tags decoded from com.apple.metadata:_kMDItemUserTags (macOS only)
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct DirNode {
+    pub name: String,
+    pub path: String,
+    /// Whether the directory has (non-skipped) subdirectories, even when
+    /// they were not expanded because of the depth limit
+    pub has_children: bool,
+    /// Per-level entry cap was hit; `children` is incomplete
+    pub truncated: bool,
+    /// The directory could not be read (permission denied etc.)
+    pub denied: bool,
+    pub children: Vec<DirNode>,
+}
+
+#[derive(Serialize)]
+pub struct ResolvedPath {
+    pub path: String,
+    pub exists: bool,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    /// Closest existing parent when `path` itself doesn't exist
+    pub nearest_existing: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct DupesOptions {
+    pub show_hidden: bool,
+    pub ignores: Vec<String>,
+    /// Files smaller than this are not considered (default 1 byte: skip empties)
+    pub min_size: Option<u64>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DupeGroupEvent {
+    pub request_id: String,
+    /// Size of each file in the group
+    pub size: u64,
+    pub paths: Vec<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DupesDoneEvent {
+    pub request_id: String,
+    pub groups: u64,
+    /// size * (copies - 1) summed over all groups
+    pub reclaimable_bytes: u64,
+    pub cancelled: bool,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ArchiveEntry {
+    pub name: String,
+    /// Full path inside the archive, '/'-separated, no trailing slash
+    pub inner_path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<String>,
+    /// Listed but can't be extracted without a password
+    pub encrypted: bool,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ArchiveProgressEvent {
+    pub op_id: String,
+    pub done: u64,
+    pub total: u64,
+    pub current: String,
+}
+
+/// One trash folder: the home trash or a volume's (`.Trash-1000`, ...)
+#[derive(Serialize, Clone)]
+pub struct TrashLocation {
+    /// The folder holding the trashed items (`.../files` on Linux)
+    pub path: String,
+    pub bytes: u64,
+    /// Top-level entries, i.e. what the user trashed
+    pub items: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct TrashInfo {
+    pub bytes: u64,
+    pub items: u64,
+    pub locations: Vec<TrashLocation>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct TrashProgressEvent {
+    pub op_id: String,
+    pub done: u64,
+    pub total: u64,
+    pub current: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct TrashDoneEvent {
+    pub op_id: String,
+    pub removed: u64,
+    pub failed: u64,
+}
+
+/// Raw per-extension tally kept in the composition cache
+#[derive(Clone, Default)]
+pub struct CompositionTally {
+    /// ext (lowercase, "" for none) -> (bytes, files)
+    pub by_ext: HashMap<String, (u64, u64)>,
+    pub total_bytes: u64,
+    pub total_files: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct CompositionBucket {
+    pub key: String,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct CompositionEvent {
+    pub request_id: String,
+    pub path: String,
+    pub total_bytes: u64,
+    pub total_files: u64,
+    /// Top-N extensions, plus an "other" bucket for the rest
+    pub by_extension: Vec<CompositionBucket>,
+    /// images / video / audio / code / archives / documents / other
+    pub by_category: Vec<CompositionBucket>,
+    pub done: bool,
+}
+
+#[derive(Serialize, Clone)]
+pub struct SelectionSizeEvent {
+    pub request_id: String,
+    pub bytes: u64,
+    /// Files counted so far (selected files + files inside selected folders)
+    pub items: u64,
+    /// Folders still being walked
+    pub scanning: u64,
+    pub cancelled: bool,
+}
+
+/// What a failed command rejects with, as `{ kind, paths, os_code, message }`.
+/// The UI picks dialogs by `kind`; `message` is for showing, not parsing.
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ApiError {
+    NotFound(ErrorDetail),
+    PermissionDenied(ErrorDetail),
+    AlreadyExists(ErrorDetail),
+    CrossDevice(ErrorDetail),
+    Cancelled(ErrorDetail),
+    InvalidInput(ErrorDetail),
+    Io(ErrorDetail),
+    Unsupported(ErrorDetail),
+    /// the feature doesn't exist on this OS (xattrs on Windows, ...)
+    UnsupportedPlatform(ErrorDetail),
+    /// outside the allowed roots (see `set_allowed_roots`)
+    ForbiddenPath(ErrorDetail),
+    /// a `list_dir` snapshot id that timed out; list again without it
+    SnapshotExpired(ErrorDetail),
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ErrorDetail {
+    /// the path(s) the failing operation was working on, when known
+    pub paths: Vec<String>,
+    /// raw OS error (errno / Win32 code) when the error came from the OS
+    pub os_code: Option<i32>,
+    pub message: String,
+}
+
+impl ErrorDetail {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl ApiError {
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        ApiError::InvalidInput(ErrorDetail::new(message))
+    }
+
+    pub fn forbidden(path: impl AsRef<std::path::Path>) -> Self {
+        ApiError::ForbiddenPath(ErrorDetail::new("outside the allowed folders")).with_path(path)
+    }
+
+    pub fn already_exists(path: impl AsRef<std::path::Path>) -> Self {
+        ApiError::AlreadyExists(ErrorDetail::new("already exists")).with_path(path)
+    }
+
+    pub fn unsupported(message: impl Into<String>) -> Self {
+        ApiError::Unsupported(ErrorDetail::new(message))
+    }
+
+    pub fn unsupported_platform(message: impl Into<String>) -> Self {
+        ApiError::UnsupportedPlatform(ErrorDetail::new(message))
+    }
+
+    pub fn snapshot_expired(id: &str) -> Self {
+        ApiError::SnapshotExpired(ErrorDetail::new(format!("snapshot expired: {}", id)))
+    }
+
+    pub fn cancelled() -> Self {
+        ApiError::Cancelled(ErrorDetail::new("cancelled"))
+    }
+
+    pub fn detail(&self) -> &ErrorDetail {
+        match self {
+            ApiError::NotFound(d)
+            | ApiError::PermissionDenied(d)
+            | ApiError::AlreadyExists(d)
+            | ApiError::CrossDevice(d)
+            | ApiError::Cancelled(d)
+            | ApiError::InvalidInput(d)
+            | ApiError::Io(d)
+            | ApiError::Unsupported(d)
+            | ApiError::UnsupportedPlatform(d)
+            | ApiError::ForbiddenPath(d)
+            | ApiError::SnapshotExpired(d) => d,
+        }
+    }
+
+    fn detail_mut(&mut self) -> &mut ErrorDetail {
+        match self {
+            ApiError::NotFound(d)
+            | ApiError::PermissionDenied(d)
+            | ApiError::AlreadyExists(d)
+            | ApiError::CrossDevice(d)
+            | ApiError::Cancelled(d)
+            | ApiError::InvalidInput(d)
+            | ApiError::Io(d)
+            | ApiError::Unsupported(d)
+            | ApiError::UnsupportedPlatform(d)
+            | ApiError::ForbiddenPath(d)
+            | ApiError::SnapshotExpired(d) => d,
+        }
+    }
+
+    /// Record a path the error is about (no-op if already there)
+    pub fn with_path(mut self, path: impl AsRef<std::path::Path>) -> Self {
+        let p = path.as_ref().to_string_lossy().to_string();
+        let paths = &mut self.detail_mut().paths;
+        if !paths.contains(&p) {
+            paths.push(p);
+        }
+        self
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.detail().message)
+    }
+}
+
+/// EXDEV / ERROR_NOT_SAME_DEVICE, for platforms where the kind isn't mapped
+#[cfg(unix)]
+const CROSS_DEVICE_CODE: i32 = libc::EXDEV;
+#[cfg(windows)]
+const CROSS_DEVICE_CODE: i32 = 17;
+#[cfg(not(any(unix, windows)))]
+const CROSS_DEVICE_CODE: i32 = -1;
+
+impl From<std::io::Error> for ApiError {
+    fn from(e: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+
+        let make: fn(ErrorDetail) -> ApiError = match e.kind() {
+            ErrorKind::NotFound => ApiError::NotFound,
+            ErrorKind::PermissionDenied | ErrorKind::ReadOnlyFilesystem => {
+                ApiError::PermissionDenied
+            }
+            ErrorKind::AlreadyExists | ErrorKind::DirectoryNotEmpty => ApiError::AlreadyExists,
+            ErrorKind::CrossesDevices => ApiError::CrossDevice,
+            _ if e.raw_os_error() == Some(CROSS_DEVICE_CODE) => ApiError::CrossDevice,
+            ErrorKind::InvalidInput
+            | ErrorKind::InvalidFilename
+            | ErrorKind::NotADirectory
+            | ErrorKind::IsADirectory => ApiError::InvalidInput,
+            ErrorKind::Unsupported => ApiError::Unsupported,
+            _ => ApiError::Io,
+        };
+        make(ErrorDetail {
+            paths: Vec::new(),
+            os_code: e.raw_os_error(),
+            message: e.to_string(),
+        })
+    }
+}
+
+impl From<fs_extra::error::Error> for ApiError {
+    fn from(e: fs_extra::error::Error) -> Self {
+        use fs_extra::error::ErrorKind;
+
+        let message = e.to_string();
+        let make: fn(ErrorDetail) -> ApiError = match e.kind {
+            ErrorKind::Io(io) => return io.into(),
+            ErrorKind::NotFound => ApiError::NotFound,
+            ErrorKind::PermissionDenied => ApiError::PermissionDenied,
+            ErrorKind::AlreadyExists => ApiError::AlreadyExists,
+            // the copy was aborted from its progress handler
+            ErrorKind::Interrupted => ApiError::Cancelled,
+            ErrorKind::InvalidFolder
+            | ErrorKind::InvalidFile
+            | ErrorKind::InvalidFileName
+            | ErrorKind::InvalidPath => ApiError::InvalidInput,
+            _ => ApiError::Io,
+        };
+        make(ErrorDetail::new(message))
+    }
+}
+
+/// Internal failures that only carry text (a poisoned lock, a task that died)
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        ApiError::Io(ErrorDetail::new(message))
+    }
+}
+
+/// For code still returning `Result<_, String>`
+impl From<ApiError> for String {
+    fn from(e: ApiError) -> Self {
+        e.to_string()
+    }
+}
+
+/// One JSON line of the log
+#[derive(Serialize, Clone)]
+pub struct LogEntry {
+    pub ts: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub op_id: Option<String>,
+    /// any other fields of the event
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, serde_json::Value>,
 }
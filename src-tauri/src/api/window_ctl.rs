@@ -0,0 +1,166 @@
+// src/api/window_ctl.rs
+use std::{collections::HashMap, sync::Mutex};
+
+use once_cell::sync::Lazy;
+use tauri::{AppHandle, LogicalSize, Manager, PhysicalSize, WebviewWindow};
+
+use crate::api::{
+    settings::{self, SettingsState},
+    types::{ApiError, SettingsPatch, WindowState},
+};
+
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 3.0;
+const COMPACT_SIZE: (f64, f64) = (360.0, 480.0);
+
+/// Zoom per window label; webviews can't be asked for theirs
+static ZOOM: Lazy<Mutex<HashMap<String, f64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// What a compact window goes back to
+struct BeforeCompact {
+    size: PhysicalSize<u32>,
+    decorated: bool,
+}
+
+static COMPACT: Lazy<Mutex<HashMap<String, BeforeCompact>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Apply the saved zoom to the windows open at startup
+pub fn init(app: &AppHandle) {
+    let zoom = settings::current(app).zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    for win in app.webview_windows().values() {
+        remember_zoom(win, zoom);
+        if zoom != 1.0 {
+            let _ = win.set_zoom(zoom);
+        }
+    }
+}
+
+fn remember_zoom(win: &WebviewWindow, zoom: f64) {
+    if let Ok(mut z) = ZOOM.lock() {
+        z.insert(win.label().to_string(), zoom);
+    }
+}
+
+fn zoom_of(win: &WebviewWindow) -> f64 {
+    let known = ZOOM.lock().ok().and_then(|z| z.get(win.label()).copied());
+    known.unwrap_or_else(|| settings::current(win.app_handle()).zoom)
+}
+
+fn state(win: &WebviewWindow) -> Result<WindowState, ApiError> {
+    let scale = win.scale_factor().map_err(|e| e.to_string())?;
+    let size: LogicalSize<f64> = win
+        .inner_size()
+        .map_err(|e| e.to_string())?
+        .to_logical(scale);
+    Ok(WindowState {
+        zoom: zoom_of(win),
+        always_on_top: win.is_always_on_top().map_err(|e| e.to_string())?,
+        fullscreen: win.is_fullscreen().map_err(|e| e.to_string())?,
+        compact: COMPACT
+            .lock()
+            .map(|c| c.contains_key(win.label()))
+            .unwrap_or(false),
+        decorated: win.is_decorated().map_err(|e| e.to_string())?,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+#[tauri::command]
+pub fn get_window_state(window: WebviewWindow) -> Result<WindowState, ApiError> {
+    state(&window)
+}
+
+/// Page zoom of the calling window, clamped to 0.5-3; saved as the zoom
+/// every window starts with
+#[tauri::command]
+pub fn set_zoom(
+    window: WebviewWindow,
+    settings_state: tauri::State<'_, SettingsState>,
+    factor: f64,
+) -> Result<WindowState, ApiError> {
+    if !factor.is_finite() {
+        return Err(ApiError::invalid_input(format!("bad zoom: {}", factor)));
+    }
+    let zoom = factor.clamp(MIN_ZOOM, MAX_ZOOM);
+    window.set_zoom(zoom).map_err(|e| e.to_string())?;
+    remember_zoom(&window, zoom);
+    settings::update(
+        window.app_handle(),
+        &settings_state,
+        SettingsPatch {
+            zoom: Some(zoom),
+            ..Default::default()
+        },
+    )?;
+    state(&window)
+}
+
+#[tauri::command]
+pub fn get_zoom(window: WebviewWindow) -> f64 {
+    zoom_of(&window)
+}
+
+#[tauri::command]
+pub fn set_always_on_top(window: WebviewWindow, on_top: bool) -> Result<WindowState, ApiError> {
+    window
+        .set_always_on_top(on_top)
+        .map_err(|e| e.to_string())?;
+    state(&window)
+}
+
+#[tauri::command]
+pub fn toggle_fullscreen(window: WebviewWindow) -> Result<WindowState, ApiError> {
+    let fullscreen = window.is_fullscreen().map_err(|e| e.to_string())?;
+    window
+        .set_fullscreen(!fullscreen)
+        .map_err(|e| e.to_string())?;
+    state(&window)
+}
+
+/// Shrink the calling window to `width` x `height` (logical, default 360x480),
+/// without decorations unless `keep_decorations`; turning it off restores the
+/// size and decorations it had before
+#[tauri::command]
+pub fn set_compact(
+    window: WebviewWindow,
+    compact: bool,
+    width: Option<f64>,
+    height: Option<f64>,
+    keep_decorations: Option<bool>,
+) -> Result<WindowState, ApiError> {
+    let label = window.label().to_string();
+    let saved = COMPACT.lock().map_err(|e| e.to_string())?.remove(&label);
+    if compact {
+        // entering again keeps the size from before the first time
+        let before = match saved {
+            Some(before) => before,
+            None => BeforeCompact {
+                size: window.inner_size().map_err(|e| e.to_string())?,
+                decorated: window.is_decorated().map_err(|e| e.to_string())?,
+            },
+        };
+        if window.is_fullscreen().map_err(|e| e.to_string())? {
+            window.set_fullscreen(false).map_err(|e| e.to_string())?;
+        }
+        window
+            .set_decorations(keep_decorations.unwrap_or(false))
+            .map_err(|e| e.to_string())?;
+        window
+            .set_size(LogicalSize::new(
+                width.unwrap_or(COMPACT_SIZE.0),
+                height.unwrap_or(COMPACT_SIZE.1),
+            ))
+            .map_err(|e| e.to_string())?;
+        COMPACT
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(label, before);
+    } else if let Some(before) = saved {
+        window
+            .set_decorations(before.decorated)
+            .map_err(|e| e.to_string())?;
+        window.set_size(before.size).map_err(|e| e.to_string())?;
+    }
+    state(&window)
+}
@@ -0,0 +1,151 @@
+// src/api/xattrs.rs
+#[cfg(unix)]
+use crate::api::types::XattrPair;
+use crate::api::{
+    sandbox,
+    types::{ApiError, Xattrs},
+};
+#[cfg(unix)]
+use base64::Engine;
+use std::path::Path;
+use tauri::AppHandle;
+
+#[cfg(target_os = "macos")]
+const TAGS_ATTR: &str = "com.apple.metadata:_kMDItemUserTags";
+/// errno when the attribute doesn't exist (macOS ENOATTR)
+#[cfg(target_os = "macos")]
+const ENOATTR: i32 = 93;
+
+#[cfg(not(target_os = "macos"))]
+fn unsupported(what: &str) -> ApiError {
+    ApiError::unsupported_platform(format!("{} aren't supported on this platform", what))
+}
+
+/// Finder stores tags as "Name\n<color index>"; the UI only wants the name.
+#[cfg(target_os = "macos")]
+fn decode_tags(raw: &[u8]) -> Vec<String> {
+    plist::from_bytes::<Vec<String>>(raw)
+        .map(|tags| {
+            tags.into_iter()
+                .map(|t| t.split('\n').next().unwrap_or_default().to_string())
+                .filter(|t| !t.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn read_all(path: &Path) -> Result<Xattrs, ApiError> {
+    let mut attrs = Vec::new();
+    #[allow(unused_mut)]
+    let mut tags = Vec::new();
+    let failed = |e: std::io::Error| ApiError::from(e).with_path(path);
+
+    for name in xattr::list(path).map_err(failed)? {
+        let Some(value) = xattr::get(path, &name).map_err(failed)? else {
+            continue;
+        };
+        let name = name.to_string_lossy().to_string();
+        #[cfg(target_os = "macos")]
+        if name == TAGS_ATTR {
+            tags = decode_tags(&value);
+        }
+        attrs.push(XattrPair {
+            name,
+            value: base64::engine::general_purpose::STANDARD.encode(&value),
+        });
+    }
+    Ok(Xattrs { attrs, tags })
+}
+
+/// Copy every xattr of `src` onto `dst` (not recursive). Best effort: the
+/// target fs may not support xattrs at all.
+#[cfg(unix)]
+pub(crate) fn copy_xattrs(src: &Path, dst: &Path) {
+    let Ok(names) = xattr::list(src) else {
+        return;
+    };
+    for name in names {
+        if let Ok(Some(value)) = xattr::get(src, &name) {
+            let _ = xattr::set(dst, &name, &value);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn copy_xattrs(_src: &Path, _dst: &Path) {}
+
+#[tauri::command]
+pub fn get_xattrs(app: AppHandle, path: String) -> Result<Xattrs, ApiError> {
+    sandbox::check(&app, &path)?;
+    #[cfg(unix)]
+    {
+        read_all(Path::new(&path))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Err(unsupported("extended attributes"))
+    }
+}
+
+/// `value` is base64, same as what get_xattrs returns
+#[tauri::command]
+pub fn set_xattr(
+    app: AppHandle,
+    path: String,
+    name: String,
+    value: String,
+) -> Result<(), ApiError> {
+    sandbox::check(&app, &path)?;
+    #[cfg(unix)]
+    {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(value)
+            .map_err(|e| ApiError::invalid_input(format!("value isn't base64: {}", e)))?;
+        xattr::set(&path, &name, &bytes).map_err(|e| ApiError::from(e).with_path(&path))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (path, name, value);
+        Err(unsupported("extended attributes"))
+    }
+}
+
+#[tauri::command]
+pub fn remove_xattr(app: AppHandle, path: String, name: String) -> Result<(), ApiError> {
+    sandbox::check(&app, &path)?;
+    #[cfg(unix)]
+    {
+        xattr::remove(&path, &name).map_err(|e| ApiError::from(e).with_path(&path))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (path, name);
+        Err(unsupported("extended attributes"))
+    }
+}
+
+/// Replace the Finder tags of `path`. An empty list removes the attribute.
+#[tauri::command]
+pub fn set_tags(app: AppHandle, path: String, tags: Vec<String>) -> Result<(), ApiError> {
+    sandbox::check(&app, &path)?;
+    #[cfg(target_os = "macos")]
+    {
+        let failed = |e: std::io::Error| ApiError::from(e).with_path(&path);
+        if tags.is_empty() {
+            return match xattr::remove(&path, TAGS_ATTR) {
+                Err(e) if e.raw_os_error() != Some(ENOATTR) => Err(failed(e)),
+                _ => Ok(()),
+            };
+        }
+        let mut buf = Vec::new();
+        plist::to_writer_binary(&mut buf, &tags).map_err(|e| e.to_string())?;
+        xattr::set(&path, TAGS_ATTR, &buf).map_err(failed)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (path, tags);
+        Err(unsupported("Finder tags"))
+    }
+}
@@ -3,19 +3,104 @@
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(api::storage::init())
+        .manage(api::sandbox::AllowedRoots::default())
+        .manage(api::transfer::TransferState::default())
+        .setup(|app| {
+            api::logs::init(app.handle());
+            api::settings::init(app.handle());
+            api::bookmarks::init(app.handle());
+            api::history::init(app.handle());
+            api::window_ctl::init(app.handle());
+            api::path_sizer::persist::init(app.handle());
+            api::path_sizer::watch::init(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             api::fs_list::list_dir,
+            api::fs_tree::list_dir_tree,
+            api::fs_tree::cancel_dir_tree,
+            api::fs_path::resolve_path,
             api::fs_ops::copy_paths,
             api::fs_ops::move_paths,
             api::fs_ops::delete_paths,
             api::fs_ops::rename_path,
+            api::transfer::set_pending_transfer,
+            api::transfer::get_pending_transfer,
+            api::transfer::clear_pending_transfer,
+            api::transfer::paste_pending,
             api::devtools::toggle_devtools,
+            api::dupes::find_duplicates,
+            api::dupes::cancel_find_duplicates,
             api::path_sizer::get_cached_sizes,
+            api::path_sizer::get_or_ensure_sizes,
+            api::path_sizer::auto_invalidate,
+            api::path_sizer::watch_sizes,
+            api::path_sizer::unwatch_sizes,
+            api::path_sizer::invalidate_size_cache,
+            api::path_sizer::get_top_entries,
+            api::path_sizer::get_cache_stats,
+            api::path_sizer::set_size_cache_limit,
+            api::path_sizer::clear_persistent_size_cache,
             api::path_sizer::ensure_path_sizer,
+            api::path_sizer::analyze_volume,
+            api::path_sizer::dir_size,
+            api::path_sizer::cancel_path_sizer,
+            api::path_sizer::cancel_scans_under,
+            api::path_sizer::cancel_all_scans,
+            api::path_sizer::set_sizer_concurrency,
+            api::path_sizer::pause_path_sizer,
+            api::path_sizer::resume_path_sizer,
+            api::path_sizer::pause_all_scans,
+            api::path_sizer::resume_all_scans,
+            api::path_sizer::promote_scan,
+            api::path_sizer::list_active_scans,
+            api::path_sizer::size_of_selection,
+            api::path_sizer::cancel_selection_size,
+            api::path_sizer::folder_composition,
+            api::path_sizer::cancel_folder_composition,
+            api::thumbnails::get_thumbnail,
+            api::thumbnails::clear_thumbnail_cache,
+            api::trash::get_trash_info,
+            api::trash::empty_trash,
+            api::preview::read_text_preview,
+            api::preview::read_file_range,
+            api::xattrs::get_xattrs,
+            api::xattrs::set_xattr,
+            api::xattrs::remove_xattr,
+            api::xattrs::set_tags,
+            api::archive::list_archive,
+            api::archive::extract_archive_entries,
+            api::settings::get_settings,
+            api::settings::update_settings,
+            api::logs::get_recent_logs,
+            api::logs::set_log_level,
+            api::sandbox::set_allowed_roots,
+            api::sandbox::get_allowed_roots,
+            api::bookmarks::list_bookmarks,
+            api::bookmarks::add_bookmark,
+            api::bookmarks::remove_bookmark,
+            api::bookmarks::reorder_bookmarks,
+            api::history::record_visit,
+            api::history::get_recent_locations,
+            api::history::get_frequent_locations,
+            api::history::clear_history,
+            api::storage::request_storage_access,
+            api::window_ctl::get_window_state,
+            api::window_ctl::set_zoom,
+            api::window_ctl::get_zoom,
+            api::window_ctl::set_always_on_top,
+            api::window_ctl::toggle_fullscreen,
+            api::window_ctl::set_compact,
             api::app::exit
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                api::path_sizer::persist::flush();
+            }
+        });
 }
 
 mod api;
@@ -13,6 +13,7 @@ import { useCommander } from "../store/CommanderContext";
 import { PaneId } from "../types/PaneTypes";
 import useRename from "../hooks/useRename";
 import { renamePath } from "../utils/fsOps";
+import { isApiError } from "../types/ApiError";
 import { join } from "@tauri-apps/api/path";
 
 type Props = { id: PaneId };
@@ -86,7 +87,9 @@ export default function Pane({ id }: Props) {
             }, 0);
         } catch (e) {
             console.error(e);
-            alert("Rename failed");
+            alert(isApiError(e) && e.kind === "already_exists"
+                ? `Rename failed: "${newBase}" already exists`
+                : "Rename failed");
         } finally {
             cancelRename();
         }
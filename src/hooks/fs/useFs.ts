@@ -1,12 +1,13 @@
 import { useCallback, useEffect, useMemo, useRef, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { homeDir, join } from "@tauri-apps/api/path";
-import { listen, type UnlistenFn } from "@tauri-apps/api/event";
-import type { FileEntry } from "../../types/FileEntry";
+import { type UnlistenFn } from "@tauri-apps/api/event";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
+import type { DirListing } from "../../types/FileEntry";
 import type { RowType } from "../../types/RowType";
 import { compare, fmtSize, getDate, getTime } from "../../utils/fileDataHelpers";
 import { PaneView } from "../../types/PaneTypes";
-import type { PathSizerChildEvent, PathSizerProgressEvent, PathSizerSummaryEvent } from "../../types/PathSizerTypes";
+import type { CachedSize, PathSizerChildEvent, PathSizerErrorEvent, PathSizerProgressEvent, PathSizerSummaryEvent } from "../../types/PathSizerTypes";
 import useFsHookStart from "./useFsHookStart";
 import { goUpNav, openEntryNav } from "../../utils/fsNav";
 
@@ -31,10 +32,12 @@ export default function useFs(view?: PaneView) {
 
     useEffect(() => {
         let unsubs: UnlistenFn[] = [];
+        // scan events are sent to the window that asked for the scan
+        const win = getCurrentWebviewWindow();
         (async () => {
             // PROGRESS (batched + monotonic)
             unsubs.push(
-                await listen<PathSizerProgressEvent | any>("dir_size:progress", (evt) => {
+                await win.listen<PathSizerProgressEvent | any>("dir_size:progress", (evt) => {
                     const p = evt.payload as any;
                     if (!p || p.scan_key !== currentScanKeyRef.current) return;
                     if (!scanningDirsRef.current.has(p.name)) return;
@@ -59,7 +62,7 @@ export default function useFs(view?: PaneView) {
 
             // CHILD (final for a subdir) — immediate, monotonic
             unsubs.push(
-                await listen<PathSizerChildEvent | any>("dir_size:child", (evt) => {
+                await win.listen<PathSizerChildEvent | any>("dir_size:child", (evt) => {
                     const p = evt.payload as any;
                     if (!p || p.scan_key !== currentScanKeyRef.current) return;
 
@@ -92,7 +95,7 @@ export default function useFs(view?: PaneView) {
 
             // SUMMARY (final for current folder)
             unsubs.push(
-                await listen<PathSizerSummaryEvent | any>("dir_size:summary", (evt) => {
+                await win.listen<PathSizerSummaryEvent | any>("dir_size:summary", (evt) => {
                     const p = evt.payload as any;
                     if (!p || p.scan_key !== currentScanKeyRef.current) return;
 
@@ -116,6 +119,27 @@ export default function useFs(view?: PaneView) {
                 })
             );
 
+            // ERROR: the folder itself couldn't be sized; no summary will come
+            unsubs.push(
+                await win.listen<PathSizerErrorEvent>("dir_size:error", (evt) => {
+                    const p = evt.payload;
+                    if (!p || p.scan_key !== currentScanKeyRef.current) return;
+
+                    pendingProgressRef.current.clear();
+                    if (flushTimerRef.current) { clearTimeout(flushTimerRef.current); flushTimerRef.current = null; }
+                    const unsized = new Set(scanningDirsRef.current);
+                    scanningDirsRef.current.clear();
+
+                    setRows(prev =>
+                        prev.map(r =>
+                            r?.isDir && r.realName && unsized.has(r.realName)
+                                ? { ...r, size: "size unavailable" }
+                                : r
+                        )
+                    );
+                })
+            );
+
             eventsReadyResolveRef.current?.();
         })().catch(console.error);
 
@@ -137,7 +161,7 @@ export default function useFs(view?: PaneView) {
         currentScanKeyRef.current = scanKey;
 
         // read dir
-        const entries = (await invoke<FileEntry[]>("list_dir", { path: p })) ?? [];
+        const entries = (await invoke<DirListing>("list_dir", { path: p }))?.entries ?? [];
         const filtered = v.showHidden ? entries : entries.filter(e => !e.name.startsWith("."));
         const sorted = [...filtered].sort((a, b) => compare(a, b, view));
 
@@ -147,9 +171,9 @@ export default function useFs(view?: PaneView) {
         // Prefill from cache
         const childDirs = sorted.filter(e => e.is_dir);
         const childFullPaths = await Promise.all(childDirs.map(e => join(p, e.name)));
-        let cached: Array<null | [number, number, boolean]> = [];
+        let cached: Array<null | CachedSize> = [];
         try {
-            cached = await invoke<Array<null | [number, number, boolean]>>("get_cached_sizes", {
+            cached = await invoke<Array<null | CachedSize>>("get_cached_sizes", {
                 paths: childFullPaths,
                 show_hidden: !!v.showHidden,
                 showHidden: !!v.showHidden, // harmless extra param
@@ -195,8 +219,8 @@ export default function useFs(view?: PaneView) {
             let completed = false;
 
             if (maybe) {
-                initialBytes = maybe[0] ?? 0;
-                completed = !!maybe[2];
+                initialBytes = maybe.bytes ?? 0;
+                completed = !!maybe.completed;
             }
 
             knownDirBytesRef.current.set(e.name, initialBytes);
@@ -241,7 +265,7 @@ export default function useFs(view?: PaneView) {
         if (needsScan) {
             try {
                 const jobId = `ps-${Date.now()}-${Math.random().toString(36).slice(2)}`;
-                await invoke("ensure_path_sizer", {
+                const backendKey = await invoke<string>("ensure_path_sizer", {
                     path: p,
                     jobId,
                     job_id: jobId,
@@ -249,6 +273,8 @@ export default function useFs(view?: PaneView) {
                     showHidden: !!v.showHidden,
                     ignores: ignores as string[],
                 });
+                // backend normalizes the path in its key (trailing slash, case on Windows)
+                if (token === loadSeqRef.current && backendKey) currentScanKeyRef.current = backendKey;
             } catch (e) {
                 console.error("ensure_path_sizer failed:", e);
                 scanningDirsRef.current.clear();
@@ -6,7 +6,7 @@ import { CommanderProvider } from "./store/CommanderContext";
 import { invoke } from "@tauri-apps/api/core";
 
 if (import.meta.env.VITE_DEVMODE! === "dev") {
-  await invoke("toggle_devtools");
+  await invoke<boolean>("toggle_devtools").catch(console.warn);
 }
 
 
@@ -0,0 +1,23 @@
+// What a rejected invoke() carries from commands returning ApiError
+export type ApiErrorKind =
+    | "not_found"
+    | "permission_denied"
+    | "already_exists"
+    | "cross_device"
+    | "cancelled"
+    | "invalid_input"
+    | "io"
+    | "unsupported"
+    | "unsupported_platform" // the feature doesn't exist on this OS
+    | "forbidden_path"    // outside the roots set with set_allowed_roots
+    | "snapshot_expired"; // list_dir snapshot_id timed out; list again without it
+
+export type ApiError = {
+    kind: ApiErrorKind;
+    paths: string[];        // path(s) the failing operation was working on
+    os_code: number | null; // raw errno / Win32 code, when the OS reported it
+    message: string;        // for display only; branch on `kind`
+};
+
+export const isApiError = (e: unknown): e is ApiError =>
+    typeof e === "object" && e !== null && "kind" in e && "message" in e;
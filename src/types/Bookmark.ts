@@ -0,0 +1,10 @@
+// list_bookmarks and the bookmark edits return the whole list, as does `bookmarks:changed`
+export type Bookmark = {
+    id: string;
+    path: string;       // as entered
+    canonical: string;  // one bookmark per canonical path
+    label: string;
+    icon: "home" | "drive" | "folder" | "file";
+    created_at: string; // RFC 3339
+    missing: boolean;   // the path no longer exists
+};
@@ -3,4 +3,19 @@ export type FileEntry = {
     is_dir: boolean;
     size: number;
     modified?: string | null; // "YYYY-MM-DD HH:MM"
+    child_count?: number | null; // only with includeChildCounts
+    child_count_approx?: boolean; // true => count is ">= child_count"
+    mode_octal?: number | null; // unix only
+    mode_string?: string | null; // "drwxr-xr-x", unix only
+    owner?: string | null;
+    group?: string | null;
+    executable?: boolean;
+};
+
+export type DirListing = {
+    entries: FileEntry[];
+    total: number; // matching entries before offset/limit
+    offset: number;
+    sort: { key: string; dir: string; dirs_first: boolean };
+    snapshot_id?: string | null;
 };
@@ -0,0 +1,7 @@
+// get_recent_locations / get_frequent_locations
+export type LocationVisit = {
+    path: string;
+    visits: number;
+    last_visit: number; // ms since the epoch
+    score: number;      // frecency: visits, weighted down as the last one ages
+};
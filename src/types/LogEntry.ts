@@ -0,0 +1,12 @@
+// get_recent_logs; the log file holds the same objects as JSON lines
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace";
+
+export type LogEntry = {
+    ts: string; // RFC 3339, local time
+    level: LogLevel;
+    target: string; // backend module, e.g. "nc_lib::api::fs_ops"
+    message: string;
+    path?: string;
+    op_id?: string;
+    fields?: Record<string, unknown>;
+};
@@ -2,19 +2,155 @@ export type PathSizerChildEvent = {
     path: string;   // root folder being sized
     name: string;   // immediate child dir name
     bytes: number;  // recursive size of that child
-    items?: number; // optional: immediate children count (if backend sends it)
+    items?: number; // files + dirs below that child
+    files?: number;
+    dirs?: number;
+    other_device?: boolean; // mount point skipped by same_device (bytes is 0)
+    junction?: boolean;     // Windows junction left out (bytes is 0)
+    errors?: number;        // entries below that couldn't be read
     job_id: string;
+    scan_key?: string;
+    echo_path?: string;     // root as spelled by the call that started the scan
 };
 
 export type PathSizerSummaryEvent = {
     path: string;   // root folder being sized
     bytes: number;  // total for root (files + all child dirs)
-    items?: number; // optional: total immediate children
+    items?: number; // files + dirs below root (root-level files included)
+    files?: number;
+    dirs?: number;
+    skipped_mounts?: string[]; // mount points left out by same_device
+    estimated?: boolean;       // max_depth scan: bytes is a lower bound
+    unreadable?: number;       // broken links skipped with follow_symlinks
+    junctions?: number;        // Windows junctions left out (no follow_junctions)
+    errors?: number;           // unreadable entries; see dir_size:errors
+    elapsed_ms?: number;
+    bytes_per_sec?: number;    // average; bytes walked, cache hits left out
     job_id: string;
+    scan_key?: string;
+    echo_path?: string;
 };
 
 export type PathSizerProgressEvent = {
     job_id: String,
     bytes: number,
     name: String,
+    items?: number,
+    files?: number,
+    dirs?: number,
+    scan_key?: string,
+    echo_path?: string,
+    expected_bytes?: number | null, // estimate_total: whole scan's expected size
+    expected_items?: number | null, // null: no estimate (off, or ran out of time)
+    bytes_per_sec?: number,         // this child's recent walk rate; 0 while unknown
+    eta_ms?: number | null,         // child's (prior size) or else whole scan's time left
+};
+
+export type PathSizerInvalidatedEvent = {
+    paths: string[]; // canonical paths whose cached sizes were dropped
+};
+
+// auto_invalidate: folders changed on disk; their cached sizes are now partial
+export type PathSizerStaleEvent = {
+    paths: string[];
+};
+
+export type CachedSize = {
+    bytes: number;
+    items: number;
+    completed: boolean;
+    age_ms: number;       // time since the entry was written
+    approximate: boolean; // same path, other show_hidden/ignores options
+    estimated?: boolean;  // written by a depth-limited scan
+    errors?: number;
+    complete_with_errors?: boolean; // finished, but some entries couldn't be read
+};
+
+// get_or_ensure_sizes: sizes line up with `paths`
+export type PathSizerSizesReply = {
+    sizes: Array<CachedSize | null>;
+    scan_key: string | null; // scan started/joined for the misses; null if none needed
+};
+
+export type PathSizerTopEntry = {
+    path: string;
+    bytes: number;
+    is_dir: boolean;
+};
+
+export type PathSizerTopEvent = {
+    job_id: string;
+    scan_key: string;
+    echo_path: string;
+    entries: PathSizerTopEntry[]; // largest first, at most top_n
+};
+
+export type PathSizerErrorsEvent = {
+    job_id: string;
+    scan_key: string;
+    echo_path: string;
+    count: number;
+    paths: string[]; // first few unreadable paths
+};
+
+export type PathSizerErrorEvent = {
+    job_id: string;
+    scan_key: string;
+    echo_path: string;
+    echo_path: string;
+    code: "not_found" | "permission_denied" | "not_a_directory" | "io";
+    message: string; // the scan root itself couldn't be read
+};
+
+export type ActiveScan = {
+    scan_key: string;
+    path: string;
+    bytes: number; // counted so far
+    state: "running" | "paused" | "yielding"; // yielding: background, waiting on visible scans
+    priority: "visible" | "background";
+};
+
+// batched variants, sent instead of the single events with `batch_events: true`
+export type PathSizerChildrenEvent = {
+    job_id: string;
+    scan_key: string;
+    echo_path: string;
+    children: {
+        name: string;
+        bytes: number;
+        items: number;
+        files: number;
+        dirs: number;
+        other_device: boolean;
+        junction: boolean;
+        errors: number;
+    }[];
+};
+
+export type PathSizerProgressBatchEvent = {
+    job_id: string;
+    scan_key: string;
+    echo_path: string;
+    progress: {
+        name: string;
+        bytes: number;
+        items: number;
+        files: number;
+        dirs: number;
+        bytes_per_sec: number;
+        eta_ms: number | null;
+    }[];
+    expected_bytes: number | null;
+    expected_items: number | null;
+};
+
+// analyze_volume: sent after the summary
+export type PathSizerVolumeEvent = {
+    job_id: string;
+    scan_key: string;
+    echo_path: string;
+    total_bytes: number;
+    used_bytes: number;    // as the filesystem reports it
+    scanned_bytes: number; // what the scan found (the summary's bytes)
+    other_bytes: number;   // used but not found: system/unreadable/"other"
 };
@@ -0,0 +1,16 @@
+// get_settings / update_settings; `settings:changed` carries the whole object
+export type Settings = {
+    ignores: string[];
+    show_hidden: boolean;
+    sizer_concurrency: number; // 1-32; 0 = pick per volume
+    size_mode: "apparent" | "disk_usage";
+    confirm_delete: boolean;
+    delete_to_trash: boolean;
+    track_history: boolean;   // record_visit keeps a history
+    zoom: number;             // page zoom, restored on startup
+    enable_devtools: boolean; // allow devtools in release builds that have them
+    paste_conflict: "overwrite" | "skip" | "fail"; // paste_pending onto an existing name
+};
+
+// update_settings({ partial }): only the fields present change
+export type SettingsPatch = Partial<Settings>;
@@ -0,0 +1,15 @@
+import { ApiError } from "./ApiError";
+
+// get_pending_transfer / set_pending_transfer; "transfer:changed" carries it (or null)
+export type PendingTransfer = {
+    paths: string[];
+    mode: "copy" | "cut";
+    missing: string[]; // registered paths that no longer exist
+};
+
+// paste_pending: one per registered path
+export type PasteResult = {
+    path: string;
+    status: "done" | "missing" | "skipped" | "failed";
+    error?: ApiError;
+};
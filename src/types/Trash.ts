@@ -0,0 +1,27 @@
+// get_trash_info / empty_trash (with preview: what would be deleted)
+export type TrashLocation = {
+    path: string;   // folder holding the trashed items (.../files on Linux)
+    bytes: number;
+    items: number;  // top-level entries
+};
+
+export type TrashInfo = {
+    bytes: number;
+    items: number;
+    locations: TrashLocation[];
+};
+
+// "trash:progress"
+export type TrashProgressEvent = {
+    op_id: string;
+    done: number;
+    total: number;
+    current: string;
+};
+
+// "trash:done"
+export type TrashDoneEvent = {
+    op_id: string;
+    removed: number;
+    failed: number;
+};
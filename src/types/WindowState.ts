@@ -0,0 +1,10 @@
+// Returned by the window_ctl commands (set_zoom, set_compact, ...) for the calling window
+export type WindowState = {
+    zoom: number;   // 0.5-3
+    always_on_top: boolean;
+    fullscreen: boolean;
+    compact: boolean;
+    decorated: boolean;
+    width: number;  // logical size
+    height: number;
+};